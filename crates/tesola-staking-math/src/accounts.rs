@@ -0,0 +1,472 @@
+//! Decoding of raw program accounts
+//!
+//! Mirrors the Anchor layout of the program's accounts: an 8-byte
+//! discriminator followed by the Borsh-encoded fields in declaration order.
+
+use crate::emission::{Emission, EmissionEpoch, MAX_EMISSION_EPOCHS};
+use crate::locks::{LockOption, MAX_LOCK_OPTIONS};
+use crate::penalty::{PenaltyPoint, MAX_PENALTY_POINTS};
+use crate::rewards::{PoolParams, StakeSnapshot};
+use crate::solvency::{Accrual, AccrualPoint, StakeAccrual};
+use crate::tiers::{TierMultipliers, MAX_TIERS, MAX_TIER_NAME_LEN};
+use crate::vesting::{VestingConfig, VestingState};
+
+/// `StakeInfo` account discriminator
+pub const STAKE_INFO_DISCRIMINATOR: [u8; 8] = [66, 62, 68, 70, 108, 179, 183, 235];
+
+/// `PoolState` account discriminator
+pub const POOL_STATE_DISCRIMINATOR: [u8; 8] = [247, 237, 227, 245, 215, 195, 222, 70];
+
+/// `EmissionSchedule` account discriminator
+pub const EMISSION_SCHEDULE_DISCRIMINATOR: [u8; 8] = [124, 49, 254, 56, 35, 98, 181, 88];
+
+/// `TierTable` account discriminator
+pub const TIER_TABLE_DISCRIMINATOR: [u8; 8] = [214, 49, 131, 139, 225, 24, 30, 105];
+
+/// `VestingSchedule` account discriminator
+pub const VESTING_SCHEDULE_DISCRIMINATOR: [u8; 8] = [130, 200, 173, 148, 39, 75, 243, 147];
+
+/// Errors produced while decoding account data
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The data does not start with the expected account discriminator
+    WrongDiscriminator,
+    /// The data ends before all fields were read
+    TooShort,
+    /// A vector holds more entries than the program allows
+    TooManyEntries,
+}
+
+pub type DecodeResult<T> = core::result::Result<T, DecodeError>;
+
+/// Decoded `StakeInfo` account
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StakeInfoAccount {
+    pub owner: [u8; 32],
+    pub mint: [u8; 32],
+    pub staked_at: i64,
+    pub release_date: i64,
+    pub is_staked: bool,
+    pub tier: u8,
+    pub last_claim_time: i64,
+    pub staking_period: u64,
+    pub auto_compound: bool,
+    pub accumulated_compound: u64,
+    pub current_time_multiplier: u64,
+    pub last_multiplier_update: i64,
+    pub milestones_achieved: u8,
+    pub next_milestone_days: u64,
+    pub compound_frequency: u8,
+    pub last_compound_time: i64,
+    pub compound_streak: u16,
+    pub compound_streak_multiplier: u64,
+    pub reward_iou: u64,
+    pub rarity_bonus_bps: u16,
+    pub lock_boost_bps: u16,
+    pub last_transfer_time: i64,
+    pub programmable: bool,
+    pub accrual: StakeAccrual,
+}
+
+/// Decoded `PoolState` account
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PoolStateAccount {
+    pub admin: [u8; 32],
+    pub reward_rate: u64,
+    pub pause_flags: u8,
+    pub total_staked: u64,
+    pub common_multiplier: u64,
+    pub rare_multiplier: u64,
+    pub epic_multiplier: u64,
+    pub legendary_multiplier: u64,
+    pub max_nfts_per_user: u8,
+    pub time_multiplier_increment: u64,
+    pub time_multiplier_period_days: u64,
+    pub max_time_multiplier: u64,
+    pub reward_mint: [u8; 32],
+    pub reward_vault: [u8; 32],
+    pub total_liabilities: u64,
+    pub underfunded_mode: bool,
+    pub emission_schedule: [u8; 32],
+    pub collection: [u8; 32],
+    pub bump: u8,
+    pub pending_admin: [u8; 32],
+    pub change_delay: i64,
+    penalty_points: [PenaltyPoint; MAX_PENALTY_POINTS],
+    penalty_point_count: usize,
+    pub tier_table: [u8; 32],
+    pub rarity_root: [u8; 32],
+    lock_options: [LockOption; MAX_LOCK_OPTIONS],
+    lock_option_count: usize,
+    pub transfer_cooldown: i64,
+    pub vesting_enabled: bool,
+    pub vesting: VestingConfig,
+    pub reward_token_program: [u8; 32],
+    pub accrual: Accrual,
+    pub treasury: [u8; 32],
+}
+
+/// Decoded `EmissionSchedule` account
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct EmissionScheduleAccount {
+    pub pool: [u8; 32],
+    pub activated_at: i64,
+    epochs: [EmissionEpoch; MAX_EMISSION_EPOCHS],
+    epoch_count: usize,
+}
+
+/// Decoded `TierTable` account, tier names are skipped
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TierTableAccount {
+    pub pool: [u8; 32],
+    multipliers: TierMultipliers,
+}
+
+/// Decoded `VestingSchedule` account
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct VestingScheduleAccount {
+    pub pool: [u8; 32],
+    pub owner: [u8; 32],
+    pub state: VestingState,
+}
+
+impl StakeInfoAccount {
+    pub fn decode(data: &[u8]) -> DecodeResult<Self> {
+        let mut r = Reader::new(data, &STAKE_INFO_DISCRIMINATOR)?;
+        Ok(Self {
+            owner: r.pubkey()?,
+            mint: r.pubkey()?,
+            staked_at: r.i64()?,
+            release_date: r.i64()?,
+            is_staked: r.bool()?,
+            tier: r.u8()?,
+            last_claim_time: r.i64()?,
+            staking_period: r.u64()?,
+            auto_compound: r.bool()?,
+            accumulated_compound: r.u64()?,
+            current_time_multiplier: r.u64()?,
+            last_multiplier_update: r.i64()?,
+            milestones_achieved: r.u8()?,
+            next_milestone_days: r.u64()?,
+            compound_frequency: r.u8()?,
+            last_compound_time: r.i64()?,
+            compound_streak: r.u16()?,
+            compound_streak_multiplier: r.u64()?,
+            reward_iou: r.u64()?,
+            rarity_bonus_bps: r.u16()?,
+            lock_boost_bps: r.u16()?,
+            last_transfer_time: r.i64()?,
+            programmable: r.bool()?,
+            accrual: StakeAccrual {
+                weight: r.u64()?,
+                point: AccrualPoint {
+                    flat_seconds: r.i64()?,
+                    scheduled: r.u128()?,
+                },
+            },
+        })
+    }
+
+    pub fn snapshot(&self) -> StakeSnapshot {
+        StakeSnapshot {
+            staked_at: self.staked_at,
+            release_date: self.release_date,
+            last_claim_time: self.last_claim_time,
+            staking_period: self.staking_period,
+            tier: self.tier,
+            current_time_multiplier: self.current_time_multiplier,
+            compound_streak_multiplier: self.compound_streak_multiplier,
+            rarity_bonus_bps: self.rarity_bonus_bps as u64,
+            lock_boost_bps: self.lock_boost_bps as u64,
+        }
+    }
+}
+
+impl PoolStateAccount {
+    pub fn decode(data: &[u8]) -> DecodeResult<Self> {
+        let mut r = Reader::new(data, &POOL_STATE_DISCRIMINATOR)?;
+        let mut pool = Self {
+            admin: r.pubkey()?,
+            reward_rate: r.u64()?,
+            pause_flags: r.u8()?,
+            total_staked: r.u64()?,
+            common_multiplier: r.u64()?,
+            rare_multiplier: r.u64()?,
+            epic_multiplier: r.u64()?,
+            legendary_multiplier: r.u64()?,
+            max_nfts_per_user: r.u8()?,
+            time_multiplier_increment: r.u64()?,
+            time_multiplier_period_days: r.u64()?,
+            max_time_multiplier: r.u64()?,
+            reward_mint: r.pubkey()?,
+            reward_vault: r.pubkey()?,
+            total_liabilities: r.u64()?,
+            underfunded_mode: r.bool()?,
+            emission_schedule: r.pubkey()?,
+            collection: r.pubkey()?,
+            bump: r.u8()?,
+            pending_admin: r.pubkey()?,
+            change_delay: r.i64()?,
+            ..Self::default()
+        };
+
+        pool.penalty_point_count = r.u32()? as usize;
+        if pool.penalty_point_count > MAX_PENALTY_POINTS {
+            return Err(DecodeError::TooManyEntries);
+        }
+        for point in pool
+            .penalty_points
+            .iter_mut()
+            .take(pool.penalty_point_count)
+        {
+            *point = PenaltyPoint {
+                progress_percent: r.u16()?,
+                penalty_bps: r.u16()?,
+            };
+        }
+        pool.tier_table = r.pubkey()?;
+        pool.rarity_root = r.pubkey()?;
+
+        pool.lock_option_count = r.u32()? as usize;
+        if pool.lock_option_count > MAX_LOCK_OPTIONS {
+            return Err(DecodeError::TooManyEntries);
+        }
+        for option in pool.lock_options.iter_mut().take(pool.lock_option_count) {
+            *option = LockOption {
+                days: r.u64()?,
+                boost_bps: r.u16()?,
+            };
+        }
+        pool.transfer_cooldown = r.i64()?;
+        pool.vesting_enabled = r.bool()?;
+        pool.vesting = VestingConfig {
+            cliff_seconds: r.i64()?,
+            duration_seconds: r.i64()?,
+            early_exit_haircut_bps: r.u16()?,
+        };
+        pool.reward_token_program = r.pubkey()?;
+        pool.accrual = Accrual {
+            weight: r.u128()?,
+            flat_offset: r.u128()?,
+            scheduled_offset: r.u128()?,
+            scale_bps: r.u64()?,
+        };
+        pool.treasury = r.pubkey()?;
+
+        Ok(pool)
+    }
+
+    /// Reward parameters, priced by `tier_table` once the pool has one
+    pub fn params(&self, tier_table: Option<&TierTableAccount>) -> PoolParams {
+        let tiers = match tier_table {
+            Some(table) => table.multipliers,
+            None => self.legacy_tiers(),
+        };
+
+        PoolParams {
+            reward_rate: self.reward_rate,
+            tiers,
+            time_multiplier_increment: self.time_multiplier_increment,
+            time_multiplier_period_days: self.time_multiplier_period_days,
+            max_time_multiplier: self.max_time_multiplier,
+        }
+    }
+}
+
+impl PoolStateAccount {
+    /// Whether the pool prices rewards through an emission schedule
+    pub fn has_emission_schedule(&self) -> bool {
+        self.emission_schedule != [0u8; 32]
+    }
+
+    /// The pool's emergency unstake penalty curve
+    pub fn penalty_curve(&self) -> &[PenaltyPoint] {
+        &self.penalty_points[..self.penalty_point_count]
+    }
+
+    /// Lock periods the pool offers at stake time
+    pub fn lock_options(&self) -> &[LockOption] {
+        &self.lock_options[..self.lock_option_count]
+    }
+
+    /// Terms that vesting schedules release under
+    ///
+    /// With vesting off every schedule is fully unlocked.
+    pub fn vesting_terms(&self) -> VestingConfig {
+        if self.vesting_enabled {
+            self.vesting
+        } else {
+            VestingConfig::default()
+        }
+    }
+
+    /// Whether the pool prices tiers through a tier table
+    pub fn has_tier_table(&self) -> bool {
+        self.tier_table != [0u8; 32]
+    }
+
+    /// Common, Rare, Epic and Legendary multipliers stored on the pool itself
+    fn legacy_tiers(&self) -> TierMultipliers {
+        TierMultipliers::new(&[
+            self.common_multiplier,
+            self.rare_multiplier,
+            self.epic_multiplier,
+            self.legendary_multiplier,
+        ])
+        .unwrap_or_default()
+    }
+}
+
+impl EmissionScheduleAccount {
+    pub fn decode(data: &[u8]) -> DecodeResult<Self> {
+        let mut r = Reader::new(data, &EMISSION_SCHEDULE_DISCRIMINATOR)?;
+        let pool = r.pubkey()?;
+        let activated_at = r.i64()?;
+        let epoch_count = r.u32()? as usize;
+        if epoch_count > MAX_EMISSION_EPOCHS {
+            return Err(DecodeError::TooManyEntries);
+        }
+
+        let mut epochs = [EmissionEpoch::default(); MAX_EMISSION_EPOCHS];
+        for epoch in epochs.iter_mut().take(epoch_count) {
+            *epoch = EmissionEpoch {
+                start_time: r.i64()?,
+                end_time: r.i64()?,
+                reward_rate: r.u64()?,
+            };
+        }
+
+        Ok(Self {
+            pool,
+            activated_at,
+            epochs,
+            epoch_count,
+        })
+    }
+
+    pub fn epochs(&self) -> &[EmissionEpoch] {
+        &self.epochs[..self.epoch_count]
+    }
+
+    pub fn emission(&self) -> Emission<'_> {
+        Emission::Scheduled {
+            epochs: self.epochs(),
+            activated_at: self.activated_at,
+        }
+    }
+}
+
+impl TierTableAccount {
+    pub fn decode(data: &[u8]) -> DecodeResult<Self> {
+        let mut r = Reader::new(data, &TIER_TABLE_DISCRIMINATOR)?;
+        let pool = r.pubkey()?;
+        let tier_count = r.u32()? as usize;
+        if tier_count > MAX_TIERS {
+            return Err(DecodeError::TooManyEntries);
+        }
+
+        let mut multipliers = [0u64; MAX_TIERS];
+        for multiplier in multipliers.iter_mut().take(tier_count) {
+            r.skip_string(MAX_TIER_NAME_LEN)?;
+            *multiplier = r.u64()?;
+        }
+
+        Ok(Self {
+            pool,
+            multipliers: TierMultipliers::new(&multipliers[..tier_count]).unwrap_or_default(),
+        })
+    }
+
+    pub fn multipliers(&self) -> &TierMultipliers {
+        &self.multipliers
+    }
+}
+
+impl VestingScheduleAccount {
+    pub fn decode(data: &[u8]) -> DecodeResult<Self> {
+        let mut r = Reader::new(data, &VESTING_SCHEDULE_DISCRIMINATOR)?;
+        Ok(Self {
+            pool: r.pubkey()?,
+            owner: r.pubkey()?,
+            state: VestingState {
+                amount: r.u64()?,
+                start_time: r.i64()?,
+                withdrawn: r.u64()?,
+                carried: r.u64()?,
+            },
+        })
+    }
+}
+
+/// Little-endian Borsh field reader
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8], discriminator: &[u8; 8]) -> DecodeResult<Self> {
+        if data.len() < 8 {
+            return Err(DecodeError::TooShort);
+        }
+        if &data[..8] != discriminator {
+            return Err(DecodeError::WrongDiscriminator);
+        }
+        Ok(Self { data: &data[8..] })
+    }
+
+    fn take<const N: usize>(&mut self) -> DecodeResult<[u8; N]> {
+        if self.data.len() < N {
+            return Err(DecodeError::TooShort);
+        }
+        let (head, rest) = self.data.split_at(N);
+        self.data = rest;
+        let mut out = [0u8; N];
+        out.copy_from_slice(head);
+        Ok(out)
+    }
+
+    /// Skip a Borsh string of at most `max_len` bytes
+    fn skip_string(&mut self, max_len: usize) -> DecodeResult<()> {
+        let len = self.u32()? as usize;
+        if len > max_len {
+            return Err(DecodeError::TooManyEntries);
+        }
+        if self.data.len() < len {
+            return Err(DecodeError::TooShort);
+        }
+        self.data = &self.data[len..];
+        Ok(())
+    }
+
+    fn pubkey(&mut self) -> DecodeResult<[u8; 32]> {
+        self.take::<32>()
+    }
+
+    fn bool(&mut self) -> DecodeResult<bool> {
+        Ok(self.u8()? != 0)
+    }
+
+    fn u8(&mut self) -> DecodeResult<u8> {
+        Ok(self.take::<1>()?[0])
+    }
+
+    fn u16(&mut self) -> DecodeResult<u16> {
+        Ok(u16::from_le_bytes(self.take::<2>()?))
+    }
+
+    fn u32(&mut self) -> DecodeResult<u32> {
+        Ok(u32::from_le_bytes(self.take::<4>()?))
+    }
+
+    fn u64(&mut self) -> DecodeResult<u64> {
+        Ok(u64::from_le_bytes(self.take::<8>()?))
+    }
+
+    fn i64(&mut self) -> DecodeResult<i64> {
+        Ok(i64::from_le_bytes(self.take::<8>()?))
+    }
+
+    fn u128(&mut self) -> DecodeResult<u128> {
+        Ok(u128::from_le_bytes(self.take::<16>()?))
+    }
+}
@@ -0,0 +1,16 @@
+//! Collection bonus for holders staking several NFTs
+
+/// Collection bonus in basis points for a user with `staked_count` NFTs staked
+pub fn collection_bonus(staked_count: u8) -> u64 {
+    if staked_count >= 21 {
+        2000 // 20% for 21+ NFTs
+    } else if staked_count >= 11 {
+        1500 // 15% for 11-20 NFTs
+    } else if staked_count >= 6 {
+        1000 // 10% for 6-10 NFTs
+    } else if staked_count >= 3 {
+        500 // 5% for 3-5 NFTs
+    } else {
+        0 // No bonus for fewer than 3 NFTs
+    }
+}
@@ -0,0 +1,32 @@
+//! Auto-compound schedule and streak bonus
+
+pub const FREQUENCY_DAILY: u8 = 0;
+pub const FREQUENCY_WEEKLY: u8 = 1;
+pub const FREQUENCY_MONTHLY: u8 = 2;
+pub const FREQUENCY_MANUAL: u8 = 255;
+
+/// Streak bonus cap in basis points (10%)
+pub const MAX_STREAK_MULTIPLIER: u64 = 1000;
+
+/// Whether `frequency` is a value `set_compound_frequency` accepts
+pub fn is_valid_frequency(frequency: u8) -> bool {
+    matches!(
+        frequency,
+        FREQUENCY_DAILY | FREQUENCY_WEEKLY | FREQUENCY_MONTHLY | FREQUENCY_MANUAL
+    )
+}
+
+/// Minimum seconds between auto-compounds, `None` for manual or unknown values
+pub fn compound_interval(frequency: u8) -> Option<i64> {
+    match frequency {
+        FREQUENCY_DAILY => Some(86_400),      // Daily (24 hours)
+        FREQUENCY_WEEKLY => Some(604_800),    // Weekly (7 days)
+        FREQUENCY_MONTHLY => Some(2_592_000), // Monthly (30 days)
+        _ => None,
+    }
+}
+
+/// Streak bonus in basis points: +1% per 5 consecutive compounds, capped at 10%
+pub fn compound_streak_multiplier(streak: u16) -> u64 {
+    core::cmp::min((streak / 5) as u64 * 100, MAX_STREAK_MULTIPLIER)
+}
@@ -0,0 +1,86 @@
+//! Scheduled emission campaigns
+
+/// Maximum number of epochs in one emission schedule
+pub const MAX_EMISSION_EPOCHS: usize = 24;
+
+/// One epoch of an emission schedule, `[start_time, end_time)` at `reward_rate`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct EmissionEpoch {
+    pub start_time: i64,
+    pub end_time: i64,
+    pub reward_rate: u64,
+}
+
+/// Where a pool's base reward rate comes from
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Emission<'a> {
+    /// Constant `PoolParams::reward_rate`
+    Flat,
+    /// Per-epoch rates from `activated_at` on; time before it accrues at the
+    /// flat rate and time after it outside every epoch earns nothing
+    Scheduled {
+        epochs: &'a [EmissionEpoch],
+        activated_at: i64,
+    },
+}
+
+impl EmissionEpoch {
+    /// Seconds of `[from, to)` that fall inside this epoch
+    pub fn overlap(&self, from: i64, to: i64) -> i64 {
+        let start = core::cmp::max(self.start_time, from);
+        let end = core::cmp::min(self.end_time, to);
+        core::cmp::max(end.saturating_sub(start), 0)
+    }
+}
+
+impl Emission<'_> {
+    /// Base reward rate in effect at `now`
+    pub fn rate_at(&self, flat_rate: u64, now: i64) -> u64 {
+        match self {
+            Emission::Flat => flat_rate,
+            Emission::Scheduled { activated_at, .. } if now < *activated_at => flat_rate,
+            Emission::Scheduled { epochs, .. } => epochs
+                .iter()
+                .find(|e| e.start_time <= now && now < e.end_time)
+                .map(|e| e.reward_rate)
+                .unwrap_or(0),
+        }
+    }
+}
+
+/// Whether `epochs` are well-formed: at least one, each non-empty and in
+/// ascending, non-overlapping order
+pub fn is_valid_schedule(epochs: &[EmissionEpoch]) -> bool {
+    !epochs.is_empty()
+        && epochs.iter().all(|e| e.start_time < e.end_time)
+        && epochs.windows(2).all(|w| w[0].end_time <= w[1].start_time)
+}
+
+/// Whether replacing `old` with `new` at `now` leaves every started epoch untouched
+///
+/// Rewards already accrued under an epoch must not be repriced retroactively,
+/// so started epochs can be neither edited nor removed, and no epoch can be
+/// added that starts at or before `now`.
+pub fn preserves_started_epochs(old: &[EmissionEpoch], new: &[EmissionEpoch], now: i64) -> bool {
+    let started = |epoch: Option<&EmissionEpoch>| epoch.is_some_and(|e| e.start_time <= now);
+    (0..core::cmp::max(old.len(), new.len())).all(|i| {
+        let (before, after) = (old.get(i), new.get(i));
+        !(started(before) || started(after)) || before == after
+    })
+}
+
+/// Whether replacing `old` with `new` at `now` gives stakers `notice` seconds
+/// of warning
+///
+/// Every epoch that is added, edited or removed must start at or after
+/// `now + notice`, so the schedule a staker sees holds until then.
+pub fn gives_notice(old: &[EmissionEpoch], new: &[EmissionEpoch], now: i64, notice: i64) -> bool {
+    let earliest = now.saturating_add(notice);
+    let too_soon = |epoch: Option<&EmissionEpoch>| {
+        epoch.is_some_and(|e| e.start_time <= now || e.start_time < earliest)
+    };
+    (0..core::cmp::max(old.len(), new.len())).all(|i| {
+        let (before, after) = (old.get(i), new.get(i));
+        before == after || !(too_soon(before) || too_soon(after))
+    })
+}
@@ -0,0 +1,42 @@
+//! Reward math for the TESOLA NFT staking program.
+//!
+//! Every formula the on-chain program uses to value a stake lives here as a
+//! plain function over plain structs, so the program, the backend and the
+//! browser all compute the same numbers from the same account data.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+pub mod accounts;
+pub mod bonus;
+pub mod compound;
+pub mod emission;
+pub mod locks;
+pub mod milestones;
+pub mod pause;
+pub mod penalty;
+pub mod rarity;
+pub mod rewards;
+pub mod solvency;
+pub mod streams;
+pub mod tiers;
+pub mod vesting;
+
+pub use bonus::collection_bonus;
+pub use emission::{Emission, EmissionEpoch};
+pub use penalty::{emergency_unstake_quote, EmergencyQuote, PenaltyPoint};
+pub use rewards::{calculate_rewards, settle_rewards, time_multiplier, PoolParams, StakeSnapshot};
+pub use tiers::TierMultipliers;
+
+/// Seconds in one reward day
+pub const SECONDS_PER_DAY: i64 = 86_400;
+
+/// Denominator for all basis-point values (10000 = 100%)
+pub const BPS_DENOMINATOR: u64 = 10_000;
+
+/// Errors produced by the reward math
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MathError {
+    /// An intermediate value overflowed or underflowed
+    Overflow,
+}
+
+pub type MathResult<T> = core::result::Result<T, MathError>;
@@ -0,0 +1,63 @@
+//! Lock periods offered at stake time
+
+use crate::BPS_DENOMINATOR;
+
+/// Maximum number of lock options a pool can offer
+pub const MAX_LOCK_OPTIONS: usize = 8;
+
+/// A lock period in days and the reward boost committing to it earns
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LockOption {
+    pub days: u64,
+    pub boost_bps: u16,
+}
+
+/// 30/90/180/365-day locks boosted by 0/10/25/50%
+#[rustfmt::skip]
+pub const DEFAULT_LOCK_OPTIONS: [LockOption; 4] = [
+    LockOption { days: 30,  boost_bps: 0 },
+    LockOption { days: 90,  boost_bps: 1000 },
+    LockOption { days: 180, boost_bps: 2500 },
+    LockOption { days: 365, boost_bps: 5000 },
+];
+
+/// Whether `options` is a usable set of lock options
+///
+/// Periods are positive and strictly increasing, and no boost exceeds 100%.
+pub fn is_valid_options(options: &[LockOption]) -> bool {
+    if options.is_empty() || options.len() > MAX_LOCK_OPTIONS {
+        return false;
+    }
+    if options[0].days == 0 {
+        return false;
+    }
+    options
+        .iter()
+        .all(|option| option.boost_bps as u64 <= BPS_DENOMINATOR)
+        && options.windows(2).all(|pair| pair[0].days < pair[1].days)
+}
+
+/// Boost for locking `days`, `None` when the pool does not offer that period
+pub fn boost_for(options: &[LockOption], days: u64) -> Option<u16> {
+    options
+        .iter()
+        .find(|option| option.days == days)
+        .map(|option| option.boost_bps)
+}
+
+/// New period and boost after extending a `current_days` lock by `additional_days`
+///
+/// `None` when the extended period is not one of `options`.
+pub fn extend(
+    options: &[LockOption],
+    current_days: u64,
+    additional_days: u64,
+) -> Option<LockOption> {
+    if additional_days == 0 {
+        return None;
+    }
+
+    let days = current_days.checked_add(additional_days)?;
+    let boost_bps = boost_for(options, days)?;
+    Some(LockOption { days, boost_bps })
+}
@@ -0,0 +1,55 @@
+//! Staking-duration milestones and their one-off bonuses
+
+use crate::rewards::PoolParams;
+use crate::{MathError, MathResult};
+
+/// A staking-duration milestone
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Milestone {
+    pub days: u64,
+    pub bit: u8,            // Position in `StakeInfo.milestones_achieved`
+    pub bonus_percent: u64, // Percentage of the base daily reward
+}
+
+/// Milestones in ascending order
+#[rustfmt::skip]
+pub const MILESTONES: [Milestone; 4] = [
+    Milestone { days: 30, bit: 0, bonus_percent: 5 },   // 30-day milestone
+    Milestone { days: 90, bit: 1, bonus_percent: 10 },  // 90-day milestone
+    Milestone { days: 180, bit: 2, bonus_percent: 15 }, // 180-day milestone
+    Milestone { days: 365, bit: 3, bonus_percent: 25 }, // 365-day milestone
+];
+
+/// Days each milestone bonus is worth
+pub const MILESTONE_REWARD_DAYS: u64 = 30;
+
+impl Milestone {
+    pub fn mask(&self) -> u8 {
+        1u8 << self.bit
+    }
+
+    /// Whether the milestone is reached after `days_staked` and not yet in `achieved`
+    pub fn is_pending(&self, achieved: u8, days_staked: i64) -> bool {
+        days_staked >= self.days as i64 && achieved & self.mask() == 0
+    }
+}
+
+/// One-off reward for reaching `milestone`: 30 days of the tier's base reward at the milestone percentage
+pub fn milestone_reward(pool: &PoolParams, tier: u8, milestone: &Milestone) -> MathResult<u64> {
+    pool.base_daily_reward(tier)?
+        .checked_mul(milestone.bonus_percent)
+        .ok_or(MathError::Overflow)?
+        .checked_div(100)
+        .ok_or(MathError::Overflow)?
+        .checked_mul(MILESTONE_REWARD_DAYS)
+        .ok_or(MathError::Overflow)
+}
+
+/// Day count of the next milestone not yet achieved, or 0 when none remain
+pub fn next_milestone_days(achieved: u8, days_staked: i64) -> u64 {
+    MILESTONES
+        .iter()
+        .find(|m| m.days as i64 > days_staked && achieved & m.mask() == 0)
+        .map(|m| m.days)
+        .unwrap_or(0)
+}
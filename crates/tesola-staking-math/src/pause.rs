@@ -0,0 +1,86 @@
+//! Granular pause flags
+//!
+//! Each bit of `PoolState.pause_flags` freezes one class of operation, so an
+//! incident can stop new stakes while users keep the ability to leave.
+
+/// Blocks `stake_nft`
+pub const PAUSE_STAKING: u8 = 1 << 0;
+/// Blocks reward claims and milestone credits
+pub const PAUSE_CLAIMING: u8 = 1 << 1;
+/// Blocks auto-compounding
+pub const PAUSE_COMPOUNDING: u8 = 1 << 2;
+/// Blocks `unstake_nft` once the staking period has completed
+pub const PAUSE_UNSTAKING: u8 = 1 << 3;
+/// Blocks early exit through `emergency_unstake_nft`
+pub const PAUSE_EMERGENCY_EXIT: u8 = 1 << 4;
+
+/// Every operation paused
+pub const PAUSE_ALL: u8 =
+    PAUSE_STAKING | PAUSE_CLAIMING | PAUSE_COMPOUNDING | PAUSE_UNSTAKING | PAUSE_EMERGENCY_EXIT;
+
+/// Whether `flags` contains only known pause bits
+pub fn is_valid_flags(flags: u8) -> bool {
+    flags & !PAUSE_ALL == 0
+}
+
+/// Whether `operation` (one `PAUSE_*` bit) may run under `flags`
+pub fn is_allowed(flags: u8, operation: u8) -> bool {
+    flags & operation == 0
+}
+
+/// Program instructions that a pause flag can block
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Instruction {
+    StakeNft,
+    ExtendLock,
+    UpdateTimeMultiplier,
+    ProcessAutoCompound,
+    ClaimRewards,
+    ClaimAllRewards,
+    ClaimRewardIou,
+    WithdrawVested,
+    ExitVesting,
+    TransferStake,
+    UnstakeNft,
+    EmergencyUnstakeNft,
+}
+
+impl Instruction {
+    /// Every instruction a pause flag can block
+    pub const ALL: [Instruction; 12] = [
+        Instruction::StakeNft,
+        Instruction::ExtendLock,
+        Instruction::UpdateTimeMultiplier,
+        Instruction::ProcessAutoCompound,
+        Instruction::ClaimRewards,
+        Instruction::ClaimAllRewards,
+        Instruction::ClaimRewardIou,
+        Instruction::WithdrawVested,
+        Instruction::ExitVesting,
+        Instruction::TransferStake,
+        Instruction::UnstakeNft,
+        Instruction::EmergencyUnstakeNft,
+    ];
+
+    /// The `PAUSE_*` bit that blocks this instruction
+    pub const fn pause_flag(self) -> u8 {
+        match self {
+            Instruction::StakeNft | Instruction::ExtendLock => PAUSE_STAKING,
+            Instruction::UpdateTimeMultiplier
+            | Instruction::ClaimRewards
+            | Instruction::ClaimAllRewards
+            | Instruction::ClaimRewardIou
+            | Instruction::WithdrawVested
+            | Instruction::ExitVesting
+            | Instruction::TransferStake => PAUSE_CLAIMING,
+            Instruction::ProcessAutoCompound => PAUSE_COMPOUNDING,
+            Instruction::UnstakeNft => PAUSE_UNSTAKING,
+            Instruction::EmergencyUnstakeNft => PAUSE_EMERGENCY_EXIT,
+        }
+    }
+
+    /// Whether this instruction may run under `flags`
+    pub fn is_allowed(self, flags: u8) -> bool {
+        is_allowed(flags, self.pause_flag())
+    }
+}
@@ -0,0 +1,144 @@
+//! Emergency unstake penalty
+
+use crate::emission::Emission;
+use crate::rewards::{calculate_rewards, PoolParams, StakeSnapshot};
+use crate::{MathResult, BPS_DENOMINATOR, SECONDS_PER_DAY};
+
+/// Maximum number of points in a penalty curve
+pub const MAX_PENALTY_POINTS: usize = 10;
+
+/// One point of a penalty curve: `penalty_bps` at `progress_percent` of the lock
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PenaltyPoint {
+    pub progress_percent: u16,
+    pub penalty_bps: u16,
+}
+
+/// The 50/30/15/5% bands at 30/60/90% progress, as a piecewise-linear curve
+#[rustfmt::skip]
+pub const DEFAULT_PENALTY_CURVE: [PenaltyPoint; 8] = [
+    PenaltyPoint { progress_percent: 0,   penalty_bps: 5000 },
+    PenaltyPoint { progress_percent: 29,  penalty_bps: 5000 },
+    PenaltyPoint { progress_percent: 30,  penalty_bps: 3000 },
+    PenaltyPoint { progress_percent: 59,  penalty_bps: 3000 },
+    PenaltyPoint { progress_percent: 60,  penalty_bps: 1500 },
+    PenaltyPoint { progress_percent: 89,  penalty_bps: 1500 },
+    PenaltyPoint { progress_percent: 90,  penalty_bps: 500 },
+    PenaltyPoint { progress_percent: 100, penalty_bps: 500 },
+];
+
+/// Outcome of an emergency unstake at a given time
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct EmergencyQuote {
+    pub earned_rewards: u64,
+    pub penalty_bps: u64,
+    pub penalty_amount: u64,
+    pub final_rewards: u64,
+    pub progress_percentage: u8,
+    pub curve_segment: u8, // Index of the curve point starting the segment that priced the penalty
+    pub lock_completed: bool, // Past the release date: a regular unstake without penalty
+}
+
+/// Whether `points` is a usable penalty curve
+///
+/// A curve starts at 0% progress, has strictly increasing progress and never
+/// exceeds a 100% penalty.
+pub fn is_valid_curve(points: &[PenaltyPoint]) -> bool {
+    if points.is_empty() || points.len() > MAX_PENALTY_POINTS {
+        return false;
+    }
+    if points[0].progress_percent != 0 {
+        return false;
+    }
+    points
+        .iter()
+        .all(|point| point.penalty_bps as u64 <= BPS_DENOMINATOR)
+        && points
+            .windows(2)
+            .all(|pair| pair[0].progress_percent < pair[1].progress_percent)
+}
+
+/// Lock progress in percent of `staking_period` days, capped at 100
+///
+/// A zero-day period counts as complete.
+pub fn progress_percentage(staked_at: i64, now: i64, staking_period: u64) -> u8 {
+    let staking_duration_days = core::cmp::max(now.saturating_sub(staked_at), 0) / SECONDS_PER_DAY;
+    let progress = (staking_duration_days as u64)
+        .saturating_mul(100)
+        .checked_div(staking_period)
+        .unwrap_or(100);
+    core::cmp::min(progress, 100) as u8
+}
+
+/// Penalty in basis points at `progress` percent, and the curve segment that priced it
+///
+/// Between two points the penalty is interpolated linearly; past the last
+/// point it stays at the last point's value.
+pub fn penalty_at(curve: &[PenaltyPoint], progress: i64) -> (u64, u8) {
+    let Some(last) = curve.last() else {
+        return (0, 0);
+    };
+    let progress = core::cmp::max(progress, 0);
+
+    for (index, pair) in curve.windows(2).enumerate() {
+        let (start, end) = (pair[0], pair[1]);
+        if progress >= end.progress_percent as i64 {
+            continue;
+        }
+        let span = (end.progress_percent - start.progress_percent) as i64;
+        let offset = progress - start.progress_percent as i64;
+        let delta = end.penalty_bps as i64 - start.penalty_bps as i64;
+        let bps = start.penalty_bps as i64 + delta * offset / span;
+        return (bps as u64, index as u8);
+    }
+
+    (last.penalty_bps as u64, (curve.len() - 1) as u8)
+}
+
+/// Rewards and penalty an emergency unstake would produce at `now`
+pub fn emergency_unstake_quote(
+    stake: &StakeSnapshot,
+    pool: &PoolParams,
+    emission: Emission,
+    curve: &[PenaltyPoint],
+    now: i64,
+) -> MathResult<EmergencyQuote> {
+    let earned_rewards = calculate_rewards(stake, pool, emission, now)?;
+    Ok(apply_emergency_penalty(earned_rewards, stake, curve, now))
+}
+
+/// Applies the emergency penalty for `stake` at `now` to already settled rewards
+///
+/// Once the release date has passed the exit is a regular unstake and no
+/// penalty applies.
+pub fn apply_emergency_penalty(
+    earned_rewards: u64,
+    stake: &StakeSnapshot,
+    curve: &[PenaltyPoint],
+    now: i64,
+) -> EmergencyQuote {
+    let progress = progress_percentage(stake.staked_at, now, stake.staking_period);
+    if now >= stake.release_date {
+        return EmergencyQuote {
+            earned_rewards,
+            final_rewards: earned_rewards,
+            progress_percentage: progress,
+            lock_completed: true,
+            ..EmergencyQuote::default()
+        };
+    }
+
+    let (penalty_bps, curve_segment) = penalty_at(curve, progress as i64);
+    let penalty_amount =
+        (earned_rewards as u128 * penalty_bps as u128 / BPS_DENOMINATOR as u128) as u64;
+
+    EmergencyQuote {
+        earned_rewards,
+        penalty_bps,
+        penalty_amount,
+        final_rewards: earned_rewards.saturating_sub(penalty_amount),
+        progress_percentage: progress,
+        curve_segment,
+        lock_completed: false,
+    }
+}
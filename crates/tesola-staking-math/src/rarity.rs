@@ -0,0 +1,66 @@
+//! Per-mint rarity overrides proven against a Merkle root
+//!
+//! The admin publishes the root of a tree whose leaves are
+//! `(mint, tier, bonus_bps)`; a staker proves their NFT's entry with the
+//! sibling hashes along its path. Pairs are hashed in sorted order so a
+//! proof needs no left/right flags, and leaves and inner nodes carry
+//! different prefixes so a node can never pass as a leaf.
+//!
+//! The hash function is passed in so the program can use the keccak
+//! syscall and off-chain builders any Keccak-256 implementation.
+
+/// Longest accepted proof, enough for 2^24 leaves
+pub const MAX_PROOF_LEN: usize = 24;
+
+/// Largest per-mint bonus, 100% on top of the tier multiplier
+pub const MAX_RARITY_BONUS_BPS: u16 = 10_000;
+
+const LEAF_PREFIX: [u8; 1] = [0];
+const NODE_PREFIX: [u8; 1] = [1];
+
+/// One entry of the rarity tree
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RarityLeaf {
+    pub mint: [u8; 32],
+    pub tier: u8,
+    pub bonus_bps: u16,
+}
+
+impl RarityLeaf {
+    /// Leaf hash, `hash(0x00 || mint || tier || bonus_bps as u16 LE)`
+    pub fn hash<H>(&self, hashv: H) -> [u8; 32]
+    where
+        H: Fn(&[&[u8]]) -> [u8; 32],
+    {
+        hashv(&[
+            &LEAF_PREFIX,
+            &self.mint,
+            &[self.tier],
+            &self.bonus_bps.to_le_bytes(),
+        ])
+    }
+}
+
+/// Parent of two nodes, `hash(0x01 || min(a, b) || max(a, b))`
+pub fn hash_pair<H>(hashv: H, a: &[u8; 32], b: &[u8; 32]) -> [u8; 32]
+where
+    H: Fn(&[&[u8]]) -> [u8; 32],
+{
+    let (low, high) = if a <= b { (a, b) } else { (b, a) };
+    hashv(&[&NODE_PREFIX, low, high])
+}
+
+/// Whether `proof` links `leaf` to `root`
+pub fn verify<H>(hashv: H, leaf: &RarityLeaf, proof: &[[u8; 32]], root: &[u8; 32]) -> bool
+where
+    H: Fn(&[&[u8]]) -> [u8; 32],
+{
+    if proof.len() > MAX_PROOF_LEN || leaf.bonus_bps > MAX_RARITY_BONUS_BPS {
+        return false;
+    }
+
+    let computed = proof.iter().fold(leaf.hash(&hashv), |node, sibling| {
+        hash_pair(&hashv, &node, sibling)
+    });
+    computed == *root
+}
@@ -0,0 +1,152 @@
+//! Base reward accrual
+
+use crate::emission::Emission;
+use crate::tiers::TierMultipliers;
+use crate::{MathError, MathResult, BPS_DENOMINATOR, SECONDS_PER_DAY};
+
+/// Pool-level parameters that feed the reward formula
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PoolParams {
+    pub reward_rate: u64,
+    pub tiers: TierMultipliers,
+    pub time_multiplier_increment: u64, // Basis points per period
+    pub time_multiplier_period_days: u64, // Period length in days
+    pub max_time_multiplier: u64,       // Cap in basis points
+}
+
+/// Per-stake state that feeds the reward formula
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StakeSnapshot {
+    pub staked_at: i64,
+    pub release_date: i64,
+    pub last_claim_time: i64,
+    pub staking_period: u64, // In days
+    pub tier: u8,
+    pub current_time_multiplier: u64,    // Basis points
+    pub compound_streak_multiplier: u64, // Basis points
+    pub rarity_bonus_bps: u64,           // Per-mint bonus proven at stake time
+    pub lock_boost_bps: u64,             // Boost of the lock option chosen at stake time
+}
+
+impl PoolParams {
+    /// Tier multiplier in percent (100 = 1x), unknown tiers fall back to Common
+    pub fn tier_multiplier(&self, tier: u8) -> u64 {
+        self.tiers.multiplier(tier)
+    }
+
+    /// Daily reward for a tier before any time or streak multiplier
+    pub fn base_daily_reward(&self, tier: u8) -> MathResult<u64> {
+        self.reward_rate
+            .checked_mul(self.tier_multiplier(tier))
+            .ok_or(MathError::Overflow)?
+            .checked_div(100)
+            .ok_or(MathError::Overflow)
+    }
+}
+
+/// Applies a basis-point bonus on top of `amount` (`bonus_bps` = 500 adds 5%)
+pub fn apply_bonus_bps(amount: u64, bonus_bps: u64) -> MathResult<u64> {
+    let factor = BPS_DENOMINATOR
+        .checked_add(bonus_bps)
+        .ok_or(MathError::Overflow)?;
+    let scaled = (amount as u128) * (factor as u128) / (BPS_DENOMINATOR as u128);
+    u64::try_from(scaled).map_err(|_| MathError::Overflow)
+}
+
+/// Time multiplier in basis points earned after staking since `staked_at`
+pub fn time_multiplier(pool: &PoolParams, staked_at: i64, now: i64) -> u64 {
+    let days_staked = now.saturating_sub(staked_at) / SECONDS_PER_DAY;
+    if days_staked <= 0 || pool.time_multiplier_period_days == 0 {
+        return 0;
+    }
+
+    let periods = days_staked as u64 / pool.time_multiplier_period_days;
+    core::cmp::min(
+        periods.saturating_mul(pool.time_multiplier_increment),
+        pool.max_time_multiplier,
+    )
+}
+
+/// Daily reward for a stake with its rarity bonus, lock boost, time and compound-streak multipliers applied
+pub fn daily_reward(stake: &StakeSnapshot, pool: &PoolParams) -> MathResult<u64> {
+    let base_daily_reward =
+        apply_bonus_bps(pool.base_daily_reward(stake.tier)?, stake.rarity_bonus_bps)?;
+    let base_daily_reward = apply_bonus_bps(base_daily_reward, stake.lock_boost_bps)?;
+    let with_time_multiplier = apply_bonus_bps(base_daily_reward, stake.current_time_multiplier)?;
+    apply_bonus_bps(with_time_multiplier, stake.compound_streak_multiplier)
+}
+
+/// Rewards accrued since `stake.last_claim_time`
+///
+/// Each stretch of time accrues its daily reward pro rata by the second,
+/// rounded down, at the base rate `emission` sets for that stretch.
+pub fn calculate_rewards(
+    stake: &StakeSnapshot,
+    pool: &PoolParams,
+    emission: Emission,
+    now: i64,
+) -> MathResult<u64> {
+    let time_elapsed = now
+        .checked_sub(stake.last_claim_time)
+        .ok_or(MathError::Overflow)?;
+    if time_elapsed <= 0 {
+        return Ok(0);
+    }
+
+    match emission {
+        Emission::Flat => accrue(stake, pool, time_elapsed),
+        Emission::Scheduled {
+            epochs,
+            activated_at,
+        } => {
+            // Time before the schedule took over keeps the flat rate
+            let flat_seconds =
+                core::cmp::min(now, activated_at).saturating_sub(stake.last_claim_time);
+            let mut total = if flat_seconds > 0 {
+                accrue(stake, pool, flat_seconds)?
+            } else {
+                0
+            };
+
+            let from = core::cmp::max(stake.last_claim_time, activated_at);
+            for epoch in epochs {
+                let seconds = epoch.overlap(from, now);
+                if seconds == 0 {
+                    continue;
+                }
+                let epoch_pool = PoolParams {
+                    reward_rate: epoch.reward_rate,
+                    ..*pool
+                };
+                total = total
+                    .checked_add(accrue(stake, &epoch_pool, seconds)?)
+                    .ok_or(MathError::Overflow)?;
+            }
+            Ok(total)
+        }
+    }
+}
+
+/// Rewards for `seconds` of staking at the pool's current rate
+fn accrue(stake: &StakeSnapshot, pool: &PoolParams, seconds: i64) -> MathResult<u64> {
+    let per_day = daily_reward(stake, pool)?;
+    let reward = per_day as u128 * seconds as u128 / SECONDS_PER_DAY as u128;
+    u64::try_from(reward).map_err(|_| MathError::Overflow)
+}
+
+/// Settles rewards accrued up to `now` and advances the checkpoint
+///
+/// Claim, compound and unstake all settle through here so a period is never
+/// paid twice.
+pub fn settle_rewards(
+    stake: &mut StakeSnapshot,
+    pool: &PoolParams,
+    emission: Emission,
+    now: i64,
+) -> MathResult<u64> {
+    let rewards = calculate_rewards(stake, pool, emission, now)?;
+    if now > stake.last_claim_time {
+        stake.last_claim_time = now;
+    }
+    Ok(rewards)
+}
@@ -0,0 +1,244 @@
+//! Reward vault solvency, pro-rata payouts and the bound on unsettled accrual
+
+use crate::emission::Emission;
+use crate::rewards::{PoolParams, StakeSnapshot};
+use crate::{MathError, MathResult, BPS_DENOMINATOR, SECONDS_PER_DAY};
+
+/// Accrual weight of a stake earning exactly the base rate
+pub const ACCRUAL_WEIGHT_UNIT: u64 = 1_000_000;
+
+/// Outcome of paying a claim out of a possibly underfunded vault
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Payout {
+    pub paid: u64,
+    pub shortfall: u64, // Stays owed as an IOU until the vault can pay it
+}
+
+/// Vault balance relative to outstanding liabilities in basis points (10000 = fully covered)
+pub fn solvency_ratio_bps(vault_balance: u64, total_liabilities: u64) -> u64 {
+    if total_liabilities == 0 {
+        return BPS_DENOMINATOR;
+    }
+    let ratio = vault_balance as u128 * BPS_DENOMINATOR as u128 / total_liabilities as u128;
+    u64::try_from(ratio).unwrap_or(u64::MAX)
+}
+
+/// Splits `owed` into what the vault can pay now and the shortfall
+///
+/// A fully covered vault pays in full. Otherwise every claimant receives the
+/// same share of what they are owed, `vault_balance / total_liabilities`, so
+/// early claimants cannot drain the vault ahead of everyone else.
+/// `total_liabilities` must already include `owed`.
+pub fn pro_rata_payout(owed: u64, vault_balance: u64, total_liabilities: u64) -> Payout {
+    if vault_balance >= total_liabilities {
+        return Payout {
+            paid: owed,
+            shortfall: 0,
+        };
+    }
+
+    let paid = (owed as u128 * vault_balance as u128 / total_liabilities as u128) as u64;
+    Payout {
+        paid,
+        shortfall: owed - paid,
+    }
+}
+
+/// Splits `owed` the way claims, unstakes and stake transfers pay out
+///
+/// Pools in underfunded mode pay the `pro_rata_payout` share and carry the
+/// rest as an IOU. Otherwise a vault that cannot cover `owed` yields `None`
+/// and the instruction fails.
+pub fn claim_payout(
+    owed: u64,
+    vault_balance: u64,
+    total_liabilities: u64,
+    underfunded_mode: bool,
+) -> Option<Payout> {
+    if underfunded_mode {
+        Some(pro_rata_payout(owed, vault_balance, total_liabilities))
+    } else if vault_balance >= owed {
+        Some(Payout {
+            paid: owed,
+            shortfall: 0,
+        })
+    } else {
+        None
+    }
+}
+
+/// Position of the pool's accrual clock at one point in time
+///
+/// A stake last settled at `then` has since earned its daily reward for
+/// `flat_seconds` at the flat rate plus `scheduled` epoch rate-seconds,
+/// both measured between `AccrualPoint::at(then)` and `AccrualPoint::at(now)`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AccrualPoint {
+    pub flat_seconds: i64, // Seconds at the flat rate, which stops at the schedule's activation
+    pub scheduled: u128,   // Sum of epoch rate times seconds since activation
+}
+
+/// Upper bound on a stake's daily reward per unit of base rate, in `ACCRUAL_WEIGHT_UNIT`s
+///
+/// `update_time_multiplier` raises a stake's time multiplier without
+/// settling it, so the bound assumes the larger of the stake's multiplier and
+/// the pool's cap.
+pub fn accrual_weight(stake: &StakeSnapshot, pool: &PoolParams) -> MathResult<u64> {
+    let time_multiplier = core::cmp::max(stake.current_time_multiplier, pool.max_time_multiplier);
+    let mut weight = pool.tier_multiplier(stake.tier) as u128 * ACCRUAL_WEIGHT_UNIT as u128 / 100;
+    for bonus_bps in [
+        stake.rarity_bonus_bps,
+        stake.lock_boost_bps,
+        time_multiplier,
+        stake.compound_streak_multiplier,
+    ] {
+        let factor = BPS_DENOMINATOR as u128 + bonus_bps as u128;
+        weight = weight
+            .checked_mul(factor)
+            .ok_or(MathError::Overflow)?
+            .div_ceil(BPS_DENOMINATOR as u128);
+    }
+    u64::try_from(weight).map_err(|_| MathError::Overflow)
+}
+
+impl AccrualPoint {
+    /// Where the accrual clock of a pool paying by `emission` stands at `now`
+    pub fn at(emission: Emission, now: i64) -> Self {
+        match emission {
+            Emission::Flat => Self {
+                flat_seconds: now,
+                scheduled: 0,
+            },
+            Emission::Scheduled {
+                epochs,
+                activated_at,
+            } => Self {
+                flat_seconds: core::cmp::min(now, activated_at),
+                scheduled: epochs.iter().fold(0u128, |total, e| {
+                    let seconds = e.overlap(activated_at, now) as u128;
+                    total.saturating_add(e.reward_rate as u128 * seconds)
+                }),
+            },
+        }
+    }
+
+    fn flat(&self) -> u128 {
+        u128::try_from(self.flat_seconds).unwrap_or(0)
+    }
+}
+
+/// Pool-wide sums bounding the rewards every stake accrued since it last settled
+///
+/// Each stake is tracked from its last settlement with `track` and removed
+/// with `untrack` before it settles again or leaves the pool. The flat rate
+/// is applied when reading `pending` because a changed `reward_rate` reprices
+/// unsettled time retroactively, exactly as `calculate_rewards` does.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Accrual {
+    pub weight: u128,           // Sum of tracked stake weights
+    pub flat_offset: u128,      // Sum of weight times flat seconds at each stake's last settlement
+    pub scheduled_offset: u128, // Sum of weight times scheduled rate-seconds at the same points
+    pub scale_bps: u64,         // Growth of multipliers since weights were taken, 0 reads as 10000
+}
+
+/// One stake's entry in `Accrual`, kept on the stake so it can be removed exactly
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StakeAccrual {
+    pub weight: u64, // Divided by the pool's scale at the time it was tracked
+    pub point: AccrualPoint,
+}
+
+impl Accrual {
+    fn scale(&self) -> u64 {
+        if self.scale_bps == 0 {
+            BPS_DENOMINATOR
+        } else {
+            self.scale_bps
+        }
+    }
+
+    /// Start counting a stake of `weight` (see `accrual_weight`) from `point`
+    pub fn track(&mut self, weight: u64, point: AccrualPoint) -> MathResult<StakeAccrual> {
+        let normalized = (weight as u128 * BPS_DENOMINATOR as u128).div_ceil(self.scale() as u128);
+        let normalized = u64::try_from(normalized).map_err(|_| MathError::Overflow)?;
+
+        let flat = (normalized as u128)
+            .checked_mul(point.flat())
+            .ok_or(MathError::Overflow)?;
+        let scheduled = (normalized as u128)
+            .checked_mul(point.scheduled)
+            .ok_or(MathError::Overflow)?;
+        self.weight = self
+            .weight
+            .checked_add(normalized as u128)
+            .ok_or(MathError::Overflow)?;
+        self.flat_offset = self
+            .flat_offset
+            .checked_add(flat)
+            .ok_or(MathError::Overflow)?;
+        self.scheduled_offset = self
+            .scheduled_offset
+            .checked_add(scheduled)
+            .ok_or(MathError::Overflow)?;
+
+        Ok(StakeAccrual {
+            weight: normalized,
+            point,
+        })
+    }
+
+    /// Stop counting a stake previously returned by `track`
+    pub fn untrack(&mut self, stake: &StakeAccrual) {
+        let weight = stake.weight as u128;
+        self.weight = self.weight.saturating_sub(weight);
+        self.flat_offset = self
+            .flat_offset
+            .saturating_sub(weight.saturating_mul(stake.point.flat()));
+        self.scheduled_offset = self
+            .scheduled_offset
+            .saturating_sub(weight.saturating_mul(stake.point.scheduled));
+    }
+
+    /// Scale every tracked weight by the largest growth among `(old, new)` multipliers
+    ///
+    /// Tier and time multipliers reprice unsettled time retroactively, so a
+    /// raised multiplier raises the bound for stakes that have not settled
+    /// since. Stakes drop back to their exact weight when they next settle.
+    pub fn rescale(&mut self, changes: &[(u64, u64)]) -> MathResult<()> {
+        let growth = changes
+            .iter()
+            .filter(|(old, new)| *old > 0 && new > old)
+            .max_by(|a, b| (a.1 as u128 * b.0 as u128).cmp(&(b.1 as u128 * a.0 as u128)));
+
+        if let Some(&(old, new)) = growth {
+            let scale = (self.scale() as u128 * new as u128).div_ceil(old as u128);
+            self.scale_bps = u64::try_from(scale).map_err(|_| MathError::Overflow)?;
+        }
+        Ok(())
+    }
+
+    /// Upper bound on rewards tracked stakes accrued up to `now` at `flat_rate`
+    pub fn pending(&self, flat_rate: u64, now: AccrualPoint) -> MathResult<u64> {
+        let flat = self
+            .weight
+            .checked_mul(now.flat())
+            .ok_or(MathError::Overflow)?
+            .saturating_sub(self.flat_offset);
+        let scheduled = self
+            .weight
+            .checked_mul(now.scheduled)
+            .ok_or(MathError::Overflow)?
+            .saturating_sub(self.scheduled_offset);
+
+        let rate_seconds = flat
+            .checked_mul(flat_rate as u128)
+            .and_then(|flat| flat.checked_add(scheduled))
+            .ok_or(MathError::Overflow)?;
+        let unscaled = rate_seconds.div_ceil(ACCRUAL_WEIGHT_UNIT as u128 * SECONDS_PER_DAY as u128);
+        let pending = unscaled
+            .checked_mul(self.scale() as u128)
+            .ok_or(MathError::Overflow)?
+            .div_ceil(BPS_DENOMINATOR as u128);
+        u64::try_from(pending).map_err(|_| MathError::Overflow)
+    }
+}
@@ -0,0 +1,30 @@
+//! Additional per-pool reward streams
+
+use crate::emission::{Emission, EmissionEpoch};
+use crate::rewards::{calculate_rewards, PoolParams, StakeSnapshot};
+use crate::MathResult;
+
+/// Maximum number of additional reward streams per pool
+pub const MAX_REWARD_STREAMS: usize = 4;
+
+/// Rewards a stake accrued from `stream` between `last_settled` and `now`
+///
+/// A stream is priced like a one-epoch emission schedule: its rate replaces
+/// the pool's base rate and is scaled by the stake's tier and multipliers.
+pub fn stream_rewards(
+    stake: &StakeSnapshot,
+    pool: &PoolParams,
+    stream: &EmissionEpoch,
+    last_settled: i64,
+    now: i64,
+) -> MathResult<u64> {
+    let stake = StakeSnapshot {
+        last_claim_time: last_settled,
+        ..*stake
+    };
+    let emission = Emission::Scheduled {
+        epochs: core::slice::from_ref(stream),
+        activated_at: last_settled,
+    };
+    calculate_rewards(&stake, pool, emission, now)
+}
@@ -0,0 +1,59 @@
+//! Tier multiplier table
+
+/// Most tiers a pool can define
+pub const MAX_TIERS: usize = 16;
+
+/// Longest tier name in bytes
+pub const MAX_TIER_NAME_LEN: usize = 16;
+
+/// Common, Rare, Epic and Legendary multipliers of a new pool, in percent (100 = 1x)
+pub const DEFAULT_TIER_MULTIPLIERS: [u64; 4] = [100, 200, 400, 800];
+
+/// Multipliers in percent indexed by tier id
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TierMultipliers {
+    multipliers: [u64; MAX_TIERS],
+    len: u8,
+}
+
+impl TierMultipliers {
+    /// Table of `multipliers` in tier order, `None` past `MAX_TIERS` entries
+    pub fn new(multipliers: &[u64]) -> Option<Self> {
+        if multipliers.len() > MAX_TIERS {
+            return None;
+        }
+
+        let mut table = Self {
+            len: multipliers.len() as u8,
+            ..Self::default()
+        };
+        table.multipliers[..multipliers.len()].copy_from_slice(multipliers);
+        Some(table)
+    }
+
+    pub fn len(&self) -> usize {
+        self.len as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Whether `tier` is defined
+    pub fn contains(&self, tier: u8) -> bool {
+        (tier as usize) < self.len()
+    }
+
+    /// Multiplier of `tier`, unknown tiers fall back to the first (Common) tier
+    pub fn multiplier(&self, tier: u8) -> u64 {
+        if self.contains(tier) {
+            self.multipliers[tier as usize]
+        } else {
+            self.multipliers[0]
+        }
+    }
+
+    pub fn as_slice(&self) -> &[u64] {
+        &self.multipliers[..self.len()]
+    }
+}
@@ -0,0 +1,137 @@
+//! Linear vesting of claimed rewards
+//!
+//! A user has one running schedule. Each claim deposited into it moves
+//! whatever has unlocked so far aside as withdrawable, then adds the new
+//! amount and moves the schedule's start to the amount-weighted average of
+//! the old start and the deposit time. A small deposit, such as a delegate's
+//! claim, therefore barely delays what is already vesting. Nothing unlocks
+//! before the cliff; after it the schedule unlocks linearly from its start
+//! until `duration_seconds` have passed.
+
+use crate::{MathError, MathResult, BPS_DENOMINATOR};
+
+/// Pool-wide vesting terms
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct VestingConfig {
+    pub cliff_seconds: i64,
+    pub duration_seconds: i64,
+    pub early_exit_haircut_bps: u16, // Share of the locked amount forfeited by exit
+}
+
+/// A user's running vesting schedule
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct VestingState {
+    pub amount: u64,     // Principal of the running schedule
+    pub start_time: i64, // Amount-weighted start of the running schedule
+    pub withdrawn: u64,  // Withdrawn or carried out of the running schedule
+    pub carried: u64,    // Unlocked before the last deposit and not yet withdrawn
+}
+
+/// Outcome of leaving a vesting schedule early
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ExitQuote {
+    pub unlocked: u64, // Paid in full
+    pub locked: u64,
+    pub haircut: u64, // Forfeited share of `locked`
+    pub payout: u64,  // `unlocked + locked - haircut`
+}
+
+/// Whether `config` is usable: a positive duration, a cliff inside it and a haircut of at most 100%
+pub fn is_valid_config(config: &VestingConfig) -> bool {
+    config.duration_seconds > 0
+        && config.cliff_seconds >= 0
+        && config.cliff_seconds <= config.duration_seconds
+        && config.early_exit_haircut_bps as u64 <= BPS_DENOMINATOR
+}
+
+impl VestingState {
+    /// Amount of the running schedule unlocked at `now`
+    pub fn vested(&self, config: &VestingConfig, now: i64) -> u64 {
+        let elapsed = now.saturating_sub(self.start_time);
+        if elapsed < config.cliff_seconds {
+            return 0;
+        }
+        if config.duration_seconds <= 0 || elapsed >= config.duration_seconds {
+            return self.amount;
+        }
+
+        let vested = self.amount as u128 * elapsed as u128 / config.duration_seconds as u128;
+        vested as u64
+    }
+
+    /// Amount that can be withdrawn at `now`
+    pub fn withdrawable(&self, config: &VestingConfig, now: i64) -> u64 {
+        self.carried
+            .saturating_add(self.vested(config, now).saturating_sub(self.withdrawn))
+    }
+
+    /// Amount still locked at `now`
+    pub fn locked(&self, config: &VestingConfig, now: i64) -> u64 {
+        self.amount.saturating_sub(self.released(config, now))
+    }
+
+    /// Amount of the running schedule unlocked or already withdrawn at `now`
+    ///
+    /// A deposit can move the start past the cliff again, so `withdrawn` may
+    /// briefly exceed what the schedule has vested.
+    fn released(&self, config: &VestingConfig, now: i64) -> u64 {
+        core::cmp::max(self.vested(config, now), self.withdrawn)
+    }
+
+    /// Add `amount` to the schedule at `now`
+    ///
+    /// Everything unlocked moves aside as withdrawable, and the start moves
+    /// to the amount-weighted average of the old start and `now`, rounded
+    /// later. A fully unlocked schedule starts over at `now`.
+    pub fn deposit(&mut self, config: &VestingConfig, amount: u64, now: i64) -> MathResult<()> {
+        let carried = self.withdrawable(config, now);
+        if self.locked(config, now) == 0 {
+            *self = VestingState {
+                amount,
+                start_time: now,
+                withdrawn: 0,
+                carried,
+            };
+            return Ok(());
+        }
+
+        let released = self.released(config, now);
+        let total = self.amount.checked_add(amount).ok_or(MathError::Overflow)?;
+        let elapsed = core::cmp::max(now.saturating_sub(self.start_time), 0) as u128;
+        let shift = (elapsed * amount as u128).div_ceil(total as u128) as i64;
+
+        *self = VestingState {
+            amount: total,
+            start_time: self.start_time + shift,
+            withdrawn: released,
+            carried,
+        };
+        Ok(())
+    }
+
+    /// Withdraw everything unlocked at `now` and return the amount
+    pub fn withdraw(&mut self, config: &VestingConfig, now: i64) -> u64 {
+        let amount = self.withdrawable(config, now);
+        self.carried = 0;
+        self.withdrawn = self.released(config, now);
+        amount
+    }
+
+    /// Leave the schedule at `now`: unlocked in full, locked minus the haircut
+    ///
+    /// The schedule is emptied.
+    pub fn exit(&mut self, config: &VestingConfig, now: i64) -> ExitQuote {
+        let unlocked = self.withdrawable(config, now);
+        let locked = self.locked(config, now);
+        let haircut = (locked as u128 * config.early_exit_haircut_bps as u128
+            / BPS_DENOMINATOR as u128) as u64;
+
+        *self = VestingState::default();
+        ExitQuote {
+            unlocked,
+            locked,
+            haircut,
+            payout: unlocked + (locked - haircut),
+        }
+    }
+}
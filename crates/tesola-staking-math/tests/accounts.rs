@@ -0,0 +1,230 @@
+use sha2::{Digest, Sha256};
+use tesola_staking_math::accounts::{
+    DecodeError, PoolStateAccount, StakeInfoAccount, EMISSION_SCHEDULE_DISCRIMINATOR,
+    POOL_STATE_DISCRIMINATOR, STAKE_INFO_DISCRIMINATOR, TIER_TABLE_DISCRIMINATOR,
+    VESTING_SCHEDULE_DISCRIMINATOR,
+};
+use tesola_staking_math::locks::LockOption;
+use tesola_staking_math::penalty::PenaltyPoint;
+use tesola_staking_math::solvency::{Accrual, AccrualPoint, StakeAccrual};
+use tesola_staking_math::vesting::VestingConfig;
+
+mod common;
+
+use common::accounts::{pool_state, pool_state_bytes, stake_info, stake_info_bytes, PoolState};
+
+/// Anchor's discriminator: the first 8 bytes of `sha256("account:<Name>")`
+fn discriminator(name: &str) -> [u8; 8] {
+    let hash = Sha256::digest(format!("account:{name}"));
+    hash[..8].try_into().unwrap()
+}
+
+#[test]
+fn discriminators_match_anchor() {
+    assert_eq!(STAKE_INFO_DISCRIMINATOR, discriminator("StakeInfo"));
+    assert_eq!(POOL_STATE_DISCRIMINATOR, discriminator("PoolState"));
+    assert_eq!(
+        EMISSION_SCHEDULE_DISCRIMINATOR,
+        discriminator("EmissionSchedule")
+    );
+    assert_eq!(TIER_TABLE_DISCRIMINATOR, discriminator("TierTable"));
+    assert_eq!(
+        VESTING_SCHEDULE_DISCRIMINATOR,
+        discriminator("VestingSchedule")
+    );
+}
+
+#[test]
+fn decodes_every_stake_info_field() {
+    let fixture = stake_info();
+    let decoded = StakeInfoAccount::decode(&stake_info_bytes(&fixture)).unwrap();
+
+    assert_eq!(
+        decoded,
+        StakeInfoAccount {
+            owner: fixture.owner,
+            mint: fixture.mint,
+            staked_at: fixture.staked_at,
+            release_date: fixture.release_date,
+            is_staked: fixture.is_staked,
+            tier: fixture.tier,
+            last_claim_time: fixture.last_claim_time,
+            staking_period: fixture.staking_period,
+            auto_compound: fixture.auto_compound,
+            accumulated_compound: fixture.accumulated_compound,
+            current_time_multiplier: fixture.current_time_multiplier,
+            last_multiplier_update: fixture.last_multiplier_update,
+            milestones_achieved: fixture.milestones_achieved,
+            next_milestone_days: fixture.next_milestone_days,
+            compound_frequency: fixture.compound_frequency,
+            last_compound_time: fixture.last_compound_time,
+            compound_streak: fixture.compound_streak,
+            compound_streak_multiplier: fixture.compound_streak_multiplier,
+            reward_iou: fixture.reward_iou,
+            rarity_bonus_bps: fixture.rarity_bonus_bps,
+            lock_boost_bps: fixture.lock_boost_bps,
+            last_transfer_time: fixture.last_transfer_time,
+            programmable: fixture.programmable,
+            accrual: StakeAccrual {
+                weight: fixture.accrual.weight,
+                point: AccrualPoint {
+                    flat_seconds: fixture.accrual.flat_seconds,
+                    scheduled: fixture.accrual.scheduled,
+                },
+            },
+        }
+    );
+}
+
+#[test]
+fn decodes_every_pool_state_field() {
+    let fixture = PoolState {
+        emission_schedule: [11; 32],
+        tier_table: [12; 32],
+        ..pool_state()
+    };
+    let decoded = PoolStateAccount::decode(&pool_state_bytes(&fixture)).unwrap();
+
+    assert_eq!(decoded.admin, fixture.admin);
+    assert_eq!(decoded.reward_rate, fixture.reward_rate);
+    assert_eq!(decoded.pause_flags, fixture.pause_flags);
+    assert_eq!(decoded.total_staked, fixture.total_staked);
+    assert_eq!(decoded.common_multiplier, fixture.common_multiplier);
+    assert_eq!(decoded.rare_multiplier, fixture.rare_multiplier);
+    assert_eq!(decoded.epic_multiplier, fixture.epic_multiplier);
+    assert_eq!(decoded.legendary_multiplier, fixture.legendary_multiplier);
+    assert_eq!(decoded.max_nfts_per_user, fixture.max_nfts_per_user);
+    assert_eq!(
+        decoded.time_multiplier_increment,
+        fixture.time_multiplier_increment
+    );
+    assert_eq!(
+        decoded.time_multiplier_period_days,
+        fixture.time_multiplier_period_days
+    );
+    assert_eq!(decoded.max_time_multiplier, fixture.max_time_multiplier);
+    assert_eq!(decoded.reward_mint, fixture.reward_mint);
+    assert_eq!(decoded.reward_vault, fixture.reward_vault);
+    assert_eq!(decoded.total_liabilities, fixture.total_liabilities);
+    assert_eq!(decoded.underfunded_mode, fixture.underfunded_mode);
+    assert_eq!(decoded.emission_schedule, fixture.emission_schedule);
+    assert!(decoded.has_emission_schedule());
+    assert_eq!(decoded.collection, fixture.collection);
+    assert_eq!(decoded.bump, fixture.bump);
+    assert_eq!(decoded.pending_admin, fixture.pending_admin);
+    assert_eq!(decoded.change_delay, fixture.change_delay);
+    assert_eq!(
+        decoded.penalty_curve(),
+        [
+            PenaltyPoint {
+                progress_percent: 0,
+                penalty_bps: 4000,
+            },
+            PenaltyPoint {
+                progress_percent: 100,
+                penalty_bps: 0,
+            },
+        ]
+    );
+    assert_eq!(decoded.tier_table, fixture.tier_table);
+    assert!(decoded.has_tier_table());
+    assert_eq!(decoded.rarity_root, fixture.rarity_root);
+    assert_eq!(
+        decoded.lock_options(),
+        [
+            LockOption {
+                days: 30,
+                boost_bps: 0,
+            },
+            LockOption {
+                days: 90,
+                boost_bps: 1000,
+            },
+        ]
+    );
+    assert_eq!(decoded.transfer_cooldown, fixture.transfer_cooldown);
+    assert_eq!(decoded.vesting_enabled, fixture.vesting.enabled);
+    assert_eq!(
+        decoded.vesting,
+        VestingConfig {
+            cliff_seconds: fixture.vesting.cliff_seconds,
+            duration_seconds: fixture.vesting.duration_seconds,
+            early_exit_haircut_bps: fixture.vesting.early_exit_haircut_bps,
+        }
+    );
+    assert_eq!(decoded.vesting_terms(), decoded.vesting);
+    assert_eq!(decoded.reward_token_program, fixture.reward_token_program);
+    assert_eq!(
+        decoded.accrual,
+        Accrual {
+            weight: fixture.accrual.weight,
+            flat_offset: fixture.accrual.flat_offset,
+            scheduled_offset: fixture.accrual.scheduled_offset,
+            scale_bps: fixture.accrual.scale_bps,
+        }
+    );
+    assert_eq!(decoded.treasury, fixture.treasury);
+}
+
+#[test]
+fn pool_params_come_from_the_pool_multipliers() {
+    let fixture = pool_state();
+    let params = PoolStateAccount::decode(&pool_state_bytes(&fixture))
+        .unwrap()
+        .params(None);
+
+    assert_eq!(params, common::pool());
+}
+
+#[test]
+fn truncated_accounts_are_rejected() {
+    let stake = stake_info_bytes(&stake_info());
+    let pool = pool_state_bytes(&pool_state());
+
+    for len in 0..stake.len() {
+        assert_eq!(
+            StakeInfoAccount::decode(&stake[..len]),
+            Err(DecodeError::TooShort),
+            "length {len}"
+        );
+    }
+    for len in 0..pool.len() {
+        assert!(
+            PoolStateAccount::decode(&pool[..len]).is_err(),
+            "length {len}"
+        );
+    }
+}
+
+#[test]
+fn accounts_of_another_type_are_rejected() {
+    let stake = stake_info_bytes(&stake_info());
+    let pool = pool_state_bytes(&pool_state());
+
+    assert_eq!(
+        StakeInfoAccount::decode(&pool),
+        Err(DecodeError::WrongDiscriminator)
+    );
+    assert_eq!(
+        PoolStateAccount::decode(&stake),
+        Err(DecodeError::WrongDiscriminator)
+    );
+}
+
+#[test]
+fn oversized_penalty_curves_are_rejected() {
+    let fixture = PoolState {
+        penalty_curve: (0..11)
+            .map(|i| common::accounts::PenaltyPoint {
+                progress_percent: i * 10,
+                penalty_bps: 0,
+            })
+            .collect(),
+        ..pool_state()
+    };
+
+    assert_eq!(
+        PoolStateAccount::decode(&pool_state_bytes(&fixture)).unwrap_err(),
+        DecodeError::TooManyEntries
+    );
+}
@@ -0,0 +1,217 @@
+//! Borsh mirrors of the program's `StakeInfo` and `PoolState`, fields in declaration order
+
+use borsh::BorshSerialize;
+use tesola_staking_math::accounts::{POOL_STATE_DISCRIMINATOR, STAKE_INFO_DISCRIMINATOR};
+use tesola_staking_math::SECONDS_PER_DAY;
+
+use super::START;
+
+#[derive(BorshSerialize)]
+pub struct StakeInfo {
+    pub owner: [u8; 32],
+    pub mint: [u8; 32],
+    pub staked_at: i64,
+    pub release_date: i64,
+    pub is_staked: bool,
+    pub tier: u8,
+    pub last_claim_time: i64,
+    pub staking_period: u64,
+    pub auto_compound: bool,
+    pub accumulated_compound: u64,
+    pub current_time_multiplier: u64,
+    pub last_multiplier_update: i64,
+    pub milestones_achieved: u8,
+    pub next_milestone_days: u64,
+    pub compound_frequency: u8,
+    pub last_compound_time: i64,
+    pub compound_streak: u16,
+    pub compound_streak_multiplier: u64,
+    pub reward_iou: u64,
+    pub rarity_bonus_bps: u16,
+    pub lock_boost_bps: u16,
+    pub last_transfer_time: i64,
+    pub programmable: bool,
+    pub accrual: StakeAccrual,
+}
+
+#[derive(BorshSerialize)]
+pub struct StakeAccrual {
+    pub weight: u64,
+    pub flat_seconds: i64,
+    pub scheduled: u128,
+}
+
+#[derive(BorshSerialize)]
+pub struct PoolState {
+    pub admin: [u8; 32],
+    pub reward_rate: u64,
+    pub pause_flags: u8,
+    pub total_staked: u64,
+    pub common_multiplier: u64,
+    pub rare_multiplier: u64,
+    pub epic_multiplier: u64,
+    pub legendary_multiplier: u64,
+    pub max_nfts_per_user: u8,
+    pub time_multiplier_increment: u64,
+    pub time_multiplier_period_days: u64,
+    pub max_time_multiplier: u64,
+    pub reward_mint: [u8; 32],
+    pub reward_vault: [u8; 32],
+    pub total_liabilities: u64,
+    pub underfunded_mode: bool,
+    pub emission_schedule: [u8; 32],
+    pub collection: [u8; 32],
+    pub bump: u8,
+    pub pending_admin: [u8; 32],
+    pub change_delay: i64,
+    pub penalty_curve: Vec<PenaltyPoint>,
+    pub tier_table: [u8; 32],
+    pub rarity_root: [u8; 32],
+    pub lock_options: Vec<LockOption>,
+    pub transfer_cooldown: i64,
+    pub vesting: VestingConfig,
+    pub reward_token_program: [u8; 32],
+    pub accrual: Accrual,
+    pub treasury: [u8; 32],
+}
+
+#[derive(BorshSerialize)]
+pub struct PenaltyPoint {
+    pub progress_percent: u16,
+    pub penalty_bps: u16,
+}
+
+#[derive(BorshSerialize)]
+pub struct LockOption {
+    pub days: u64,
+    pub boost_bps: u16,
+}
+
+#[derive(BorshSerialize)]
+pub struct VestingConfig {
+    pub enabled: bool,
+    pub cliff_seconds: i64,
+    pub duration_seconds: i64,
+    pub early_exit_haircut_bps: u16,
+}
+
+#[derive(BorshSerialize)]
+pub struct Accrual {
+    pub weight: u128,
+    pub flat_offset: u128,
+    pub scheduled_offset: u128,
+    pub scale_bps: u64,
+}
+
+/// A compounding Epic stake 10 days into a 90-day lock, every field set
+pub fn stake_info() -> StakeInfo {
+    StakeInfo {
+        owner: [1; 32],
+        mint: [2; 32],
+        staked_at: START,
+        release_date: START + 90 * SECONDS_PER_DAY,
+        is_staked: true,
+        tier: 2,
+        last_claim_time: START + 10 * SECONDS_PER_DAY,
+        staking_period: 90,
+        auto_compound: true,
+        accumulated_compound: 1_234,
+        current_time_multiplier: 500,
+        last_multiplier_update: START + 9 * SECONDS_PER_DAY,
+        milestones_achieved: 0b1,
+        next_milestone_days: 30,
+        compound_frequency: 1,
+        last_compound_time: START + 7 * SECONDS_PER_DAY,
+        compound_streak: 3,
+        compound_streak_multiplier: 150,
+        reward_iou: 77,
+        rarity_bonus_bps: 250,
+        lock_boost_bps: 1000,
+        last_transfer_time: START + 5 * SECONDS_PER_DAY,
+        programmable: true,
+        accrual: StakeAccrual {
+            weight: 4_400,
+            flat_seconds: 864_000,
+            scheduled: 1 << 70,
+        },
+    }
+}
+
+/// A flat-rate pool on the built-in tiers, every field set
+pub fn pool_state() -> PoolState {
+    PoolState {
+        admin: [3; 32],
+        reward_rate: 100_000_000,
+        pause_flags: 0b100,
+        total_staked: 12,
+        common_multiplier: 100,
+        rare_multiplier: 200,
+        epic_multiplier: 400,
+        legendary_multiplier: 800,
+        max_nfts_per_user: 5,
+        time_multiplier_increment: 500,
+        time_multiplier_period_days: 30,
+        max_time_multiplier: 5000,
+        reward_mint: [4; 32],
+        reward_vault: [5; 32],
+        total_liabilities: 9_000,
+        underfunded_mode: true,
+        emission_schedule: [0; 32],
+        collection: [6; 32],
+        bump: 254,
+        pending_admin: [7; 32],
+        change_delay: 2 * SECONDS_PER_DAY,
+        penalty_curve: vec![
+            PenaltyPoint {
+                progress_percent: 0,
+                penalty_bps: 4000,
+            },
+            PenaltyPoint {
+                progress_percent: 100,
+                penalty_bps: 0,
+            },
+        ],
+        tier_table: [0; 32],
+        rarity_root: [8; 32],
+        lock_options: vec![
+            LockOption {
+                days: 30,
+                boost_bps: 0,
+            },
+            LockOption {
+                days: 90,
+                boost_bps: 1000,
+            },
+        ],
+        transfer_cooldown: SECONDS_PER_DAY,
+        vesting: VestingConfig {
+            enabled: true,
+            cliff_seconds: 7 * SECONDS_PER_DAY,
+            duration_seconds: 30 * SECONDS_PER_DAY,
+            early_exit_haircut_bps: 2500,
+        },
+        reward_token_program: [9; 32],
+        accrual: Accrual {
+            weight: 1 << 80,
+            flat_offset: 11,
+            scheduled_offset: 13,
+            scale_bps: 10_500,
+        },
+        treasury: [10; 32],
+    }
+}
+
+/// Account data as the program stores it: discriminator, then the Borsh fields
+pub fn encode(discriminator: &[u8; 8], account: &impl BorshSerialize) -> Vec<u8> {
+    let mut data = discriminator.to_vec();
+    account.serialize(&mut data).unwrap();
+    data
+}
+
+pub fn stake_info_bytes(stake_info: &StakeInfo) -> Vec<u8> {
+    encode(&STAKE_INFO_DISCRIMINATOR, stake_info)
+}
+
+pub fn pool_state_bytes(pool_state: &PoolState) -> Vec<u8> {
+    encode(&POOL_STATE_DISCRIMINATOR, pool_state)
+}
@@ -0,0 +1,35 @@
+//! Fixtures shared by the integration tests; not every test binary uses all of them
+#![allow(dead_code)]
+
+pub mod accounts;
+
+use tesola_staking_math::tiers::DEFAULT_TIER_MULTIPLIERS;
+use tesola_staking_math::{PoolParams, StakeSnapshot, TierMultipliers, SECONDS_PER_DAY};
+
+pub const START: i64 = 1_700_000_000;
+
+/// Default tier multipliers, +5% every 30 days capped at +50%
+pub fn pool() -> PoolParams {
+    PoolParams {
+        reward_rate: 100_000_000,
+        tiers: TierMultipliers::new(&DEFAULT_TIER_MULTIPLIERS).unwrap(),
+        time_multiplier_increment: 500,
+        time_multiplier_period_days: 30,
+        max_time_multiplier: 5000,
+    }
+}
+
+/// A 90-day stake opened at `START` with no bonuses
+pub fn stake(tier: u8) -> StakeSnapshot {
+    StakeSnapshot {
+        staked_at: START,
+        release_date: START + 90 * SECONDS_PER_DAY,
+        last_claim_time: START,
+        staking_period: 90,
+        tier,
+        current_time_multiplier: 0,
+        compound_streak_multiplier: 0,
+        rarity_bonus_bps: 0,
+        lock_boost_bps: 0,
+    }
+}
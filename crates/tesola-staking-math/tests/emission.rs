@@ -0,0 +1,233 @@
+use tesola_staking_math::emission::{gives_notice, is_valid_schedule, preserves_started_epochs};
+use tesola_staking_math::{
+    calculate_rewards, Emission, EmissionEpoch, PoolParams, SECONDS_PER_DAY,
+};
+
+mod common;
+
+use common::{pool, stake, START};
+
+const DAY: i64 = SECONDS_PER_DAY;
+
+fn epoch(start_day: i64, end_day: i64, reward_rate: u64) -> EmissionEpoch {
+    EmissionEpoch {
+        start_time: START + start_day * DAY,
+        end_time: START + end_day * DAY,
+        reward_rate,
+    }
+}
+
+/// Activated on day 2: days 2..5 at 300, a gap until day 7, days 7..10 at 50
+fn epochs() -> [EmissionEpoch; 2] {
+    [epoch(2, 5, 300), epoch(7, 10, 50)]
+}
+
+fn scheduled(epochs: &[EmissionEpoch]) -> Emission<'_> {
+    Emission::Scheduled {
+        epochs,
+        activated_at: START + 2 * DAY,
+    }
+}
+
+/// Common tier without bonuses, so one day at rate `r` earns exactly `r`
+fn rewards_at(emission: Emission, day: i64) -> u64 {
+    let pool = PoolParams {
+        reward_rate: 100,
+        ..pool()
+    };
+    calculate_rewards(&stake(0), &pool, emission, START + day * DAY).unwrap()
+}
+
+#[test]
+fn time_before_activation_keeps_the_flat_rate() {
+    let epochs = epochs();
+    assert_eq!(rewards_at(scheduled(&epochs), 1), 100);
+    assert_eq!(rewards_at(scheduled(&epochs), 2), 200);
+    assert_eq!(
+        rewards_at(scheduled(&epochs), 2),
+        rewards_at(Emission::Flat, 2)
+    );
+}
+
+#[test]
+fn each_epoch_pays_its_own_rate() {
+    let epochs = epochs();
+    assert_eq!(rewards_at(scheduled(&epochs), 3), 200 + 300);
+    assert_eq!(rewards_at(scheduled(&epochs), 5), 200 + 900);
+    assert_eq!(rewards_at(scheduled(&epochs), 8), 200 + 900 + 50);
+}
+
+#[test]
+fn gaps_and_time_after_the_last_epoch_earn_nothing() {
+    let epochs = epochs();
+    // Days 5..7 fall between epochs
+    assert_eq!(
+        rewards_at(scheduled(&epochs), 7),
+        rewards_at(scheduled(&epochs), 5)
+    );
+    // Nothing after day 10
+    assert_eq!(rewards_at(scheduled(&epochs), 10), 200 + 900 + 150);
+    assert_eq!(
+        rewards_at(scheduled(&epochs), 400),
+        rewards_at(scheduled(&epochs), 10)
+    );
+    // An empty schedule stops all emission from activation on
+    assert_eq!(rewards_at(scheduled(&[]), 30), 200);
+}
+
+#[test]
+fn claims_after_activation_only_count_epochs_since_the_last_claim() {
+    let epochs = epochs();
+    let pool = PoolParams {
+        reward_rate: 100,
+        ..pool()
+    };
+    let mut stake = stake(0);
+    stake.last_claim_time = START + 4 * DAY;
+
+    let rewards = calculate_rewards(&stake, &pool, scheduled(&epochs), START + 8 * DAY).unwrap();
+    assert_eq!(rewards, 300 + 50);
+}
+
+#[test]
+fn rate_at_falls_back_to_flat_before_activation_and_zero_in_gaps() {
+    let epochs = epochs();
+    let emission = scheduled(&epochs);
+
+    assert_eq!(Emission::Flat.rate_at(100, START), 100);
+    assert_eq!(emission.rate_at(100, START + DAY), 100);
+    assert_eq!(emission.rate_at(100, START + 2 * DAY), 300);
+    // Epochs are half-open: the end belongs to what follows
+    assert_eq!(emission.rate_at(100, START + 5 * DAY - 1), 300);
+    assert_eq!(emission.rate_at(100, START + 5 * DAY), 0);
+    assert_eq!(emission.rate_at(100, START + 7 * DAY), 50);
+    assert_eq!(emission.rate_at(100, START + 10 * DAY), 0);
+}
+
+#[test]
+fn schedules_must_be_ordered_and_non_overlapping() {
+    assert!(is_valid_schedule(&epochs()));
+    assert!(is_valid_schedule(&[epoch(0, 1, 10), epoch(1, 2, 20)]));
+
+    // No epochs
+    assert!(!is_valid_schedule(&[]));
+    // Empty epoch
+    assert!(!is_valid_schedule(&[epoch(3, 3, 10)]));
+    assert!(!is_valid_schedule(&[epoch(4, 3, 10)]));
+    // Overlapping
+    assert!(!is_valid_schedule(&[epoch(0, 2, 10), epoch(1, 3, 20)]));
+    // Out of order
+    assert!(!is_valid_schedule(&[epoch(5, 6, 10), epoch(1, 2, 20)]));
+}
+
+#[test]
+fn started_epochs_cannot_be_edited_or_removed() {
+    let old = epochs();
+    let now = START + 3 * DAY;
+
+    let mut edited = old;
+    edited[0].reward_rate = 1;
+    assert!(!preserves_started_epochs(&old, &edited, now));
+
+    let mut shortened = old;
+    shortened[0].end_time -= 1;
+    assert!(!preserves_started_epochs(&old, &shortened, now));
+
+    assert!(!preserves_started_epochs(&old, &old[1..], now));
+    assert!(!preserves_started_epochs(&old, &[], now));
+}
+
+#[test]
+fn future_epochs_can_change_freely() {
+    let old = epochs();
+    let now = START + 3 * DAY;
+
+    let mut edited = old;
+    edited[1].reward_rate = 75;
+    assert!(preserves_started_epochs(&old, &edited, now));
+    assert!(preserves_started_epochs(&old, &old[..1], now));
+    assert!(preserves_started_epochs(
+        &old,
+        &[old[0], old[1], epoch(12, 14, 10)],
+        now
+    ));
+}
+
+#[test]
+fn epochs_starting_now_or_earlier_cannot_be_added() {
+    let now = START + 3 * DAY;
+
+    // Into an empty schedule
+    assert!(!preserves_started_epochs(&[], &[epoch(1, 4, 10)], now));
+    assert!(!preserves_started_epochs(&[], &[epoch(3, 4, 10)], now));
+    assert!(preserves_started_epochs(&[], &[epoch(4, 5, 10)], now));
+
+    // In place of a future epoch that has not started yet
+    let old = [epoch(1, 2, 10), epoch(6, 8, 10)];
+    assert!(!preserves_started_epochs(
+        &old,
+        &[old[0], epoch(2, 8, 10)],
+        now
+    ));
+    // After the last epoch, but already started
+    assert!(!preserves_started_epochs(
+        &old,
+        &[old[0], old[1], epoch(8, 9, 10)],
+        START + 8 * DAY
+    ));
+}
+
+#[test]
+fn changes_must_start_after_the_notice_period() {
+    let now = START + 3 * DAY;
+    let notice = 2 * DAY;
+
+    // Into an empty schedule
+    assert!(!gives_notice(&[], &[epoch(4, 6, 10)], now, notice));
+    assert!(gives_notice(&[], &[epoch(5, 6, 10)], now, notice));
+
+    let old = [epoch(1, 2, 10), epoch(4, 5, 10), epoch(8, 9, 10)];
+    // Editing or removing an epoch inside the notice period
+    let mut edited = old;
+    edited[1].reward_rate = 1;
+    assert!(!gives_notice(&old, &edited, now, notice));
+    assert!(!gives_notice(&old, &old[..1], now, notice));
+    // Moving an epoch that is past the notice period into it
+    assert!(!gives_notice(
+        &[old[0], old[2]],
+        &[old[0], epoch(4, 9, 10)],
+        now,
+        notice
+    ));
+    assert!(!gives_notice(
+        &old,
+        &[old[0], old[1], epoch(5, 9, 10)],
+        START + 4 * DAY,
+        notice
+    ));
+
+    // Beyond it, epochs can change freely
+    let mut edited = old;
+    edited[2].reward_rate = 1;
+    assert!(gives_notice(&old, &edited, now, notice));
+    assert!(gives_notice(&old, &old[..2], now, notice));
+    assert!(gives_notice(
+        &old,
+        &[old[0], old[1], old[2], epoch(9, 10, 10)],
+        now,
+        notice
+    ));
+    assert!(gives_notice(&old, &old, START + 100 * DAY, notice));
+}
+
+#[test]
+fn no_notice_still_protects_started_epochs() {
+    let old = epochs();
+    let now = START + 3 * DAY;
+
+    let mut edited = old;
+    edited[0].reward_rate = 1;
+    assert!(!gives_notice(&old, &edited, now, 0));
+    assert!(!gives_notice(&[], &[epoch(3, 4, 10)], now, 0));
+    assert!(gives_notice(&[], &[epoch(4, 5, 10)], now, 0));
+}
@@ -0,0 +1,73 @@
+use tesola_staking_math::locks::{
+    boost_for, extend, is_valid_options, LockOption, DEFAULT_LOCK_OPTIONS, MAX_LOCK_OPTIONS,
+};
+use tesola_staking_math::rewards::daily_reward;
+use tesola_staking_math::tiers::DEFAULT_TIER_MULTIPLIERS;
+use tesola_staking_math::{PoolParams, StakeSnapshot, TierMultipliers};
+
+fn option(days: u64, boost_bps: u16) -> LockOption {
+    LockOption { days, boost_bps }
+}
+
+#[test]
+fn default_options_are_valid() {
+    assert!(is_valid_options(&DEFAULT_LOCK_OPTIONS));
+}
+
+#[test]
+fn only_listed_periods_have_a_boost() {
+    assert_eq!(boost_for(&DEFAULT_LOCK_OPTIONS, 30), Some(0));
+    assert_eq!(boost_for(&DEFAULT_LOCK_OPTIONS, 180), Some(2500));
+    assert_eq!(boost_for(&DEFAULT_LOCK_OPTIONS, 45), None);
+    assert_eq!(boost_for(&DEFAULT_LOCK_OPTIONS, 0), None);
+}
+
+#[test]
+fn rejects_malformed_options() {
+    assert!(!is_valid_options(&[]));
+    assert!(!is_valid_options(&[option(0, 0)]));
+    assert!(!is_valid_options(&[option(90, 0), option(30, 500)]));
+    assert!(!is_valid_options(&[option(30, 0), option(30, 500)]));
+    assert!(!is_valid_options(&[option(30, 10_001)]));
+
+    let too_many: Vec<LockOption> = (1..=MAX_LOCK_OPTIONS as u64 + 1)
+        .map(|days| option(days, 0))
+        .collect();
+    assert!(!is_valid_options(&too_many));
+}
+
+#[test]
+fn extending_lands_on_a_listed_option() {
+    assert_eq!(
+        extend(&DEFAULT_LOCK_OPTIONS, 90, 275),
+        Some(option(365, 5000))
+    );
+    assert_eq!(
+        extend(&DEFAULT_LOCK_OPTIONS, 30, 60),
+        Some(option(90, 1000))
+    );
+}
+
+#[test]
+fn extending_to_an_unlisted_period_fails() {
+    assert_eq!(extend(&DEFAULT_LOCK_OPTIONS, 90, 30), None);
+    assert_eq!(extend(&DEFAULT_LOCK_OPTIONS, 90, 0), None);
+    assert_eq!(extend(&DEFAULT_LOCK_OPTIONS, u64::MAX, 1), None);
+}
+
+#[test]
+fn lock_boost_raises_daily_reward() {
+    let pool = PoolParams {
+        reward_rate: 100_000_000,
+        tiers: TierMultipliers::new(&DEFAULT_TIER_MULTIPLIERS).unwrap(),
+        ..PoolParams::default()
+    };
+    let unboosted = StakeSnapshot::default();
+    let boosted = StakeSnapshot {
+        lock_boost_bps: 5000,
+        ..unboosted
+    };
+
+    assert_eq!(daily_reward(&unboosted, &pool).unwrap(), 100_000_000);
+    assert_eq!(daily_reward(&boosted, &pool).unwrap(), 150_000_000);
+}
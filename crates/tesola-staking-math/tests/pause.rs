@@ -0,0 +1,130 @@
+use tesola_staking_math::pause::{
+    is_allowed, is_valid_flags, Instruction, PAUSE_ALL, PAUSE_CLAIMING, PAUSE_COMPOUNDING,
+    PAUSE_EMERGENCY_EXIT, PAUSE_STAKING, PAUSE_UNSTAKING,
+};
+
+const OPERATIONS: [u8; 5] = [
+    PAUSE_STAKING,
+    PAUSE_CLAIMING,
+    PAUSE_COMPOUNDING,
+    PAUSE_UNSTAKING,
+    PAUSE_EMERGENCY_EXIT,
+];
+
+#[test]
+fn every_combination_blocks_exactly_its_operations() {
+    for flags in 0..=PAUSE_ALL {
+        assert!(is_valid_flags(flags));
+        for operation in OPERATIONS {
+            assert_eq!(
+                is_allowed(flags, operation),
+                flags & operation == 0,
+                "flags {flags:#07b}, operation {operation:#07b}"
+            );
+        }
+    }
+}
+
+#[test]
+fn unpaused_pool_allows_everything() {
+    for operation in OPERATIONS {
+        assert!(is_allowed(0, operation));
+    }
+}
+
+#[test]
+fn fully_paused_pool_blocks_everything() {
+    for operation in OPERATIONS {
+        assert!(!is_allowed(PAUSE_ALL, operation));
+    }
+}
+
+#[test]
+fn freezing_new_stakes_still_lets_users_leave() {
+    let flags = PAUSE_STAKING;
+    assert!(!is_allowed(flags, PAUSE_STAKING));
+    assert!(is_allowed(flags, PAUSE_CLAIMING));
+    assert!(is_allowed(flags, PAUSE_COMPOUNDING));
+    assert!(is_allowed(flags, PAUSE_UNSTAKING));
+    assert!(is_allowed(flags, PAUSE_EMERGENCY_EXIT));
+}
+
+#[test]
+fn incident_mode_keeps_only_exits_open() {
+    let flags = PAUSE_STAKING | PAUSE_CLAIMING | PAUSE_COMPOUNDING;
+    assert!(!is_allowed(flags, PAUSE_STAKING));
+    assert!(!is_allowed(flags, PAUSE_CLAIMING));
+    assert!(!is_allowed(flags, PAUSE_COMPOUNDING));
+    assert!(is_allowed(flags, PAUSE_UNSTAKING));
+    assert!(is_allowed(flags, PAUSE_EMERGENCY_EXIT));
+}
+
+#[test]
+fn unknown_bits_are_rejected() {
+    for bit in 5..8 {
+        assert!(!is_valid_flags(1 << bit));
+        assert!(!is_valid_flags(PAUSE_ALL | 1 << bit));
+    }
+}
+
+/// The flag each instruction must be blocked by, written out independently
+const INSTRUCTION_FLAGS: [(Instruction, u8); 12] = [
+    (Instruction::StakeNft, PAUSE_STAKING),
+    (Instruction::ExtendLock, PAUSE_STAKING),
+    (Instruction::UpdateTimeMultiplier, PAUSE_CLAIMING),
+    (Instruction::ProcessAutoCompound, PAUSE_COMPOUNDING),
+    (Instruction::ClaimRewards, PAUSE_CLAIMING),
+    (Instruction::ClaimAllRewards, PAUSE_CLAIMING),
+    (Instruction::ClaimRewardIou, PAUSE_CLAIMING),
+    (Instruction::WithdrawVested, PAUSE_CLAIMING),
+    (Instruction::ExitVesting, PAUSE_CLAIMING),
+    (Instruction::TransferStake, PAUSE_CLAIMING),
+    (Instruction::UnstakeNft, PAUSE_UNSTAKING),
+    (Instruction::EmergencyUnstakeNft, PAUSE_EMERGENCY_EXIT),
+];
+
+#[test]
+fn each_instruction_is_blocked_by_its_flag() {
+    assert_eq!(Instruction::ALL.len(), INSTRUCTION_FLAGS.len());
+    for (instruction, flag) in INSTRUCTION_FLAGS {
+        assert!(Instruction::ALL.contains(&instruction));
+        assert_eq!(instruction.pause_flag(), flag, "{instruction:?}");
+    }
+}
+
+#[test]
+fn instructions_run_unless_their_own_flag_is_set() {
+    for flags in 0..=PAUSE_ALL {
+        for (instruction, flag) in INSTRUCTION_FLAGS {
+            assert_eq!(
+                instruction.is_allowed(flags),
+                flags & flag == 0,
+                "{instruction:?} under {flags:#07b}"
+            );
+        }
+    }
+}
+
+#[test]
+fn every_flag_blocks_some_instruction() {
+    for operation in OPERATIONS {
+        assert!(
+            Instruction::ALL
+                .iter()
+                .any(|instruction| instruction.pause_flag() == operation),
+            "{operation:#07b}"
+        );
+    }
+}
+
+#[test]
+fn incident_mode_keeps_both_unstake_instructions_open() {
+    let flags = PAUSE_STAKING | PAUSE_CLAIMING | PAUSE_COMPOUNDING;
+    for instruction in Instruction::ALL {
+        let is_exit = matches!(
+            instruction,
+            Instruction::UnstakeNft | Instruction::EmergencyUnstakeNft
+        );
+        assert_eq!(instruction.is_allowed(flags), is_exit, "{instruction:?}");
+    }
+}
@@ -0,0 +1,143 @@
+use tesola_staking_math::penalty::{
+    is_valid_curve, penalty_at, PenaltyPoint, DEFAULT_PENALTY_CURVE, MAX_PENALTY_POINTS,
+};
+
+mod common;
+
+fn point(progress_percent: u16, penalty_bps: u16) -> PenaltyPoint {
+    PenaltyPoint {
+        progress_percent,
+        penalty_bps,
+    }
+}
+
+#[test]
+fn default_curve_matches_the_fixed_bands() {
+    assert!(is_valid_curve(&DEFAULT_PENALTY_CURVE));
+
+    for progress in 0..=150 {
+        let expected = if progress < 30 {
+            5000
+        } else if progress < 60 {
+            3000
+        } else if progress < 90 {
+            1500
+        } else {
+            500
+        };
+        let (bps, _) = penalty_at(&DEFAULT_PENALTY_CURVE, progress);
+        assert_eq!(bps, expected, "progress {progress}");
+    }
+}
+
+#[test]
+fn penalty_is_interpolated_between_points() {
+    let curve = [point(0, 5000), point(50, 1000), point(100, 0)];
+
+    assert_eq!(penalty_at(&curve, 0), (5000, 0));
+    assert_eq!(penalty_at(&curve, 25), (3000, 0));
+    assert_eq!(penalty_at(&curve, 50), (1000, 1));
+    assert_eq!(penalty_at(&curve, 75), (500, 1));
+    assert_eq!(penalty_at(&curve, 100), (0, 2));
+    assert_eq!(penalty_at(&curve, 400), (0, 2));
+}
+
+#[test]
+fn negative_progress_uses_the_first_point() {
+    let curve = [point(0, 4000), point(100, 0)];
+    assert_eq!(penalty_at(&curve, -20), (4000, 0));
+}
+
+#[test]
+fn invalid_curves_are_rejected() {
+    assert!(!is_valid_curve(&[]));
+    assert!(!is_valid_curve(&[point(10, 5000)]));
+    assert!(!is_valid_curve(&[point(0, 5000), point(0, 1000)]));
+    assert!(!is_valid_curve(&[
+        point(0, 5000),
+        point(50, 1000),
+        point(40, 0)
+    ]));
+    assert!(!is_valid_curve(&[point(0, 10_001)]));
+    assert!(!is_valid_curve(&[point(0, 0); MAX_PENALTY_POINTS + 1]));
+    assert!(is_valid_curve(&[point(0, 10_000)]));
+}
+
+mod fuzz {
+    use super::common::pool;
+    use quickcheck::{quickcheck, Arbitrary, Gen};
+    use tesola_staking_math::accounts::{StakeInfoAccount, STAKE_INFO_DISCRIMINATOR};
+    use tesola_staking_math::penalty::{progress_percentage, DEFAULT_PENALTY_CURVE};
+    use tesola_staking_math::{emergency_unstake_quote, Emission, EmissionEpoch, StakeSnapshot};
+
+    #[derive(Clone, Debug)]
+    struct AnyStake(StakeSnapshot);
+
+    impl Arbitrary for AnyStake {
+        fn arbitrary(g: &mut Gen) -> Self {
+            AnyStake(StakeSnapshot {
+                staked_at: i64::arbitrary(g),
+                release_date: i64::arbitrary(g),
+                last_claim_time: i64::arbitrary(g),
+                staking_period: u64::arbitrary(g),
+                tier: u8::arbitrary(g),
+                current_time_multiplier: u64::arbitrary(g),
+                compound_streak_multiplier: u64::arbitrary(g),
+                rarity_bonus_bps: u64::arbitrary(g),
+                lock_boost_bps: u64::arbitrary(g),
+            })
+        }
+    }
+
+    fn check_quote(stake: &StakeSnapshot, emission: Emission, now: i64) -> bool {
+        let Ok(quote) =
+            emergency_unstake_quote(stake, &pool(), emission, &DEFAULT_PENALTY_CURVE, now)
+        else {
+            // Overflow is reported, never a panic
+            return true;
+        };
+
+        let conserved = quote.penalty_amount + quote.final_rewards == quote.earned_rewards;
+        let penalty_free_after_release =
+            now < stake.release_date || (quote.lock_completed && quote.penalty_amount == 0);
+        conserved && quote.progress_percentage <= 100 && penalty_free_after_release
+    }
+
+    quickcheck! {
+        fn flat_quote_is_well_formed(stake: AnyStake, now: i64) -> bool {
+            check_quote(&stake.0, Emission::Flat, now)
+        }
+
+        fn scheduled_quote_is_well_formed(
+            stake: AnyStake,
+            now: i64,
+            activated_at: i64,
+            start_time: i64,
+            end_time: i64
+        ) -> bool {
+            let epochs = [EmissionEpoch { start_time, end_time, reward_rate: 50_000_000 }];
+            let emission = Emission::Scheduled { epochs: &epochs, activated_at };
+            check_quote(&stake.0, emission, now)
+        }
+
+        fn progress_is_capped(staked_at: i64, now: i64, staking_period: u64) -> bool {
+            progress_percentage(staked_at, now, staking_period) <= 100
+        }
+
+        fn arbitrary_stake_info_bytes_never_panic(body: Vec<u8>, now: i64) -> bool {
+            let mut data = STAKE_INFO_DISCRIMINATOR.to_vec();
+            data.extend(body);
+            match StakeInfoAccount::decode(&data) {
+                Ok(account) => check_quote(&account.snapshot(), Emission::Flat, now),
+                Err(_) => true,
+            }
+        }
+    }
+
+    #[test]
+    fn zero_day_period_counts_as_complete() {
+        assert_eq!(progress_percentage(0, 0, 0), 100);
+        assert_eq!(progress_percentage(i64::MIN, i64::MAX, 1), 100);
+        assert_eq!(progress_percentage(i64::MAX, i64::MIN, 30), 0);
+    }
+}
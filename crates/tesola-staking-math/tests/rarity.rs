@@ -0,0 +1,115 @@
+use sha3::{Digest, Keccak256};
+use tesola_staking_math::rarity::{hash_pair, verify, RarityLeaf, MAX_RARITY_BONUS_BPS};
+use tesola_staking_math::rewards::daily_reward;
+use tesola_staking_math::tiers::DEFAULT_TIER_MULTIPLIERS;
+use tesola_staking_math::{PoolParams, StakeSnapshot, TierMultipliers};
+
+fn keccak(parts: &[&[u8]]) -> [u8; 32] {
+    let mut hasher = Keccak256::new();
+    for part in parts {
+        hasher.update(part);
+    }
+    hasher.finalize().into()
+}
+
+fn leaf(seed: u8, tier: u8, bonus_bps: u16) -> RarityLeaf {
+    RarityLeaf {
+        mint: [seed; 32],
+        tier,
+        bonus_bps,
+    }
+}
+
+/// Root of a four-leaf tree and the proof of each leaf
+fn tree(leaves: &[RarityLeaf; 4]) -> ([u8; 32], Vec<Vec<[u8; 32]>>) {
+    let hashes: Vec<[u8; 32]> = leaves.iter().map(|leaf| leaf.hash(keccak)).collect();
+    let left = hash_pair(keccak, &hashes[0], &hashes[1]);
+    let right = hash_pair(keccak, &hashes[2], &hashes[3]);
+    let root = hash_pair(keccak, &left, &right);
+
+    let proofs = vec![
+        vec![hashes[1], right],
+        vec![hashes[0], right],
+        vec![hashes[3], left],
+        vec![hashes[2], left],
+    ];
+    (root, proofs)
+}
+
+fn leaves() -> [RarityLeaf; 4] {
+    [
+        leaf(1, 0, 0),
+        leaf(2, 3, 0),
+        leaf(3, 4, 2_500),
+        leaf(4, 5, 10_000),
+    ]
+}
+
+#[test]
+fn every_leaf_proves_against_the_root() {
+    let leaves = leaves();
+    let (root, proofs) = tree(&leaves);
+
+    for (leaf, proof) in leaves.iter().zip(&proofs) {
+        assert!(verify(keccak, leaf, proof, &root));
+    }
+}
+
+#[test]
+fn claiming_a_different_tier_or_bonus_fails() {
+    let leaves = leaves();
+    let (root, proofs) = tree(&leaves);
+
+    let upgraded_tier = RarityLeaf {
+        tier: 5,
+        ..leaves[1]
+    };
+    let inflated_bonus = RarityLeaf {
+        bonus_bps: 5_000,
+        ..leaves[2]
+    };
+    assert!(!verify(keccak, &upgraded_tier, &proofs[1], &root));
+    assert!(!verify(keccak, &inflated_bonus, &proofs[2], &root));
+}
+
+#[test]
+fn inner_node_cannot_pass_as_a_leaf() {
+    let leaves = leaves();
+    let (root, proofs) = tree(&leaves);
+
+    // The left subtree hash proven with only the right subtree as sibling
+    let left = hash_pair(keccak, &leaves[0].hash(keccak), &leaves[1].hash(keccak));
+    let forged = RarityLeaf {
+        mint: left,
+        ..RarityLeaf::default()
+    };
+    assert!(!verify(keccak, &forged, &proofs[0][1..], &root));
+}
+
+#[test]
+fn bonus_above_cap_is_rejected() {
+    let over_cap = leaf(9, 0, MAX_RARITY_BONUS_BPS + 1);
+    let root = over_cap.hash(keccak);
+
+    assert!(!verify(keccak, &over_cap, &[], &root));
+}
+
+#[test]
+fn rarity_bonus_raises_daily_reward() {
+    let pool = PoolParams {
+        reward_rate: 100_000_000,
+        tiers: TierMultipliers::new(&DEFAULT_TIER_MULTIPLIERS).unwrap(),
+        ..PoolParams::default()
+    };
+    let plain = StakeSnapshot {
+        tier: 1,
+        ..StakeSnapshot::default()
+    };
+    let bonus = StakeSnapshot {
+        rarity_bonus_bps: 2_500,
+        ..plain
+    };
+
+    assert_eq!(daily_reward(&plain, &pool).unwrap(), 200_000_000);
+    assert_eq!(daily_reward(&bonus, &pool).unwrap(), 250_000_000);
+}
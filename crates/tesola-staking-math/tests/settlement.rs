@@ -0,0 +1,131 @@
+use tesola_staking_math::compound::compound_streak_multiplier;
+use tesola_staking_math::{
+    calculate_rewards, settle_rewards, Emission, StakeSnapshot, SECONDS_PER_DAY,
+};
+
+mod common;
+
+use common::{pool, START};
+
+fn stake() -> StakeSnapshot {
+    StakeSnapshot {
+        current_time_multiplier: 500,
+        ..common::stake(2)
+    }
+}
+
+#[test]
+fn compounding_daily_matches_single_claim() {
+    let pool = pool();
+
+    for n in 1..=4 {
+        let mut compounded = stake();
+        let mut total = 0u64;
+        for day in 1..=n {
+            total += settle_rewards(
+                &mut compounded,
+                &pool,
+                Emission::Flat,
+                START + day * SECONDS_PER_DAY,
+            )
+            .unwrap();
+        }
+
+        let mut claimed = stake();
+        let single = settle_rewards(
+            &mut claimed,
+            &pool,
+            Emission::Flat,
+            START + n * SECONDS_PER_DAY,
+        )
+        .unwrap();
+
+        assert_eq!(total, single, "{n} compounds");
+        assert_eq!(compounded.last_claim_time, claimed.last_claim_time);
+    }
+}
+
+#[test]
+fn compounding_across_a_streak_threshold_matches_single_claims_per_streak_level() {
+    let pool = pool();
+    let threshold = 5;
+
+    for n in threshold + 1..2 * threshold {
+        // Compound daily the way `auto_compound` does: settle, then bump the streak
+        let mut compounded = stake();
+        let mut streak = 0u16;
+        let mut total = 0u64;
+        for day in 1..=n {
+            total += settle_rewards(
+                &mut compounded,
+                &pool,
+                Emission::Flat,
+                START + day * SECONDS_PER_DAY,
+            )
+            .unwrap();
+            streak += 1;
+            compounded.compound_streak_multiplier = compound_streak_multiplier(streak);
+        }
+        assert_eq!(compounded.compound_streak_multiplier, 100, "{n} compounds");
+
+        // The +1% only applies after the fifth compound, so the same stretch
+        // settled once before and once after the threshold pays the same
+        let mut claimed = stake();
+        let mut single = settle_rewards(
+            &mut claimed,
+            &pool,
+            Emission::Flat,
+            START + threshold * SECONDS_PER_DAY,
+        )
+        .unwrap();
+        claimed.compound_streak_multiplier = compound_streak_multiplier(threshold as u16);
+        single += settle_rewards(
+            &mut claimed,
+            &pool,
+            Emission::Flat,
+            START + n * SECONDS_PER_DAY,
+        )
+        .unwrap();
+
+        assert_eq!(total, single, "{n} compounds");
+        assert_eq!(compounded.last_claim_time, claimed.last_claim_time);
+
+        // And the boosted days really did earn more than unboosted ones
+        let unboosted =
+            calculate_rewards(&stake(), &pool, Emission::Flat, START + n * SECONDS_PER_DAY)
+                .unwrap();
+        assert!(total > unboosted, "{n} compounds");
+    }
+}
+
+#[test]
+fn compounding_at_arbitrary_times_never_exceeds_single_claim() {
+    let pool = pool();
+    let checkpoints = [3_601, 86_400 + 17, 200_000, 200_001, 345_599, 400_000];
+
+    let mut compounded = stake();
+    let mut total = 0u64;
+    for offset in checkpoints {
+        total += settle_rewards(&mut compounded, &pool, Emission::Flat, START + offset).unwrap();
+    }
+
+    let end = START + checkpoints[checkpoints.len() - 1];
+    let single = calculate_rewards(&stake(), &pool, Emission::Flat, end).unwrap();
+
+    // Each partial day rounds down, so splitting may lose dust but never pays twice
+    assert!(total <= single);
+    assert!(single - total < checkpoints.len() as u64);
+}
+
+#[test]
+fn settling_twice_at_the_same_time_pays_nothing_more() {
+    let pool = pool();
+    let mut stake = stake();
+    let now = START + 10 * SECONDS_PER_DAY;
+
+    assert!(settle_rewards(&mut stake, &pool, Emission::Flat, now).unwrap() > 0);
+    assert_eq!(
+        settle_rewards(&mut stake, &pool, Emission::Flat, now).unwrap(),
+        0
+    );
+}
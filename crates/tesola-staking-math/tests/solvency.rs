@@ -0,0 +1,364 @@
+use quickcheck::quickcheck;
+use tesola_staking_math::solvency::{
+    accrual_weight, claim_payout, pro_rata_payout, solvency_ratio_bps, Accrual, AccrualPoint,
+    Payout,
+};
+use tesola_staking_math::tiers::DEFAULT_TIER_MULTIPLIERS;
+use tesola_staking_math::{
+    calculate_rewards, settle_rewards, Emission, EmissionEpoch, PoolParams, TierMultipliers,
+    SECONDS_PER_DAY,
+};
+
+mod common;
+
+use common::{pool, stake, START};
+
+#[test]
+fn ratio_is_full_without_liabilities_and_proportional_otherwise() {
+    assert_eq!(solvency_ratio_bps(0, 0), 10_000);
+    assert_eq!(solvency_ratio_bps(500, 0), 10_000);
+    assert_eq!(solvency_ratio_bps(500, 1_000), 5_000);
+    assert_eq!(solvency_ratio_bps(3_000, 1_000), 30_000);
+    assert_eq!(solvency_ratio_bps(u64::MAX, 1), u64::MAX);
+}
+
+#[test]
+fn covered_vault_pays_in_full() {
+    assert_eq!(
+        pro_rata_payout(400, 1_000, 1_000),
+        Payout {
+            paid: 400,
+            shortfall: 0
+        }
+    );
+}
+
+#[test]
+fn short_vault_pays_every_claimant_the_same_share() {
+    // Vault covers 60% of what is owed across the pool
+    let first = pro_rata_payout(500, 600, 1_000);
+    let second = pro_rata_payout(333, 600, 1_000);
+
+    assert_eq!(
+        first,
+        Payout {
+            paid: 300,
+            shortfall: 200
+        }
+    );
+    // Rounds down, the remainder stays owed
+    assert_eq!(
+        second,
+        Payout {
+            paid: 199,
+            shortfall: 134
+        }
+    );
+    assert_eq!(pro_rata_payout(500, 0, 1_000).paid, 0);
+}
+
+#[test]
+fn strict_pools_only_pay_claims_the_vault_covers() {
+    assert_eq!(
+        claim_payout(400, 400, 1_000, false),
+        Some(Payout {
+            paid: 400,
+            shortfall: 0
+        })
+    );
+    assert_eq!(claim_payout(400, 399, 1_000, false), None);
+    assert_eq!(
+        claim_payout(400, 399, 1_000, true),
+        Some(pro_rata_payout(400, 399, 1_000))
+    );
+}
+
+#[test]
+fn unstaking_from_a_short_vault_pays_its_share_and_keeps_an_iou() {
+    let pool = pool();
+    let day_30 = START + 30 * SECONDS_PER_DAY;
+    let mut leaving = stake(2);
+    let staying = stake(0);
+
+    // Settle the leaving stake, the other one's rewards are still accruing
+    let earned = settle_rewards(&mut leaving, &pool, Emission::Flat, day_30).unwrap();
+    let outstanding = earned + calculate_rewards(&staying, &pool, Emission::Flat, day_30).unwrap();
+    let vault = outstanding / 4;
+
+    // A strict pool still refuses the exit, an underfunded one pays its share
+    assert_eq!(claim_payout(earned, vault, outstanding, false), None);
+    let payout = claim_payout(earned, vault, outstanding, true).unwrap();
+    assert_eq!(payout, pro_rata_payout(earned, vault, outstanding));
+    assert_eq!(payout.paid + payout.shortfall, earned);
+    assert!(payout.paid > 0 && payout.shortfall > 0);
+
+    // The IOU stays a liability and is paid in full once the vault is refilled
+    let outstanding = outstanding - payout.paid;
+    let refilled = vault - payout.paid + outstanding;
+    assert_eq!(
+        claim_payout(payout.shortfall, refilled, outstanding, true),
+        Some(Payout {
+            paid: payout.shortfall,
+            shortfall: 0
+        })
+    );
+}
+
+#[test]
+fn pending_accrual_bounds_flat_rewards_of_every_stake() {
+    let pool = pool();
+    let stakes = [stake(0), stake(2), stake(3)];
+
+    let mut accrual = Accrual::default();
+    let start = AccrualPoint::at(Emission::Flat, START);
+    for stake in &stakes {
+        accrual
+            .track(accrual_weight(stake, &pool).unwrap(), start)
+            .unwrap();
+    }
+
+    for days in [0, 1, 7, 30, 365] {
+        let now = START + days * SECONDS_PER_DAY;
+        let owed: u64 = stakes
+            .iter()
+            .map(|s| calculate_rewards(s, &pool, Emission::Flat, now).unwrap())
+            .sum();
+        let pending = accrual
+            .pending(pool.reward_rate, AccrualPoint::at(Emission::Flat, now))
+            .unwrap();
+
+        assert!(pending >= owed, "{days} days");
+    }
+}
+
+#[test]
+fn pending_accrual_is_tight_when_multipliers_are_at_their_cap() {
+    let pool = pool();
+    let mut capped = stake(1);
+    capped.current_time_multiplier = pool.max_time_multiplier;
+
+    let mut accrual = Accrual::default();
+    accrual
+        .track(
+            accrual_weight(&capped, &pool).unwrap(),
+            AccrualPoint::at(Emission::Flat, START),
+        )
+        .unwrap();
+
+    let now = START + 10 * SECONDS_PER_DAY + 1234;
+    let owed = calculate_rewards(&capped, &pool, Emission::Flat, now).unwrap();
+    let pending = accrual
+        .pending(pool.reward_rate, AccrualPoint::at(Emission::Flat, now))
+        .unwrap();
+
+    assert!(pending >= owed);
+    assert!(pending - owed <= 1);
+}
+
+#[test]
+fn settling_a_stake_clears_its_share_exactly() {
+    let pool = pool();
+    let mut settled = stake(0);
+    let idle = stake(3);
+
+    let mut accrual = Accrual::default();
+    let start = AccrualPoint::at(Emission::Flat, START);
+    let mut entry = accrual
+        .track(accrual_weight(&settled, &pool).unwrap(), start)
+        .unwrap();
+    accrual
+        .track(accrual_weight(&idle, &pool).unwrap(), start)
+        .unwrap();
+
+    // Settle one stake at day 20 and start tracking it again from there
+    let day_20 = START + 20 * SECONDS_PER_DAY;
+    settle_rewards(&mut settled, &pool, Emission::Flat, day_20).unwrap();
+    accrual.untrack(&entry);
+    entry = accrual
+        .track(
+            accrual_weight(&settled, &pool).unwrap(),
+            AccrualPoint::at(Emission::Flat, day_20),
+        )
+        .unwrap();
+
+    let idle_only = {
+        let mut only = Accrual::default();
+        only.track(accrual_weight(&idle, &pool).unwrap(), start)
+            .unwrap();
+        only
+    };
+    let at_day_20 = AccrualPoint::at(Emission::Flat, day_20);
+    assert_eq!(
+        accrual.pending(pool.reward_rate, at_day_20).unwrap(),
+        idle_only.pending(pool.reward_rate, at_day_20).unwrap()
+    );
+
+    // Removing the rest leaves nothing behind
+    accrual.untrack(&entry);
+    let mut rest = Accrual::default();
+    let idle_entry = rest
+        .track(accrual_weight(&idle, &pool).unwrap(), start)
+        .unwrap();
+    accrual.untrack(&idle_entry);
+    assert_eq!(accrual, Accrual::default());
+}
+
+#[test]
+fn reward_rate_changes_reprice_pending_accrual_like_claims_do() {
+    let pool = pool();
+    let stake = stake(2);
+
+    let mut accrual = Accrual::default();
+    accrual
+        .track(
+            accrual_weight(&stake, &pool).unwrap(),
+            AccrualPoint::at(Emission::Flat, START),
+        )
+        .unwrap();
+
+    let now = START + 15 * SECONDS_PER_DAY;
+    let raised = PoolParams {
+        reward_rate: pool.reward_rate * 3,
+        ..pool
+    };
+    let owed = calculate_rewards(&stake, &raised, Emission::Flat, now).unwrap();
+    let pending = accrual
+        .pending(raised.reward_rate, AccrualPoint::at(Emission::Flat, now))
+        .unwrap();
+
+    assert!(pending >= owed);
+}
+
+#[test]
+fn raised_multipliers_rescale_weights_until_stakes_settle() {
+    let pool = pool();
+    let stake = stake(1);
+
+    let mut accrual = Accrual::default();
+    let entry = accrual
+        .track(
+            accrual_weight(&stake, &pool).unwrap(),
+            AccrualPoint::at(Emission::Flat, START),
+        )
+        .unwrap();
+
+    // Rare goes from 2x to 5x, Epic drops: only the largest growth counts
+    let mut multipliers = DEFAULT_TIER_MULTIPLIERS;
+    multipliers[1] = 500;
+    multipliers[2] = 300;
+    let raised = PoolParams {
+        tiers: TierMultipliers::new(&multipliers).unwrap(),
+        ..pool
+    };
+    accrual.rescale(&[(200, 500), (400, 300)]).unwrap();
+    assert_eq!(accrual.scale_bps, 25_000);
+
+    let now = START + 30 * SECONDS_PER_DAY;
+    let owed = calculate_rewards(&stake, &raised, Emission::Flat, now).unwrap();
+    let pending = accrual
+        .pending(raised.reward_rate, AccrualPoint::at(Emission::Flat, now))
+        .unwrap();
+    assert!(pending >= owed);
+
+    // Tracked again after the change, the stake counts at its new weight
+    accrual.untrack(&entry);
+    let point = AccrualPoint::at(Emission::Flat, now);
+    let weight = accrual_weight(&stake, &raised).unwrap();
+    accrual.track(weight, point).unwrap();
+    let later = START + 31 * SECONDS_PER_DAY;
+    let mut fresh = Accrual::default();
+    fresh.track(weight, point).unwrap();
+    let at_later = AccrualPoint::at(Emission::Flat, later);
+    let rescaled = accrual.pending(raised.reward_rate, at_later).unwrap();
+    let exact = fresh.pending(raised.reward_rate, at_later).unwrap();
+    assert!(rescaled >= exact && rescaled - exact <= 1);
+
+    // Lowered multipliers leave the scale alone
+    accrual.rescale(&[(500, 200)]).unwrap();
+    assert_eq!(accrual.scale_bps, 25_000);
+}
+
+#[test]
+fn scheduled_accrual_follows_epochs_and_stops_when_the_campaign_ends() {
+    let pool = pool();
+    let activated_at = START + 5 * SECONDS_PER_DAY;
+    let epochs = [
+        EmissionEpoch {
+            start_time: activated_at,
+            end_time: activated_at + 10 * SECONDS_PER_DAY,
+            reward_rate: 300_000_000,
+        },
+        // Gap of 5 days with no emission
+        EmissionEpoch {
+            start_time: activated_at + 15 * SECONDS_PER_DAY,
+            end_time: activated_at + 20 * SECONDS_PER_DAY,
+            reward_rate: 50_000_000,
+        },
+    ];
+    let emission = Emission::Scheduled {
+        epochs: &epochs,
+        activated_at,
+    };
+    let mut capped = stake(0);
+    capped.current_time_multiplier = pool.max_time_multiplier;
+
+    let mut accrual = Accrual::default();
+    accrual
+        .track(
+            accrual_weight(&capped, &pool).unwrap(),
+            AccrualPoint::at(emission, START),
+        )
+        .unwrap();
+
+    let mut last_pending = 0;
+    for days in [2, 5, 9, 15, 18, 25, 30, 400] {
+        let now = START + days * SECONDS_PER_DAY;
+        let owed = calculate_rewards(&capped, &pool, emission, now).unwrap();
+        let pending = accrual
+            .pending(pool.reward_rate, AccrualPoint::at(emission, now))
+            .unwrap();
+
+        assert!(pending >= owed, "{days} days");
+        assert!(pending - owed <= 2, "{days} days");
+        assert!(pending >= last_pending, "{days} days");
+        last_pending = pending;
+    }
+
+    // Nothing accrues after the last epoch
+    let ended = AccrualPoint::at(emission, activated_at + 20 * SECONDS_PER_DAY);
+    let much_later = AccrualPoint::at(emission, activated_at + 900 * SECONDS_PER_DAY);
+    assert_eq!(ended, much_later);
+}
+
+quickcheck! {
+    fn pending_accrual_never_understates_flat_rewards(
+        stakes: Vec<(u8, u16, u16, u16, u32)>,
+        elapsed: u32
+    ) -> bool {
+        let pool = pool();
+        let now = START + elapsed as i64;
+        let mut accrual = Accrual::default();
+        let mut owed = 0u64;
+
+        for (tier, rarity_bonus_bps, lock_boost_bps, streak_bps, settled_ago) in stakes {
+            let mut stake = stake(tier % 4);
+            stake.rarity_bonus_bps = rarity_bonus_bps as u64;
+            stake.lock_boost_bps = lock_boost_bps as u64;
+            stake.compound_streak_multiplier = streak_bps as u64 % 1001;
+            stake.last_claim_time = now - settled_ago as i64 % (400 * SECONDS_PER_DAY);
+
+            accrual
+                .track(
+                    accrual_weight(&stake, &pool).unwrap(),
+                    AccrualPoint::at(Emission::Flat, stake.last_claim_time),
+                )
+                .unwrap();
+            owed += calculate_rewards(&stake, &pool, Emission::Flat, now).unwrap();
+        }
+
+        let pending = accrual
+            .pending(pool.reward_rate, AccrualPoint::at(Emission::Flat, now))
+            .unwrap();
+        pending >= owed
+    }
+}
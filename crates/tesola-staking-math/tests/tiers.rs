@@ -0,0 +1,63 @@
+use tesola_staking_math::accounts::{DecodeError, TierTableAccount, TIER_TABLE_DISCRIMINATOR};
+use tesola_staking_math::tiers::{TierMultipliers, DEFAULT_TIER_MULTIPLIERS, MAX_TIERS};
+
+fn tier_table_bytes(tiers: &[(&str, u64)]) -> Vec<u8> {
+    let mut data = TIER_TABLE_DISCRIMINATOR.to_vec();
+    data.extend_from_slice(&[7u8; 32]);
+    data.extend_from_slice(&(tiers.len() as u32).to_le_bytes());
+    for (name, multiplier) in tiers {
+        data.extend_from_slice(&(name.len() as u32).to_le_bytes());
+        data.extend_from_slice(name.as_bytes());
+        data.extend_from_slice(&multiplier.to_le_bytes());
+    }
+    data
+}
+
+#[test]
+fn tiers_past_legendary_have_their_own_multiplier() {
+    let table = TierMultipliers::new(&[100, 200, 400, 800, 1600, 5000]).unwrap();
+
+    assert!(table.contains(5));
+    assert_eq!(table.multiplier(4), 1600);
+    assert_eq!(table.multiplier(5), 5000);
+}
+
+#[test]
+fn unknown_tier_falls_back_to_common() {
+    let table = TierMultipliers::new(&DEFAULT_TIER_MULTIPLIERS).unwrap();
+
+    assert!(!table.contains(4));
+    assert_eq!(table.multiplier(4), 100);
+    assert_eq!(table.multiplier(u8::MAX), 100);
+}
+
+#[test]
+fn table_is_capped_at_max_tiers() {
+    assert!(TierMultipliers::new(&[100; MAX_TIERS]).is_some());
+    assert!(TierMultipliers::new(&[100; MAX_TIERS + 1]).is_none());
+}
+
+#[test]
+fn decodes_tier_table_account() {
+    let data = tier_table_bytes(&[
+        ("Common", 100),
+        ("Rare", 200),
+        ("Epic", 400),
+        ("Legendary", 800),
+        ("Mythic", 1600),
+    ]);
+    let table = TierTableAccount::decode(&data).unwrap();
+
+    assert_eq!(table.pool, [7u8; 32]);
+    assert_eq!(table.multipliers().as_slice(), &[100, 200, 400, 800, 1600]);
+}
+
+#[test]
+fn rejects_oversized_tier_name() {
+    let data = tier_table_bytes(&[("Extremely Legendary", 100)]);
+
+    assert_eq!(
+        TierTableAccount::decode(&data),
+        Err(DecodeError::TooManyEntries)
+    );
+}
@@ -0,0 +1,214 @@
+use quickcheck::quickcheck;
+use tesola_staking_math::accounts::{VestingScheduleAccount, VESTING_SCHEDULE_DISCRIMINATOR};
+use tesola_staking_math::vesting::{is_valid_config, VestingConfig, VestingState};
+use tesola_staking_math::SECONDS_PER_DAY;
+
+const START: i64 = 1_700_000_000;
+
+fn config() -> VestingConfig {
+    VestingConfig {
+        cliff_seconds: 7 * SECONDS_PER_DAY,
+        duration_seconds: 30 * SECONDS_PER_DAY,
+        early_exit_haircut_bps: 2000,
+    }
+}
+
+fn deposited(amount: u64) -> VestingState {
+    let mut state = VestingState::default();
+    state.deposit(&config(), amount, START).unwrap();
+    state
+}
+
+#[test]
+fn nothing_unlocks_before_the_cliff() {
+    let state = deposited(3_000);
+
+    assert_eq!(
+        state.withdrawable(&config(), START + 7 * SECONDS_PER_DAY - 1),
+        0
+    );
+    assert_eq!(
+        state.withdrawable(&config(), START + 7 * SECONDS_PER_DAY),
+        700
+    );
+}
+
+#[test]
+fn unlocks_linearly_until_fully_vested() {
+    let state = deposited(3_000);
+
+    assert_eq!(
+        state.withdrawable(&config(), START + 15 * SECONDS_PER_DAY),
+        1_500
+    );
+    assert_eq!(
+        state.withdrawable(&config(), START + 30 * SECONDS_PER_DAY),
+        3_000
+    );
+    assert_eq!(
+        state.withdrawable(&config(), START + 90 * SECONDS_PER_DAY),
+        3_000
+    );
+}
+
+#[test]
+fn withdrawals_never_pay_twice() {
+    let mut state = deposited(3_000);
+
+    assert_eq!(
+        state.withdraw(&config(), START + 15 * SECONDS_PER_DAY),
+        1_500
+    );
+    assert_eq!(state.withdraw(&config(), START + 15 * SECONDS_PER_DAY), 0);
+    assert_eq!(
+        state.withdraw(&config(), START + 30 * SECONDS_PER_DAY),
+        1_500
+    );
+}
+
+#[test]
+fn deposit_keeps_unlocked_amount_withdrawable() {
+    let mut state = deposited(3_000);
+    let later = START + 15 * SECONDS_PER_DAY;
+    state.deposit(&config(), 1_000, later).unwrap();
+
+    // 1500 unlocked stays available; the 4000 principal now starts 15 * 1000 / 4000
+    // days in, so nothing more unlocks at the deposit and all of it by day 33.75
+    assert_eq!(state.withdrawable(&config(), later), 1_500);
+    assert_eq!(state.locked(&config(), later), 2_500);
+    assert_eq!(state.start_time, START + 15 * SECONDS_PER_DAY / 4);
+    assert_eq!(
+        state.withdrawable(&config(), START + 30 * SECONDS_PER_DAY),
+        1_500 + 2_000
+    );
+    assert_eq!(
+        state.withdrawable(
+            &config(),
+            START + 33 * SECONDS_PER_DAY + SECONDS_PER_DAY * 3 / 4
+        ),
+        4_000
+    );
+}
+
+#[test]
+fn deposit_into_a_fully_unlocked_schedule_starts_over() {
+    let mut state = deposited(3_000);
+    let later = START + 40 * SECONDS_PER_DAY;
+    state.deposit(&config(), 1_000, later).unwrap();
+
+    assert_eq!(state.withdrawable(&config(), later), 3_000);
+    assert_eq!(state.locked(&config(), later), 1_000);
+    assert_eq!(
+        state.withdrawable(&config(), later + 7 * SECONDS_PER_DAY),
+        3_000 + 233
+    );
+}
+
+#[test]
+fn tiny_deposits_cannot_hold_back_a_schedule() {
+    // A delegate claiming dust every hour for the whole duration
+    let mut state = deposited(1_000_000);
+    let mut now = START;
+    let mut dust = 0;
+    while now < START + 30 * SECONDS_PER_DAY {
+        now += 3_600;
+        state.deposit(&config(), 1, now).unwrap();
+        dust += 1;
+    }
+
+    // At most the dust's share of the schedule is delayed
+    let unlocked = state.withdrawable(&config(), START + 30 * SECONDS_PER_DAY);
+    assert!(unlocked >= 1_000_000 - dust, "{unlocked}");
+    assert_eq!(
+        state.withdrawable(&config(), START + 60 * SECONDS_PER_DAY),
+        1_000_000 + dust
+    );
+}
+
+#[test]
+fn a_deposit_never_unlocks_anything_early() {
+    let mut state = deposited(3_000);
+    // Past the cliff; the deposit moves the start back before it
+    let later = START + 8 * SECONDS_PER_DAY;
+    let unlocked = state.withdraw(&config(), later);
+    assert_eq!(unlocked, 800);
+    state.deposit(&config(), 3_000, later).unwrap();
+
+    assert_eq!(state.withdrawable(&config(), later), 0);
+    assert_eq!(state.locked(&config(), later), 5_200);
+    // Until the new schedule vests past what was withdrawn, nothing more unlocks
+    assert_eq!(state.withdraw(&config(), later + SECONDS_PER_DAY), 0);
+    assert_eq!(state.locked(&config(), later + SECONDS_PER_DAY), 5_200);
+}
+
+#[test]
+fn exit_forfeits_the_haircut_on_locked_rewards_only() {
+    let mut state = deposited(3_000);
+    let quote = state.exit(&config(), START + 15 * SECONDS_PER_DAY);
+
+    assert_eq!(quote.unlocked, 1_500);
+    assert_eq!(quote.locked, 1_500);
+    assert_eq!(quote.haircut, 300);
+    assert_eq!(quote.payout, 2_700);
+    assert_eq!(state, VestingState::default());
+}
+
+#[test]
+fn rejects_invalid_configs() {
+    assert!(is_valid_config(&config()));
+    assert!(!is_valid_config(&VestingConfig {
+        duration_seconds: 0,
+        ..config()
+    }));
+    assert!(!is_valid_config(&VestingConfig {
+        cliff_seconds: 31 * SECONDS_PER_DAY,
+        ..config()
+    }));
+    assert!(!is_valid_config(&VestingConfig {
+        early_exit_haircut_bps: 10_001,
+        ..config()
+    }));
+}
+
+#[test]
+fn decodes_vesting_schedule_account() {
+    let mut data = VESTING_SCHEDULE_DISCRIMINATOR.to_vec();
+    data.extend_from_slice(&[7u8; 32]);
+    data.extend_from_slice(&[9u8; 32]);
+    data.extend_from_slice(&3_000u64.to_le_bytes());
+    data.extend_from_slice(&START.to_le_bytes());
+    data.extend_from_slice(&500u64.to_le_bytes());
+    data.extend_from_slice(&250u64.to_le_bytes());
+
+    let schedule = VestingScheduleAccount::decode(&data).unwrap();
+    assert_eq!(schedule.owner, [9u8; 32]);
+    assert_eq!(
+        schedule.state,
+        VestingState {
+            amount: 3_000,
+            start_time: START,
+            withdrawn: 500,
+            carried: 250,
+        }
+    );
+}
+
+quickcheck! {
+    fn deposits_and_withdrawals_conserve_tokens(steps: Vec<(u32, u32)>) -> bool {
+        let mut state = VestingState::default();
+        let mut now = START;
+        let mut deposited = 0u64;
+        let mut withdrawn = 0u64;
+
+        for (amount, wait) in steps {
+            state.deposit(&config(), amount as u64, now).unwrap();
+            deposited += amount as u64;
+            now += wait as i64 % (40 * SECONDS_PER_DAY);
+            withdrawn += state.withdraw(&config(), now);
+        }
+
+        let final_time = now + 30 * SECONDS_PER_DAY;
+        withdrawn += state.withdraw(&config(), final_time);
+        withdrawn == deposited
+    }
+}
@@ -0,0 +1,185 @@
+//! Browser bindings for TESOLA staking reward previews.
+//!
+//! Takes raw `StakeInfo` and `PoolState` account data as fetched from RPC and
+//! runs it through the same math the program uses, so the UI shows exactly
+//! what an on-chain claim or emergency unstake would produce.
+
+use tesola_staking_math::accounts::{
+    DecodeError, EmissionScheduleAccount, PoolStateAccount, StakeInfoAccount, TierTableAccount,
+};
+use tesola_staking_math::{self as math, Emission, MathError, PoolParams};
+use wasm_bindgen::prelude::*;
+
+/// Pending rewards for a stake at a given time
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug)]
+pub struct RewardPreview {
+    pub pending_rewards: u64,
+    pub accumulated_compound: u64,
+    pub reward_iou: u64,
+    pub daily_reward: u64,
+    pub time_multiplier: u64,
+}
+
+/// Result of an emergency unstake at a given time
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug)]
+pub struct EmergencyUnstakePreview {
+    pub earned_rewards: u64,
+    pub penalty_bps: u64,
+    pub penalty_amount: u64,
+    pub final_rewards: u64,
+    pub progress_percentage: u8,
+    pub curve_segment: u8,
+    pub lock_completed: bool,
+}
+
+/// Previews the rewards `calculate_rewards` would accrue at `now` (unix seconds)
+///
+/// Pools running an emission campaign also need the `EmissionSchedule`
+/// account data, and pools with a tier table the `TierTable` account data.
+#[wasm_bindgen]
+pub fn preview_rewards(
+    stake_info_bytes: &[u8],
+    pool_state_bytes: &[u8],
+    now: i64,
+    emission_schedule_bytes: Option<Vec<u8>>,
+    tier_table_bytes: Option<Vec<u8>>,
+) -> Result<RewardPreview, JsError> {
+    let accounts = Accounts::decode(
+        stake_info_bytes,
+        pool_state_bytes,
+        emission_schedule_bytes.as_deref(),
+        tier_table_bytes.as_deref(),
+    )?;
+    let stake = accounts.stake_info.snapshot();
+    let pool = accounts.params();
+    let emission = accounts.emission();
+    let pool_now = PoolParams {
+        reward_rate: emission.rate_at(pool.reward_rate, now),
+        ..pool
+    };
+
+    Ok(RewardPreview {
+        pending_rewards: math::calculate_rewards(&stake, &pool, emission, now)
+            .map_err(math_error)?,
+        accumulated_compound: accounts.stake_info.accumulated_compound,
+        reward_iou: accounts.stake_info.reward_iou,
+        daily_reward: math::rewards::daily_reward(&stake, &pool_now).map_err(math_error)?,
+        time_multiplier: accounts.stake_info.current_time_multiplier,
+    })
+}
+
+/// Previews the outcome of `emergency_unstake_nft` at `now` (unix seconds)
+#[wasm_bindgen]
+pub fn preview_emergency_unstake(
+    stake_info_bytes: &[u8],
+    pool_state_bytes: &[u8],
+    now: i64,
+    emission_schedule_bytes: Option<Vec<u8>>,
+    tier_table_bytes: Option<Vec<u8>>,
+) -> Result<EmergencyUnstakePreview, JsError> {
+    let accounts = Accounts::decode(
+        stake_info_bytes,
+        pool_state_bytes,
+        emission_schedule_bytes.as_deref(),
+        tier_table_bytes.as_deref(),
+    )?;
+    let quote = math::emergency_unstake_quote(
+        &accounts.stake_info.snapshot(),
+        &accounts.params(),
+        accounts.emission(),
+        accounts.pool_state.penalty_curve(),
+        now,
+    )
+    .map_err(math_error)?;
+
+    Ok(EmergencyUnstakePreview {
+        earned_rewards: quote.earned_rewards,
+        penalty_bps: quote.penalty_bps,
+        penalty_amount: quote.penalty_amount,
+        final_rewards: quote.final_rewards,
+        progress_percentage: quote.progress_percentage,
+        curve_segment: quote.curve_segment,
+        lock_completed: quote.lock_completed,
+    })
+}
+
+struct Accounts {
+    stake_info: StakeInfoAccount,
+    pool_state: PoolStateAccount,
+    emission_schedule: Option<EmissionScheduleAccount>,
+    tier_table: Option<TierTableAccount>,
+}
+
+impl Accounts {
+    fn decode(
+        stake_info_bytes: &[u8],
+        pool_state_bytes: &[u8],
+        emission_schedule_bytes: Option<&[u8]>,
+        tier_table_bytes: Option<&[u8]>,
+    ) -> Result<Self, JsError> {
+        let stake_info =
+            StakeInfoAccount::decode(stake_info_bytes).map_err(|e| decode_error("StakeInfo", e))?;
+        let pool_state =
+            PoolStateAccount::decode(pool_state_bytes).map_err(|e| decode_error("PoolState", e))?;
+
+        let emission_schedule = if pool_state.has_emission_schedule() {
+            let bytes = emission_schedule_bytes.ok_or_else(|| {
+                JsError::new("PoolState: pool uses an emission schedule, pass its account data")
+            })?;
+            let schedule = EmissionScheduleAccount::decode(bytes)
+                .map_err(|e| decode_error("EmissionSchedule", e))?;
+            Some(schedule)
+        } else {
+            None
+        };
+
+        let tier_table = if pool_state.has_tier_table() {
+            let bytes = tier_table_bytes.ok_or_else(|| {
+                JsError::new("PoolState: pool uses a tier table, pass its account data")
+            })?;
+            let table =
+                TierTableAccount::decode(bytes).map_err(|e| decode_error("TierTable", e))?;
+            Some(table)
+        } else {
+            None
+        };
+
+        Ok(Self {
+            stake_info,
+            pool_state,
+            emission_schedule,
+            tier_table,
+        })
+    }
+
+    fn params(&self) -> PoolParams {
+        self.pool_state.params(self.tier_table.as_ref())
+    }
+
+    fn emission(&self) -> Emission<'_> {
+        match &self.emission_schedule {
+            Some(schedule) => schedule.emission(),
+            None => Emission::Flat,
+        }
+    }
+}
+
+fn decode_error(account: &str, error: DecodeError) -> JsError {
+    match error {
+        DecodeError::WrongDiscriminator => {
+            JsError::new(&format!("{account}: not a {account} account"))
+        }
+        DecodeError::TooShort => JsError::new(&format!("{account}: account data too short")),
+        DecodeError::TooManyEntries => {
+            JsError::new(&format!("{account}: more entries than the program allows"))
+        }
+    }
+}
+
+fn math_error(error: MathError) -> JsError {
+    match error {
+        MathError::Overflow => JsError::new("Arithmetic overflow"),
+    }
+}
@@ -0,0 +1,78 @@
+use tesola_staking_math::penalty::PenaltyPoint;
+use tesola_staking_math::{
+    calculate_rewards, emergency_unstake_quote, Emission, StakeSnapshot, SECONDS_PER_DAY,
+};
+use tesola_staking_wasm::{preview_emergency_unstake, preview_rewards};
+
+#[path = "../../tesola-staking-math/tests/common/mod.rs"]
+mod common;
+
+use common::accounts::{pool_state, pool_state_bytes, stake_info, stake_info_bytes};
+use common::{pool, START};
+
+/// The fixture stake as `calculate_rewards` sees it
+fn snapshot() -> StakeSnapshot {
+    let stake = stake_info();
+    StakeSnapshot {
+        staked_at: stake.staked_at,
+        release_date: stake.release_date,
+        last_claim_time: stake.last_claim_time,
+        staking_period: stake.staking_period,
+        tier: stake.tier,
+        current_time_multiplier: stake.current_time_multiplier,
+        compound_streak_multiplier: stake.compound_streak_multiplier,
+        rarity_bonus_bps: stake.rarity_bonus_bps as u64,
+        lock_boost_bps: stake.lock_boost_bps as u64,
+    }
+}
+
+#[test]
+fn reward_preview_matches_calculate_rewards() {
+    let stake = stake_info();
+    let stake_bytes = stake_info_bytes(&stake);
+    let pool_bytes = pool_state_bytes(&pool_state());
+
+    for day in [10, 11, 45, 90, 120] {
+        let now = START + day * SECONDS_PER_DAY;
+        let preview = preview_rewards(&stake_bytes, &pool_bytes, now, None, None).unwrap();
+
+        assert_eq!(
+            preview.pending_rewards,
+            calculate_rewards(&snapshot(), &pool(), Emission::Flat, now).unwrap(),
+            "day {day}"
+        );
+        assert_eq!(preview.accumulated_compound, stake.accumulated_compound);
+        assert_eq!(preview.reward_iou, stake.reward_iou);
+        assert_eq!(preview.time_multiplier, stake.current_time_multiplier);
+    }
+}
+
+#[test]
+fn emergency_preview_matches_the_quote() {
+    let stake_bytes = stake_info_bytes(&stake_info());
+    let pool_bytes = pool_state_bytes(&pool_state());
+    let curve: Vec<PenaltyPoint> = pool_state()
+        .penalty_curve
+        .iter()
+        .map(|point| PenaltyPoint {
+            progress_percent: point.progress_percent,
+            penalty_bps: point.penalty_bps,
+        })
+        .collect();
+
+    for day in [10, 30, 89, 90, 120] {
+        let now = START + day * SECONDS_PER_DAY;
+        let preview =
+            preview_emergency_unstake(&stake_bytes, &pool_bytes, now, None, None).unwrap();
+        let quote =
+            emergency_unstake_quote(&snapshot(), &pool(), Emission::Flat, &curve, now).unwrap();
+
+        assert_eq!(preview.earned_rewards, quote.earned_rewards, "day {day}");
+        assert_eq!(preview.penalty_bps, quote.penalty_bps);
+        assert_eq!(preview.penalty_amount, quote.penalty_amount);
+        assert_eq!(preview.final_rewards, quote.final_rewards);
+        assert_eq!(preview.progress_percentage, quote.progress_percentage);
+        assert_eq!(preview.curve_segment, quote.curve_segment);
+        assert_eq!(preview.lock_completed, quote.lock_completed);
+    }
+}
@@ -1,11 +1,14 @@
 // pages/api/admin/update-tier-multipliers.js
-import { Connection, PublicKey, Transaction, TransactionInstruction } from '@solana/web3.js';
+import { Connection, PublicKey, Transaction, TransactionInstruction, SystemProgram } from '@solana/web3.js';
 
 // 프로그램 ID와 IDL
 const PROGRAM_ID = '4SfUyQkbeyz9jeJDsR5XiUf8DATVZJXtGG4JUsYsWzTs';
 
-// update_tier_multipliers 명령 식별자 (IDL에서 가져온 값)
-const UPDATE_TIER_MULTIPLIERS_DISCRIMINATOR = [25, 132, 162, 53, 130, 129, 248, 23];
+// queue_param_change 명령 식별자 - 승수 변경은 타임락을 거쳐 적용됨
+const QUEUE_PARAM_CHANGE_DISCRIMINATOR = [140, 242, 124, 63, 143, 237, 195, 231];
+
+// ParamChange::TierMultipliers 변형 인덱스
+const TIER_MULTIPLIERS_CHANGE_KIND = 1;
 
 export default async function handler(req, res) {
   if (req.method !== 'POST') {
@@ -25,7 +28,7 @@ export default async function handler(req, res) {
     console.log('Using pool state address:', poolStateAddress);
     
     // 요청 매개변수 확인
-    const { common, rare, epic, legendary } = req.body;
+    const { common, rare, epic, legendary, effectiveAt } = req.body;
     
     if (common === undefined || rare === undefined || epic === undefined || legendary === undefined) {
       return res.status(400).json({ error: '모든 등급 승수 값이 필요합니다' });
@@ -44,13 +47,19 @@ export default async function handler(req, res) {
     const legendaryBuf = Buffer.alloc(8);
     legendaryBuf.writeBigUInt64LE(BigInt(legendary));
     
+    // 적용 시각 (기본값: 지금부터 2일 후, 풀의 최소 지연 시간 이상이어야 함)
+    const effectiveAtBuf = Buffer.alloc(8);
+    effectiveAtBuf.writeBigInt64LE(BigInt(effectiveAt || Math.floor(Date.now() / 1000) + 2 * 86400));
+    
     // 명령 데이터 구성
     const instructionData = Buffer.concat([
-      Buffer.from(UPDATE_TIER_MULTIPLIERS_DISCRIMINATOR),
+      Buffer.from(QUEUE_PARAM_CHANGE_DISCRIMINATOR),
+      Buffer.from([TIER_MULTIPLIERS_CHANGE_KIND]),
       commonBuf,
       rareBuf,
       epicBuf,
-      legendaryBuf
+      legendaryBuf,
+      effectiveAtBuf
     ]);
     
     // Solana 연결 설정
@@ -62,10 +71,25 @@ export default async function handler(req, res) {
     const poolStatePubkey = new PublicKey(poolStateAddress);
     const adminPubkey = new PublicKey(adminWallet);
     
+    // 역할(Roles) PDA - 승수 변경은 rate_setter 역할이 필요
+    const [rolesPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("roles"), poolStatePubkey.toBuffer()],
+      programId
+    );
+    
+    // 대기 중인 변경 PDA (변경 종류별 하나)
+    const [pendingChangePDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("pending_change"), poolStatePubkey.toBuffer(), Buffer.from([TIER_MULTIPLIERS_CHANGE_KIND])],
+      programId
+    );
+    
     // 계정 배열 구성
     const accounts = [
-      { pubkey: adminPubkey, isSigner: true, isWritable: true }, // admin
-      { pubkey: poolStatePubkey, isSigner: false, isWritable: true } // pool_state
+      { pubkey: adminPubkey, isSigner: true, isWritable: true }, // rate_setter
+      { pubkey: poolStatePubkey, isSigner: false, isWritable: false }, // pool_state
+      { pubkey: rolesPDA, isSigner: false, isWritable: false }, // roles
+      { pubkey: pendingChangePDA, isSigner: false, isWritable: true }, // pending_change
+      { pubkey: SystemProgram.programId, isSigner: false, isWritable: false } // system_program
     ];
     
     // 트랜잭션 명령 생성
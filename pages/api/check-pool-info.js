@@ -46,17 +46,14 @@ export default async function handler(req, res) {
           const rewardRateBytes = poolStateAccount.data.slice(40, 48);
           const rewardRate = rewardRateBytes.readBigUInt64LE(0);
           
-          // emergency_fee_percent (1바이트 u8) 추출
-          const emergencyFeePercent = poolStateAccount.data[48];
-          
-          // paused (1바이트 bool) 추출
-          const paused = poolStateAccount.data[49] !== 0;
+          // pause_flags (1바이트 u8) 추출 - 비트별 일시정지 (스테이킹/청구/복리/언스테이킹/긴급 해제)
+          const pauseFlags = poolStateAccount.data[48];
           
           poolData = {
             admin: adminPubkey.toString(),
             reward_rate: rewardRate.toString(),
-            emergency_fee_percent: emergencyFeePercent,
-            paused: paused
+            pause_flags: pauseFlags,
+            paused: pauseFlags !== 0
           };
         } catch (parseError) {
           console.error('풀 데이터 파싱 오류:', parseError);
@@ -96,25 +96,26 @@ export default async function handler(req, res) {
     const rewardMintPubkey = new PublicKey(REWARD_MINT_ADDRESS);
     
     // PDA 생성 - IDL에 정의된 시드 사용
-    // 1. stake_info PDA 생성
-    const [stakeInfoPDA] = PublicKey.findProgramAddressSync(
-      [Buffer.from(STAKE_SEED), mintPubkey.toBuffer()],
+    // 1. pool_state PDA 생성 (컬렉션별 풀)
+    const collectionMintPubkey = new PublicKey(process.env.COLLECTION_MINT_ADDRESS);
+    const [poolStatePDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from(POOL_SEED), collectionMintPubkey.toBuffer()],
       programId
     );
     
-    console.log('Stake info PDA:', stakeInfoPDA.toString());
+    console.log('Pool state PDA:', poolStatePDA.toString());
     
-    // 2. pool_state PDA 생성
-    const [poolStatePDA] = PublicKey.findProgramAddressSync(
-      [Buffer.from(POOL_SEED)],
+    // 2. stake_info PDA 생성 (풀 키 포함)
+    const [stakeInfoPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from(STAKE_SEED), poolStatePDA.toBuffer(), mintPubkey.toBuffer()],
       programId
     );
     
-    console.log('Pool state PDA:', poolStatePDA.toString());
+    console.log('Stake info PDA:', stakeInfoPDA.toString());
     
     // 3. user_staking_info PDA 생성
     const [userStakingInfoPDA] = PublicKey.findProgramAddressSync(
-      [Buffer.from(USER_STAKING_SEED), walletPubkey.toBuffer()],
+      [Buffer.from(USER_STAKING_SEED), poolStatePDA.toBuffer(), walletPubkey.toBuffer()],
       programId
     );
     
@@ -122,7 +123,7 @@ export default async function handler(req, res) {
     
     // 4. reward_vault_authority PDA 생성
     const [rewardVaultAuthorityPDA] = PublicKey.findProgramAddressSync(
-      [Buffer.from("reward_vault_authority")],
+      [Buffer.from("reward_vault_authority"), poolStatePDA.toBuffer()],
       programId
     );
     
@@ -140,7 +141,7 @@ export default async function handler(req, res) {
       console.warn('Invalid reward vault address from env, deriving PDA');
       // 대안: reward_vault를 PDA로 생성할 수도 있음
       const [derivedVaultPDA] = PublicKey.findProgramAddressSync(
-        [Buffer.from("reward_vault"), rewardMintPubkey.toBuffer()],
+        [Buffer.from("reward_vault"), poolStatePDA.toBuffer(), rewardMintPubkey.toBuffer()],
         programId
       );
       rewardVaultAddress = derivedVaultPDA;
@@ -148,10 +149,18 @@ export default async function handler(req, res) {
     
     console.log('Reward vault address:', rewardVaultAddress.toString());
     
-    // 6. 사용자의 리워드 토큰 계정 (ATA) 찾기
+    // 6. 리워드 민트를 소유한 토큰 프로그램 확인 (SPL Token 또는 Token-2022)
+    const rewardMintInfo = await connection.getAccountInfo(rewardMintPubkey);
+    const rewardTokenProgramId = rewardMintInfo ? rewardMintInfo.owner : TOKEN_PROGRAM_ID;
+    
+    console.log('Reward token program:', rewardTokenProgramId.toString());
+    
+    // 사용자의 리워드 토큰 계정 (ATA) 찾기
     const userRewardTokenAccount = await getAssociatedTokenAddress(
-      rewardMintPubkey,  // 토큰 타입 (TESOLA)
-      walletPubkey       // 토큰 소유자
+      rewardMintPubkey,     // 토큰 타입 (TESOLA)
+      walletPubkey,         // 토큰 소유자
+      false,
+      rewardTokenProgramId  // 리워드 민트의 토큰 프로그램
     );
     
     console.log('User reward token account:', userRewardTokenAccount.toString());
@@ -159,6 +168,16 @@ export default async function handler(req, res) {
     // 사용자의 리워드 토큰 계정이 존재하는지 확인
     const userTokenAccountInfo = await connection.getAccountInfo(userRewardTokenAccount);
     
+    // 7. 베스팅 스케줄 PDA - 풀이 보상을 베스팅하는 경우 청구액이 여기에 적립됨
+    const [vestingSchedulePDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("vesting"), poolStatePDA.toBuffer(), walletPubkey.toBuffer()],
+      programId
+    );
+    const vestingScheduleInfo = await connection.getAccountInfo(vestingSchedulePDA);
+    const vestingScheduleAccount = vestingScheduleInfo ? vestingSchedulePDA : programId;
+    
+    console.log('Vesting schedule:', vestingScheduleInfo ? vestingSchedulePDA.toString() : '(none)');
+    
     // 트랜잭션 생성
     const tx = new Transaction();
     
@@ -169,7 +188,8 @@ export default async function handler(req, res) {
         walletPubkey,               // payer
         userRewardTokenAccount,     // associated token account address
         walletPubkey,               // owner
-        rewardMintPubkey            // mint
+        rewardMintPubkey,           // mint
+        rewardTokenProgramId        // token program
       );
       tx.add(createATAIx);
     }
@@ -178,28 +198,32 @@ export default async function handler(req, res) {
     const instructionData = createClaimRewardsInstructionData();
     
     // claim_rewards 명령어 계정 목록 - IDL에 정의된 순서와 정확하게 일치해야 함
+    // 서명자는 소유자 본인이거나 user_staking_info 에 등록된 위임자(delegate)일 수 있음
+    // emission_schedule / tier_table / vesting_schedule 은 선택 계정으로, 없으면 프로그램 ID 를 자리 표시자로 전달
     const accounts = [
-      { pubkey: walletPubkey, isSigner: true, isWritable: true },          // user (signer, writable)
+      { pubkey: walletPubkey, isSigner: true, isWritable: true },          // authority (owner or delegate, signer)
+      { pubkey: walletPubkey, isSigner: false, isWritable: false },        // owner
       { pubkey: mintPubkey, isSigner: false, isWritable: false },          // nft_mint
       { pubkey: stakeInfoPDA, isSigner: false, isWritable: true },         // stake_info (writable, PDA)
       { pubkey: poolStatePDA, isSigner: false, isWritable: true },         // pool_state (writable, PDA)
+      { pubkey: programId, isSigner: false, isWritable: false },           // emission_schedule (optional)
+      { pubkey: programId, isSigner: false, isWritable: false },           // tier_table (optional)
       { pubkey: userStakingInfoPDA, isSigner: false, isWritable: false },  // user_staking_info (PDA)
+      { pubkey: vestingScheduleAccount, isSigner: false, isWritable: !!vestingScheduleInfo }, // vesting_schedule (optional, PDA)
       { pubkey: rewardVaultAddress, isSigner: false, isWritable: true },   // reward_vault (writable)
       { pubkey: rewardVaultAuthorityPDA, isSigner: false, isWritable: false }, // reward_vault_authority (PDA)
-      { pubkey: userRewardTokenAccount, isSigner: false, isWritable: true }, // user_token_account (writable)
+      { pubkey: userRewardTokenAccount, isSigner: false, isWritable: true }, // user_token_account (reward_destination, 기본값은 소유자 ATA)
       { pubkey: rewardMintPubkey, isSigner: false, isWritable: false },    // reward_mint
       { pubkey: SystemProgram.programId, isSigner: false, isWritable: false }, // system_program
-      { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },    // token_program
-      { pubkey: ASSOCIATED_TOKEN_PROGRAM_ID, isSigner: false, isWritable: false }, // associated_token_program
-      { pubkey: new PublicKey('SysvarRent111111111111111111111111111111111'), isSigner: false, isWritable: false } // rent
+      { pubkey: rewardTokenProgramId, isSigner: false, isWritable: false } // token_program (SPL Token 또는 Token-2022)
     ];
     
     // 계정 목록 로깅
     console.log('계정 구조:');
     const accountNames = [
-      "user", "nft_mint", "stake_info", "pool_state", "user_staking_info", 
-      "reward_vault", "reward_vault_authority", "user_token_account", "reward_mint",
-      "system_program", "token_program", "associated_token_program", "rent"
+      "authority", "owner", "nft_mint", "stake_info", "pool_state", "emission_schedule", "tier_table",
+      "user_staking_info", "vesting_schedule", "reward_vault", "reward_vault_authority", "user_token_account", "reward_mint",
+      "system_program", "token_program"
     ];
     accounts.forEach((acc, idx) => {
       console.log(`${idx}. ${accountNames[idx]}: ${acc.pubkey.toString()} (isSigner: ${acc.isSigner}, isWritable: ${acc.isWritable})`);
@@ -8,6 +8,8 @@ import { createClient } from '@supabase/supabase-js';
 // 공통 모듈에서 필요한 유틸리티 가져오기
 import {
   PROGRAM_ID,
+  COLLECTION_MINT_ADDRESS,
+  findPoolStatePDA,
   findStakeInfoPDA,
   findUserStakingInfoPDA,
   createApiResponse
@@ -106,8 +108,9 @@ export default async function handler(req, res) {
     const connection = new Connection(SOLANA_RPC_ENDPOINT, 'confirmed');
     
     // 스테이크 정보 PDA 생성
-    const [stakeInfoPDA] = findStakeInfoPDA(mintPubkey);
-    const [userStakingInfoPDA] = findUserStakingInfoPDA(walletPubkey);
+    const [poolStatePDA] = findPoolStatePDA(COLLECTION_MINT_ADDRESS);
+    const [stakeInfoPDA] = findStakeInfoPDA(mintPubkey, poolStatePDA);
+    const [userStakingInfoPDA] = findUserStakingInfoPDA(walletPubkey, poolStatePDA);
     
     console.log('Stake Info PDA:', stakeInfoPDA.toString());
     console.log('User Staking Info PDA:', userStakingInfoPDA.toString());
@@ -10,6 +10,7 @@ import { getAssociatedTokenAddress } from '@solana/spl-token';
 // 공통 모듈에서 필요한 유틸리티 가져오기
 import {
   PROGRAM_ID,
+  COLLECTION_MINT_ADDRESS,
   NFT_TIERS,
   createSerializedTransaction,
   createTokenAccountInstruction,
@@ -25,6 +26,9 @@ import {
 
 // 환경 변수 가져오기
 const SOLANA_RPC_ENDPOINT = process.env.NEXT_PUBLIC_SOLANA_RPC_ENDPOINT || 'https://api.devnet.solana.com';
+
+// Metaplex Token Metadata 프로그램 (pNFT 는 에스크로 대신 위임 및 잠금으로 스테이킹)
+const TOKEN_METADATA_PROGRAM_ID = new PublicKey('metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s');
 const supabase = createClient(
   process.env.NEXT_PUBLIC_SUPABASE_URL || '',
   process.env.SUPABASE_SERVICE_ROLE_KEY || ''
@@ -46,6 +50,9 @@ function standardizeTier(tierValue) {
   return 'COMMON';
 }
 
+// 프로그램의 기본 잠금 옵션 (math::locks::DEFAULT_LOCK_OPTIONS)
+const LOCK_PERIOD_DAYS = [30, 90, 180, 365];
+
 /**
  * 예상 보상 계산 함수
  * 
@@ -135,11 +142,11 @@ export default async function handler(req, res) {
       );
     }
     
-    // 스테이킹 기간 검증
+    // 스테이킹 기간 검증 - 풀의 잠금 옵션(기본 30/90/180/365일)만 허용
     const stakingPeriodNum = parseInt(stakingPeriod, 10);
-    if (isNaN(stakingPeriodNum) || stakingPeriodNum < 7 || stakingPeriodNum > 365) {
+    if (!LOCK_PERIOD_DAYS.includes(stakingPeriodNum)) {
       return res.status(400).json(
-        createApiResponse(false, '스테이킹 기간은 7일에서 365일 사이여야 합니다', null, 'InvalidStakingPeriod')
+        createApiResponse(false, `스테이킹 기간은 ${LOCK_PERIOD_DAYS.join('/')}일 중 하나여야 합니다`, null, 'InvalidStakingPeriod')
       );
     }
     
@@ -257,10 +264,10 @@ export default async function handler(req, res) {
     const nftTierIndex = NFT_TIERS[nftTier] || NFT_TIERS.COMMON;
     
     // PDA 주소 생성
-    const [poolStatePDA] = findPoolStatePDA();
-    const [stakeInfoPDA] = findStakeInfoPDA(mintPubkey);
+    const [poolStatePDA] = findPoolStatePDA(COLLECTION_MINT_ADDRESS);
+    const [stakeInfoPDA] = findStakeInfoPDA(mintPubkey, poolStatePDA);
     const [escrowAuthorityPDA] = findEscrowAuthorityPDA(mintPubkey);
-    const [userStakingInfoPDA] = findUserStakingInfoPDA(walletPubkey);
+    const [userStakingInfoPDA] = findUserStakingInfoPDA(walletPubkey, poolStatePDA);
     
     console.log('Pool State PDA:', poolStatePDA.toString());
     console.log('Stake Info PDA:', stakeInfoPDA.toString());
@@ -280,6 +287,22 @@ export default async function handler(req, res) {
       true  // allow owner off curve
     );
     
+    // Token Metadata 계정 (메타데이터, 마스터 에디션, 토큰 레코드)
+    const metadataSeeds = [Buffer.from('metadata'), TOKEN_METADATA_PROGRAM_ID.toBuffer(), mintPubkey.toBuffer()];
+    const [metadataPDA] = PublicKey.findProgramAddressSync(metadataSeeds, TOKEN_METADATA_PROGRAM_ID);
+    const [editionPDA] = PublicKey.findProgramAddressSync(
+      [...metadataSeeds, Buffer.from('edition')],
+      TOKEN_METADATA_PROGRAM_ID
+    );
+    const [tokenRecordPDA] = PublicKey.findProgramAddressSync(
+      [...metadataSeeds, Buffer.from('token_record'), userTokenAccount.toBuffer()],
+      TOKEN_METADATA_PROGRAM_ID
+    );
+    
+    // 토큰 레코드는 pNFT 에만 존재함 (룰셋이 있는 pNFT 는 authorizationRules 도 전달해야 함)
+    const isPnft = !!(await connection.getAccountInfo(tokenRecordPDA));
+    console.log('pNFT 여부:', isPnft);
+    
     // 최근 블록해시 가져오기
     console.log('최근 블록해시 가져오는 중...');
     const blockHashData = await connection.getLatestBlockhash('confirmed');
@@ -295,12 +318,12 @@ export default async function handler(req, res) {
       // User Staking Info 초기화 명령어 추가
       console.log('User Staking Info 초기화 명령어 추가...');
       instructions.push(
-        createInitUserStakingInfoInstruction(walletPubkey, userStakingInfoPDA)
+        createInitUserStakingInfoInstruction(walletPubkey, poolStatePDA, userStakingInfoPDA)
       );
     }
     
-    // 2. Escrow 토큰 계정 존재 확인 및 필요시 생성 명령 추가
-    const escrowTokenAccountInfo = await connection.getAccountInfo(escrowTokenAccount);
+    // 2. Escrow 토큰 계정 존재 확인 및 필요시 생성 명령 추가 (일반 NFT 전용)
+    const escrowTokenAccountInfo = isPnft || await connection.getAccountInfo(escrowTokenAccount);
     if (!escrowTokenAccountInfo) {
       console.log('Escrow 토큰 계정 생성 명령어 추가...');
       const createEscrowATAIx = await createTokenAccountInstruction(
@@ -325,7 +348,9 @@ export default async function handler(req, res) {
         escrowTokenAccount,
         escrowAuthority: escrowAuthorityPDA,
         userTokenAccount,
-        userStakingInfo: userStakingInfoPDA
+        userStakingInfo: userStakingInfoPDA,
+        metadata: metadataPDA,
+        pnft: isPnft ? { edition: editionPDA, tokenRecord: tokenRecordPDA } : undefined
       }
     );
     instructions.push(stakeNftIx);
@@ -343,7 +368,7 @@ export default async function handler(req, res) {
     const initInstructions = [];
     if (!userStakingInfoExists) {
       initInstructions.push(
-        createInitUserStakingInfoInstruction(walletPubkey, userStakingInfoPDA)
+        createInitUserStakingInfoInstruction(walletPubkey, poolStatePDA, userStakingInfoPDA)
       );
     }
     if (!escrowTokenAccountInfo) {
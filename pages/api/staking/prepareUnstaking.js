@@ -5,11 +5,12 @@
 
 import { Connection, PublicKey } from '@solana/web3.js';
 import { createClient } from '@supabase/supabase-js';
-import { getAssociatedTokenAddress } from '@solana/spl-token';
+import { getAssociatedTokenAddress, createAssociatedTokenAccountInstruction } from '@solana/spl-token';
 
 // 공통 모듈에서 필요한 유틸리티 가져오기
 import {
   PROGRAM_ID,
+  COLLECTION_MINT_ADDRESS,
   createSerializedTransaction,
   createTokenAccountInstruction,
   createUnstakeNftInstruction,
@@ -17,12 +18,20 @@ import {
   findStakeInfoPDA,
   findEscrowAuthorityPDA,
   findUserStakingInfoPDA,
+  findRewardVaultAuthorityPDA,
+  findRewardStreamsPDA,
+  findStakeRewardStatePDA,
   getErrorMessage,
   createApiResponse
 } from '../../../shared';
 
 // 환경 변수 가져오기
 const SOLANA_RPC_ENDPOINT = process.env.NEXT_PUBLIC_SOLANA_RPC_ENDPOINT || 'https://api.devnet.solana.com';
+const REWARD_MINT_ADDRESS = process.env.REWARD_MINT_ADDRESS || '6aJNuLDLEeysFm9PNz1qQ2LbmQKiNaUvzpX1z6BkXQXz';
+const REWARD_VAULT_ADDRESS = process.env.REWARD_VAULT_ADDRESS || '4ne1k9RxuR6aRbGiAMjFMKc8pkwdsVMCpD7bRQprj143';
+
+// Metaplex Token Metadata 프로그램 (pNFT 는 에스크로 대신 위임 및 잠금으로 스테이킹됨)
+const TOKEN_METADATA_PROGRAM_ID = new PublicKey('metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s');
 const supabase = createClient(
   process.env.NEXT_PUBLIC_SUPABASE_URL || '',
   process.env.SUPABASE_SERVICE_ROLE_KEY || ''
@@ -40,6 +49,26 @@ function calculateDaysElapsed(startTimestamp, endTimestamp) {
   return Math.floor((endTimestamp - startTimestamp) / secondsInDay);
 }
 
+/**
+ * 보상 스트림 레지스트리 계정에서 스트림별 민트와 볼트 추출
+ * (식별자 8바이트, 풀 32바이트, 스트림 수 4바이트, 이후 스트림당 88바이트)
+ * 
+ * @param {Buffer} data - RewardStreams 계정 데이터
+ * @returns {Array<{mint: PublicKey, vault: PublicKey}>} 레지스트리 순서의 스트림 목록
+ */
+function decodeRewardStreams(data) {
+  const count = data.readUInt32LE(40);
+  const streams = [];
+  for (let i = 0; i < count; i++) {
+    const offset = 44 + i * 88;
+    streams.push({
+      mint: new PublicKey(data.subarray(offset, offset + 32)),
+      vault: new PublicKey(data.subarray(offset + 32, offset + 64))
+    });
+  }
+  return streams;
+}
+
 /**
  * 스테이킹 기간 완료 여부 검사 함수
  * 
@@ -197,10 +226,10 @@ export default async function handler(req, res) {
     console.log('언스테이킹 패널티 정보:', penaltyInfo);
     
     // PDA 주소 생성
-    const [poolStatePDA] = findPoolStatePDA();
-    const [stakeInfoPDA] = findStakeInfoPDA(mintPubkey);
+    const [poolStatePDA] = findPoolStatePDA(COLLECTION_MINT_ADDRESS);
+    const [stakeInfoPDA] = findStakeInfoPDA(mintPubkey, poolStatePDA);
     const [escrowAuthorityPDA] = findEscrowAuthorityPDA(mintPubkey);
-    const [userStakingInfoPDA] = findUserStakingInfoPDA(walletPubkey);
+    const [userStakingInfoPDA] = findUserStakingInfoPDA(walletPubkey, poolStatePDA);
     
     console.log('Pool State PDA:', poolStatePDA.toString());
     console.log('Stake Info PDA:', stakeInfoPDA.toString());
@@ -220,6 +249,57 @@ export default async function handler(req, res) {
       true  // allow owner off curve
     );
     
+    // Token Metadata 계정 (메타데이터, 마스터 에디션, 토큰 레코드)
+    const metadataSeeds = [Buffer.from('metadata'), TOKEN_METADATA_PROGRAM_ID.toBuffer(), mintPubkey.toBuffer()];
+    const [metadataPDA] = PublicKey.findProgramAddressSync(metadataSeeds, TOKEN_METADATA_PROGRAM_ID);
+    const [editionPDA] = PublicKey.findProgramAddressSync(
+      [...metadataSeeds, Buffer.from('edition')],
+      TOKEN_METADATA_PROGRAM_ID
+    );
+    const [tokenRecordPDA] = PublicKey.findProgramAddressSync(
+      [...metadataSeeds, Buffer.from('token_record'), userTokenAccount.toBuffer()],
+      TOKEN_METADATA_PROGRAM_ID
+    );
+    const isPnft = !!(await connection.getAccountInfo(tokenRecordPDA));
+    console.log('pNFT 여부:', isPnft);
+    
+    // 보상 지급 계정 - 언스테이킹 시 남은 보상이 함께 지급됨
+    const rewardMintPubkey = new PublicKey(REWARD_MINT_ADDRESS);
+    const rewardMintInfo = await connection.getAccountInfo(rewardMintPubkey);
+    const rewardTokenProgram = rewardMintInfo ? rewardMintInfo.owner : undefined;
+    const rewardTokenAccount = await getAssociatedTokenAddress(
+      rewardMintPubkey,
+      walletPubkey,
+      false,
+      rewardTokenProgram
+    );
+    const [rewardVaultAuthorityPDA] = findRewardVaultAuthorityPDA(poolStatePDA);
+    
+    // 베스팅 스케줄 PDA - 풀이 보상을 베스팅하는 경우 지급액이 여기에 적립됨
+    const [vestingSchedulePDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from('vesting'), poolStatePDA.toBuffer(), walletPubkey.toBuffer()],
+      new PublicKey(PROGRAM_ID)
+    );
+    const hasVestingSchedule = !!(await connection.getAccountInfo(vestingSchedulePDA));
+    
+    // 추가 보상 스트림 - 언스테이킹 시 스트림 보상도 함께 정산됨
+    const [rewardStreamsPDA] = findRewardStreamsPDA(poolStatePDA);
+    const [stakeRewardStatePDA] = findStakeRewardStatePDA(stakeInfoPDA);
+    const rewardStreamsInfo = await connection.getAccountInfo(rewardStreamsPDA);
+    const streams = rewardStreamsInfo ? decodeRewardStreams(rewardStreamsInfo.data) : [];
+    const streamAccounts = [];
+    for (const stream of streams) {
+      // 스트림 민트는 SPL Token 또는 Token-2022 소유
+      const streamMintInfo = await connection.getAccountInfo(stream.mint);
+      const tokenProgram = streamMintInfo ? streamMintInfo.owner : undefined;
+      streamAccounts.push({
+        mint: stream.mint,
+        streamVault: stream.vault,
+        userTokenAccount: await getAssociatedTokenAddress(stream.mint, walletPubkey, false, tokenProgram),
+        tokenProgram
+      });
+    }
+    
     // 명령어 배열 생성
     const instructions = [];
     
@@ -234,7 +314,37 @@ export default async function handler(req, res) {
       instructions.push(createUserATAIx);
     }
     
-    // 2. 언스테이킹 명령어 추가
+    // 2. 필요한 경우 보상 토큰 계정 생성 명령어 추가 (SPL Token 또는 Token-2022)
+    const rewardTokenAccountInfo = await connection.getAccountInfo(rewardTokenAccount);
+    if (!rewardTokenAccountInfo) {
+      console.log('보상 토큰 계정 생성 명령어 추가...');
+      instructions.push(
+        createAssociatedTokenAccountInstruction(
+          walletPubkey,
+          rewardTokenAccount,
+          walletPubkey,
+          rewardMintPubkey,
+          rewardTokenProgram
+        )
+      );
+    }
+    
+    // 3. 스트림 보상 수령 계정이 없으면 생성
+    for (const stream of streamAccounts) {
+      if (!(await connection.getAccountInfo(stream.userTokenAccount))) {
+        instructions.push(
+          createAssociatedTokenAccountInstruction(
+            walletPubkey,
+            stream.userTokenAccount,
+            walletPubkey,
+            stream.mint,
+            stream.tokenProgram
+          )
+        );
+      }
+    }
+    
+    // 4. 언스테이킹 명령어 추가
     console.log('언스테이킹 명령어 추가...');
     const unstakeNftIx = createUnstakeNftInstruction(
       walletPubkey,
@@ -245,8 +355,20 @@ export default async function handler(req, res) {
         escrowTokenAccount,
         escrowAuthority: escrowAuthorityPDA,
         userTokenAccount,
-        userStakingInfo: userStakingInfoPDA
-      }
+        userStakingInfo: userStakingInfoPDA,
+        vestingSchedule: hasVestingSchedule ? vestingSchedulePDA : undefined,
+        rewardVault: new PublicKey(REWARD_VAULT_ADDRESS),
+        rewardVaultAuthority: rewardVaultAuthorityPDA,
+        rewardTokenAccount,
+        rewardMint: rewardMintPubkey,
+        rewardTokenProgram,
+        rewardStreams: rewardStreamsPDA,
+        stakeRewardState: stakeRewardStatePDA,
+        streamAccounts,
+        metadata: metadataPDA,
+        pnft: isPnft ? { edition: editionPDA, tokenRecord: tokenRecordPDA } : undefined
+      },
+      !stakingCompleted // 기간 완료 전에는 emergency_unstake_nft (패널티 적용)
     );
     instructions.push(unstakeNftIx);
     
@@ -11,6 +11,7 @@ const INSTRUCTION_DISCRIMINATORS = {
   INITIALIZE: Buffer.from([175, 175, 109, 31, 13, 152, 155, 237]),
   STAKE_NFT: Buffer.from([38, 27, 66, 46, 69, 65, 151, 219]),
   UNSTAKE_NFT: Buffer.from([17, 182, 24, 211, 101, 138, 50, 163]),
+  EMERGENCY_UNSTAKE_NFT: Buffer.from([86, 197, 139, 66, 164, 73, 30, 201]),
   INIT_USER_STAKING_INFO: Buffer.from([228, 148, 161, 162, 20, 86, 73, 202]),
   CLAIM_REWARDS: Buffer.from([4, 144, 132, 71, 116, 23, 151, 80]),
   FUND_REWARD_POOL: Buffer.from([85, 49, 108, 245, 204, 70, 243, 3]),
@@ -9,7 +9,11 @@ const PROGRAM_ID = '4SfUyQkbeyz9jeJDsR5XiUf8DATVZJXtGG4JUsYsWzTs';
 // 풀 상태 주소(초기화된 풀 상태 계정)
 const POOL_STATE_ADDRESS = '8cQViUpNWGhw2enYUNyp2WRWXAwdQbZokiATBr1Xc5uP';
 
+// 기본 풀의 컬렉션 민트 주소 (풀 상태 PDA 시드)
+const COLLECTION_MINT_ADDRESS = process.env.COLLECTION_MINT_ADDRESS;
+
 module.exports = {
   PROGRAM_ID,
-  POOL_STATE_ADDRESS
+  POOL_STATE_ADDRESS,
+  COLLECTION_MINT_ADDRESS
 };
\ No newline at end of file
@@ -15,6 +15,8 @@ const SOCIAL_SEED = Buffer.from([115, 111, 99, 105, 97, 108]); // "social"
 const PROOF_SEED = Buffer.from([112, 114, 111, 111, 102]); // "proof"
 const VOTE_SEED = Buffer.from([118, 111, 116, 101]); // "vote"
 const REWARD_VAULT_AUTHORITY_SEED = Buffer.from("reward_vault_authority");
+const REWARD_STREAMS_SEED = Buffer.from("reward_streams");
+const STAKE_REWARDS_SEED = Buffer.from("stake_rewards");
 
 // 시드 문자열 (디버깅 및 참조용)
 const SEED_STRINGS = {
@@ -25,7 +27,9 @@ const SEED_STRINGS = {
   SOCIAL_SEED_STR: "social",
   PROOF_SEED_STR: "proof",
   VOTE_SEED_STR: "vote",
-  REWARD_VAULT_AUTHORITY_SEED_STR: "reward_vault_authority"
+  REWARD_VAULT_AUTHORITY_SEED_STR: "reward_vault_authority",
+  REWARD_STREAMS_SEED_STR: "reward_streams",
+  STAKE_REWARDS_SEED_STR: "stake_rewards"
 };
 
 module.exports = {
@@ -37,5 +41,7 @@ module.exports = {
   PROOF_SEED,
   VOTE_SEED,
   REWARD_VAULT_AUTHORITY_SEED,
+  REWARD_STREAMS_SEED,
+  STAKE_REWARDS_SEED,
   SEED_STRINGS
 };
\ No newline at end of file
@@ -12,17 +12,22 @@ const {
   SOCIAL_SEED,
   PROOF_SEED,
   VOTE_SEED,
-  REWARD_VAULT_AUTHORITY_SEED
+  REWARD_VAULT_AUTHORITY_SEED,
+  REWARD_STREAMS_SEED,
+  STAKE_REWARDS_SEED
 } = require('../constants/seeds');
 
 /**
- * 풀 상태 PDA 생성
+ * 풀 상태 PDA 생성 (컬렉션별 풀)
  * 
+ * @param {PublicKey|string} collectionMint - 풀이 스테이킹하는 컬렉션 민트 주소
  * @returns {[PublicKey, number]} 풀 상태 PDA 및 범프 값
  */
-function findPoolStatePDA() {
+function findPoolStatePDA(collectionMint) {
+  const collectionKey = typeof collectionMint === 'string' ? new PublicKey(collectionMint) : collectionMint;
+  
   return PublicKey.findProgramAddressSync(
-    [POOL_SEED],
+    [POOL_SEED, collectionKey.toBuffer()],
     new PublicKey(PROGRAM_ID)
   );
 }
@@ -31,13 +36,15 @@ function findPoolStatePDA() {
  * 스테이크 정보 PDA 생성
  * 
  * @param {PublicKey|string} nftMint - NFT 민트 주소
+ * @param {PublicKey|string} poolState - 풀 상태 주소
  * @returns {[PublicKey, number]} 스테이크 정보 PDA 및 범프 값
  */
-function findStakeInfoPDA(nftMint) {
+function findStakeInfoPDA(nftMint, poolState) {
   const mintKey = typeof nftMint === 'string' ? new PublicKey(nftMint) : nftMint;
+  const poolKey = typeof poolState === 'string' ? new PublicKey(poolState) : poolState;
   
   return PublicKey.findProgramAddressSync(
-    [STAKE_SEED, mintKey.toBuffer()],
+    [STAKE_SEED, poolKey.toBuffer(), mintKey.toBuffer()],
     new PublicKey(PROGRAM_ID)
   );
 }
@@ -61,13 +68,15 @@ function findEscrowAuthorityPDA(nftMint) {
  * 사용자 스테이킹 정보 PDA 생성
  * 
  * @param {PublicKey|string} userWallet - 사용자 지갑 주소
+ * @param {PublicKey|string} poolState - 풀 상태 주소
  * @returns {[PublicKey, number]} 사용자 스테이킹 정보 PDA 및 범프 값
  */
-function findUserStakingInfoPDA(userWallet) {
+function findUserStakingInfoPDA(userWallet, poolState) {
   const walletKey = typeof userWallet === 'string' ? new PublicKey(userWallet) : userWallet;
+  const poolKey = typeof poolState === 'string' ? new PublicKey(poolState) : poolState;
   
   return PublicKey.findProgramAddressSync(
-    [USER_STAKING_SEED, walletKey.toBuffer()],
+    [USER_STAKING_SEED, poolKey.toBuffer(), walletKey.toBuffer()],
     new PublicKey(PROGRAM_ID)
   );
 }
@@ -75,11 +84,44 @@ function findUserStakingInfoPDA(userWallet) {
 /**
  * 보상 볼트 권한 PDA 생성
  * 
+ * @param {PublicKey|string} poolState - 풀 상태 계정 주소
  * @returns {[PublicKey, number]} 보상 볼트 권한 PDA 및 범프 값
  */
-function findRewardVaultAuthorityPDA() {
+function findRewardVaultAuthorityPDA(poolState) {
+  const poolKey = typeof poolState === 'string' ? new PublicKey(poolState) : poolState;
+  
   return PublicKey.findProgramAddressSync(
-    [REWARD_VAULT_AUTHORITY_SEED],
+    [REWARD_VAULT_AUTHORITY_SEED, poolKey.toBuffer()],
+    new PublicKey(PROGRAM_ID)
+  );
+}
+
+/**
+ * 추가 보상 스트림 레지스트리 PDA 생성 (풀별)
+ * 
+ * @param {PublicKey|string} poolState - 풀 상태 계정 주소
+ * @returns {[PublicKey, number]} 보상 스트림 레지스트리 PDA 및 범프 값
+ */
+function findRewardStreamsPDA(poolState) {
+  const poolKey = typeof poolState === 'string' ? new PublicKey(poolState) : poolState;
+  
+  return PublicKey.findProgramAddressSync(
+    [REWARD_STREAMS_SEED, poolKey.toBuffer()],
+    new PublicKey(PROGRAM_ID)
+  );
+}
+
+/**
+ * 스테이크별 보상 스트림 정산 상태 PDA 생성
+ * 
+ * @param {PublicKey|string} stakeInfo - 스테이크 정보 계정 주소
+ * @returns {[PublicKey, number]} 스트림 정산 상태 PDA 및 범프 값
+ */
+function findStakeRewardStatePDA(stakeInfo) {
+  const stakeKey = typeof stakeInfo === 'string' ? new PublicKey(stakeInfo) : stakeInfo;
+  
+  return PublicKey.findProgramAddressSync(
+    [STAKE_REWARDS_SEED, stakeKey.toBuffer()],
     new PublicKey(PROGRAM_ID)
   );
 }
@@ -115,6 +157,21 @@ function findActivityProofPDA(userWallet, activityId) {
   );
 }
 
+/**
+ * 희귀도 머클 루트 PDA 생성 (풀별)
+ * 
+ * @param {PublicKey|string} poolState - 풀 상태 주소
+ * @returns {[PublicKey, number]} 희귀도 루트 PDA 및 범프 값
+ */
+function findRarityRootPDA(poolState) {
+  const poolKey = typeof poolState === 'string' ? new PublicKey(poolState) : poolState;
+  
+  return PublicKey.findProgramAddressSync(
+    [PROOF_SEED, poolKey.toBuffer()],
+    new PublicKey(PROGRAM_ID)
+  );
+}
+
 /**
  * 투표 PDA 생성
  * 
@@ -138,7 +195,10 @@ module.exports = {
   findEscrowAuthorityPDA,
   findUserStakingInfoPDA,
   findRewardVaultAuthorityPDA,
+  findRewardStreamsPDA,
+  findStakeRewardStatePDA,
   findSocialActivityPDA,
   findActivityProofPDA,
+  findRarityRootPDA,
   findVotePDA
 };
\ No newline at end of file
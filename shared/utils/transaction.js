@@ -6,6 +6,7 @@ const {
   Transaction,
   PublicKey,
   SystemProgram,
+  SYSVAR_INSTRUCTIONS_PUBKEY,
   TransactionInstruction
 } = require('@solana/web3.js');
 const {
@@ -16,6 +17,9 @@ const {
 } = require('@solana/spl-token');
 
 const { PROGRAM_ID } = require('../constants/program-ids');
+
+// Metaplex Token Metadata 프로그램 (pNFT 위임 및 잠금)
+const TOKEN_METADATA_PROGRAM_ID = new PublicKey('metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s');
 const { INSTRUCTION_DISCRIMINATORS } = require('../constants/discriminators');
 const {
   serializeTransactionForClientSigning,
@@ -75,7 +79,10 @@ async function createTokenAccountInstruction(wallet, mint) {
  * @param {number} stakingPeriod - 스테이킹 기간(일)
  * @param {number} nftTier - NFT 등급 (0-3)
  * @param {boolean} autoCompound - 자동 복리 여부
- * @param {Object} accounts - 추가 계정들(PDAs)
+ * @param {Object} accounts - 추가 계정들(PDAs), 풀에 있을 때만 emissionSchedule / tierTable / rarityRoot 포함
+ *   metadata 는 항상 필요하며, pNFT 는 escrowTokenAccount 대신
+ *   pnft { edition, tokenRecord, authorizationRules?, authorizationRulesProgram? } 를 전달
+ * @param {Object} rarity - 희귀도 머클 증명 { bonusBps, proof: Buffer[] } (루트가 없으면 생략)
  * @returns {TransactionInstruction} 스테이킹 명령어
  */
 function createStakeNftInstruction(
@@ -85,7 +92,8 @@ function createStakeNftInstruction(
   stakingPeriod, 
   nftTier, 
   autoCompound = false,
-  accounts
+  accounts,
+  rarity = { bonusBps: 0, proof: [] }
 ) {
   // 데이터 버퍼 생성
   const stakingPeriodBuf = Buffer.alloc(8);
@@ -94,24 +102,48 @@ function createStakeNftInstruction(
   const nftTierBuf = Buffer.from([nftTier]);
   const autoCompoundBuf = Buffer.from([autoCompound ? 1 : 0]);
   
+  // bonus_bps (u16) + proof (Vec<[u8; 32]>)
+  const bonusBpsBuf = Buffer.alloc(2);
+  bonusBpsBuf.writeUInt16LE(rarity.bonusBps);
+  const proofLenBuf = Buffer.alloc(4);
+  proofLenBuf.writeUInt32LE(rarity.proof.length);
+  
   // 명령어 데이터 구성
   const data = Buffer.concat([
     Buffer.from(INSTRUCTION_DISCRIMINATORS.STAKE_NFT),
     stakingPeriodBuf,
     nftTierBuf,
-    autoCompoundBuf
+    autoCompoundBuf,
+    bonusBpsBuf,
+    proofLenBuf,
+    ...rarity.proof.map(node => Buffer.from(node))
   ]);
   
+  // 선택 계정이 없으면 프로그램 ID를 자리 표시자로 전달
+  const programId = new PublicKey(PROGRAM_ID);
+  const pnft = accounts.pnft || {};
+  const isPnft = Boolean(accounts.pnft);
+  
   // 계정 배열 구성
   const keys = [
     { pubkey: owner, isSigner: true, isWritable: true },      // owner
     { pubkey: nftMint, isSigner: false, isWritable: false },  // nft_mint
     { pubkey: accounts.stakeInfo, isSigner: false, isWritable: true },       // stake_info
-    { pubkey: accounts.escrowTokenAccount, isSigner: false, isWritable: true },  // escrow_nft_account
+    { pubkey: isPnft ? programId : accounts.escrowTokenAccount, isSigner: false, isWritable: !isPnft },  // escrow_nft_account (일반 NFT 전용)
     { pubkey: accounts.escrowAuthority, isSigner: false, isWritable: false },    // escrow_authority
     { pubkey: accounts.userTokenAccount, isSigner: false, isWritable: true },    // user_nft_account
+    { pubkey: accounts.metadata, isSigner: false, isWritable: true },            // token_metadata.metadata
+    { pubkey: pnft.edition || programId, isSigner: false, isWritable: false },   // token_metadata.edition (pNFT)
+    { pubkey: pnft.tokenRecord || programId, isSigner: false, isWritable: isPnft }, // token_metadata.token_record (pNFT)
+    { pubkey: pnft.authorizationRules || programId, isSigner: false, isWritable: false },        // token_metadata.authorization_rules (선택)
+    { pubkey: pnft.authorizationRulesProgram || programId, isSigner: false, isWritable: false }, // token_metadata.authorization_rules_program (선택)
+    { pubkey: isPnft ? TOKEN_METADATA_PROGRAM_ID : programId, isSigner: false, isWritable: false }, // token_metadata.token_metadata_program (pNFT)
+    { pubkey: isPnft ? SYSVAR_INSTRUCTIONS_PUBKEY : programId, isSigner: false, isWritable: false }, // token_metadata.sysvar_instructions (pNFT)
     { pubkey: accounts.userStakingInfo, isSigner: false, isWritable: true },     // user_staking_info
     { pubkey: poolState, isSigner: false, isWritable: true },  // pool_state
+    { pubkey: accounts.emissionSchedule || programId, isSigner: false, isWritable: false }, // emission_schedule (선택)
+    { pubkey: accounts.tierTable || programId, isSigner: false, isWritable: false },   // tier_table (선택)
+    { pubkey: accounts.rarityRoot || programId, isSigner: false, isWritable: false },  // rarity_root (선택)
     { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },     // system_program
     { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },            // token_program
     { pubkey: new PublicKey('SysvarRent111111111111111111111111111111111'), isSigner: false, isWritable: false } // rent
@@ -120,7 +152,7 @@ function createStakeNftInstruction(
   // 명령어 생성
   return new TransactionInstruction({
     keys,
-    programId: new PublicKey(PROGRAM_ID),
+    programId,
     data
   });
 }
@@ -128,41 +160,82 @@ function createStakeNftInstruction(
 /**
  * NFT 언스테이킹 명령어 생성
  * 
+ * 스테이킹 기간이 끝나면 unstake_nft, 그 전에는 패널티가 적용되는 emergency_unstake_nft 를 호출.
+ * 두 명령어 모두 남은 보상을 지급하고 stake_info 계정을 닫음
+ * 
  * @param {PublicKey} owner - NFT 소유자 주소
  * @param {PublicKey} nftMint - NFT 민트 주소
  * @param {PublicKey} poolState - 풀 상태 계정 주소
- * @param {Object} accounts - 추가 계정들(PDAs)
+ * @param {Object} accounts - 추가 계정들(PDAs), 풀에 있을 때만 emissionSchedule / tierTable / vestingSchedule 포함
+ *   rewardVault, rewardVaultAuthority, rewardTokenAccount, rewardMint, rewardTokenProgram 은 보상 지급용,
+ *   metadata 는 항상 필요하며, pNFT 는 escrowTokenAccount 대신 pnft { edition, tokenRecord, ... } 를 전달
+ *   rewardStreams, stakeRewardState 는 추가 보상 스트림 정산용이며, streamAccounts 에 스트림별
+ *   { streamVault, mint, userTokenAccount, tokenProgram } 을 레지스트리 순서대로 전달 (스트림이 없으면 빈 배열)
+ * @param {boolean} emergency - 스테이킹 기간 완료 전 조기 언스테이킹 여부
  * @returns {TransactionInstruction} 언스테이킹 명령어
  */
 function createUnstakeNftInstruction(
   owner, 
   nftMint, 
   poolState, 
-  accounts
+  accounts,
+  emergency = false
 ) {
   // 명령어 데이터 (식별자만 포함)
-  const data = Buffer.from(INSTRUCTION_DISCRIMINATORS.UNSTAKE_NFT);
+  const data = Buffer.from(
+    emergency ? INSTRUCTION_DISCRIMINATORS.EMERGENCY_UNSTAKE_NFT : INSTRUCTION_DISCRIMINATORS.UNSTAKE_NFT
+  );
+  
+  // 선택 계정이 없으면 프로그램 ID를 자리 표시자로 전달
+  const programId = new PublicKey(PROGRAM_ID);
+  const pnft = accounts.pnft || {};
+  const isPnft = Boolean(accounts.pnft);
   
   // 계정 배열 구성
   const keys = [
-    { pubkey: owner, isSigner: true, isWritable: true },      // owner
+    { pubkey: owner, isSigner: true, isWritable: true },      // owner (stake_info 임대료 수령)
     { pubkey: nftMint, isSigner: false, isWritable: false },  // nft_mint
-    { pubkey: accounts.stakeInfo, isSigner: false, isWritable: true },       // stake_info
-    { pubkey: accounts.escrowTokenAccount, isSigner: false, isWritable: true },  // escrow_nft_account
-    { pubkey: accounts.escrowAuthority, isSigner: false, isWritable: false },    // escrow_authority
-    { pubkey: accounts.userTokenAccount, isSigner: true, isWritable: true },     // user_nft_account
+    { pubkey: accounts.stakeInfo, isSigner: false, isWritable: true },       // stake_info (닫힘)
     { pubkey: accounts.userStakingInfo, isSigner: false, isWritable: true },     // user_staking_info
     { pubkey: poolState, isSigner: false, isWritable: true },  // pool_state
+    { pubkey: accounts.emissionSchedule || programId, isSigner: false, isWritable: false }, // emission_schedule (선택)
+    { pubkey: accounts.tierTable || programId, isSigner: false, isWritable: false },   // tier_table (선택)
+    { pubkey: accounts.vestingSchedule || programId, isSigner: false, isWritable: Boolean(accounts.vestingSchedule) }, // vesting_schedule (선택)
+    { pubkey: accounts.rewardVault, isSigner: false, isWritable: true },         // reward_vault
+    { pubkey: accounts.rewardVaultAuthority, isSigner: false, isWritable: false }, // reward_vault_authority
+    { pubkey: accounts.rewardTokenAccount, isSigner: false, isWritable: true },  // user_token_account (보상 수령 계정)
+    { pubkey: accounts.rewardMint, isSigner: false, isWritable: false },         // reward_mint
+    { pubkey: accounts.rewardStreams, isSigner: false, isWritable: false },      // reward_streams
+    { pubkey: accounts.stakeRewardState, isSigner: false, isWritable: true },    // stake_reward_state
+    { pubkey: isPnft ? programId : accounts.escrowTokenAccount, isSigner: false, isWritable: !isPnft },  // escrow_nft_account (일반 NFT 전용)
+    { pubkey: accounts.escrowAuthority, isSigner: false, isWritable: false },    // escrow_authority
+    { pubkey: accounts.userTokenAccount, isSigner: false, isWritable: true },    // user_nft_account
+    { pubkey: accounts.metadata, isSigner: false, isWritable: true },            // token_metadata.metadata
+    { pubkey: pnft.edition || programId, isSigner: false, isWritable: false },   // token_metadata.edition (pNFT)
+    { pubkey: pnft.tokenRecord || programId, isSigner: false, isWritable: isPnft }, // token_metadata.token_record (pNFT)
+    { pubkey: pnft.authorizationRules || programId, isSigner: false, isWritable: false },        // token_metadata.authorization_rules (선택)
+    { pubkey: pnft.authorizationRulesProgram || programId, isSigner: false, isWritable: false }, // token_metadata.authorization_rules_program (선택)
+    { pubkey: isPnft ? TOKEN_METADATA_PROGRAM_ID : programId, isSigner: false, isWritable: false }, // token_metadata.token_metadata_program (pNFT)
+    { pubkey: isPnft ? SYSVAR_INSTRUCTIONS_PUBKEY : programId, isSigner: false, isWritable: false }, // token_metadata.sysvar_instructions (pNFT)
     { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },     // system_program
     { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },            // token_program
-    { pubkey: ASSOCIATED_TOKEN_PROGRAM_ID, isSigner: false, isWritable: false }, // associated_token_program
-    { pubkey: new PublicKey('SysvarRent111111111111111111111111111111111'), isSigner: false, isWritable: false } // rent
+    { pubkey: accounts.rewardTokenProgram || TOKEN_PROGRAM_ID, isSigner: false, isWritable: false } // reward_token_program (SPL Token 또는 Token-2022)
   ];
   
+  // remaining_accounts: 스트림별 (stream_vault, stream_mint, user_token_account, token_program)
+  for (const stream of accounts.streamAccounts || []) {
+    keys.push(
+      { pubkey: stream.streamVault, isSigner: false, isWritable: true },
+      { pubkey: stream.mint, isSigner: false, isWritable: false },
+      { pubkey: stream.userTokenAccount, isSigner: false, isWritable: true },
+      { pubkey: stream.tokenProgram || TOKEN_PROGRAM_ID, isSigner: false, isWritable: false }
+    );
+  }
+  
   // 명령어 생성
   return new TransactionInstruction({
     keys,
-    programId: new PublicKey(PROGRAM_ID),
+    programId,
     data
   });
 }
@@ -214,16 +287,18 @@ function createClaimRewardsInstruction(
  * 사용자 스테이킹 정보 초기화 명령어 생성
  * 
  * @param {PublicKey} user - 사용자 지갑 주소
+ * @param {PublicKey} poolState - 풀 상태 PDA
  * @param {PublicKey} userStakingInfo - 사용자 스테이킹 정보 PDA
  * @returns {TransactionInstruction} 초기화 명령어
  */
-function createInitUserStakingInfoInstruction(user, userStakingInfo) {
+function createInitUserStakingInfoInstruction(user, poolState, userStakingInfo) {
   // 명령어 데이터 (식별자만 포함)
   const data = Buffer.from(INSTRUCTION_DISCRIMINATORS.INIT_USER_STAKING_INFO);
   
   // 계정 배열 구성
   const keys = [
     { pubkey: user, isSigner: true, isWritable: true },                    // user
+    { pubkey: poolState, isSigner: false, isWritable: false },             // pool_state
     { pubkey: userStakingInfo, isSigner: false, isWritable: true },        // user_staking_info
     { pubkey: SystemProgram.programId, isSigner: false, isWritable: false } // system_program
   ];
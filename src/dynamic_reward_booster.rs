@@ -1,32 +1,118 @@
 use anchor_lang::prelude::*;
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
 use anchor_spl::token::{self, Token, TokenAccount, Mint};
+use anchor_spl::token_interface::{self, TokenInterface};
+use tesola_staking_math as math;
 
 declare_id!("4SfUyQkbeyz9jeJDsR5XiUf8DATVZJXtGG4JUsYsWzTs");
 
+/// Metaplex Token Metadata, owner of NFT metadata, editions and pNFT token records
+pub mod token_metadata_program {
+    use super::*;
+    declare_id!("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+}
+
+/// Shortest notice, in seconds, a pool can give before a queued change executes
+pub const MIN_CHANGE_DELAY: i64 = 86_400;
+
 #[program]
 pub mod nft_staking_enhanced {
     use super::*;
 
+    /// Admin: create the staking pool for an NFT collection
+    ///
+    /// Each collection gets its own pool PDA, so several collections can be
+    /// staked from one deployment with separate rates and reward vaults. The
+    /// signer must be the collection's update authority or the program's
+    /// upgrade authority.
+    pub fn initialize_pool(
+        ctx: Context<InitializePool>,
+        reward_rate: u64,
+    ) -> Result<()> {
+        // Only whoever controls the collection, or the program itself, may
+        // open its pool and become the pool's admin
+        let admin = ctx.accounts.admin.key();
+        let upgrade_authority = ctx.accounts.program_data
+            .as_ref()
+            .and_then(|program_data| program_data.upgrade_authority_address);
+        if upgrade_authority != Some(admin) {
+            let collection = read_metadata(
+                &ctx.accounts.collection_metadata,
+                &ctx.accounts.collection_mint.key(),
+            )?;
+            require_keys_eq!(collection.update_authority, admin, StakingError::NotPoolCreator);
+        }
+        
+        let pool_state = &mut ctx.accounts.pool_state;
+        
+        pool_state.admin = admin;
+        pool_state.reward_rate = reward_rate;
+        pool_state.pause_flags = 0;
+        pool_state.total_staked = 0;
+        
+        // Tier multipliers in percent (100 = 1x)
+        pool_state.common_multiplier = 100;
+        pool_state.rare_multiplier = 200;
+        pool_state.epic_multiplier = 400;
+        pool_state.legendary_multiplier = 800;
+        pool_state.max_nfts_per_user = 3;
+        
+        // 5% every 30 days, capped at 50%
+        pool_state.time_multiplier_increment = 500;
+        pool_state.time_multiplier_period_days = 30;
+        pool_state.max_time_multiplier = 5000;
+        
+        // Rate changes wait at least two days
+        pool_state.change_delay = 2 * 86400;
+        
+        // 50/30/15/5% early-exit penalty at 30/60/90% progress
+        pool_state.penalty_curve = math::penalty::DEFAULT_PENALTY_CURVE
+            .iter()
+            .map(PenaltyPoint::from_math)
+            .collect();
+        
+        // 30/90/180/365-day locks boosted by 0/10/25/50%
+        pool_state.lock_options = math::locks::DEFAULT_LOCK_OPTIONS
+            .iter()
+            .map(LockOption::from_math)
+            .collect();
+        
+        // Claims pay out immediately until vesting is queued on
+        pool_state.vesting = VestingConfig::default();
+        
+        pool_state.collection = ctx.accounts.collection_mint.key();
+        pool_state.bump = ctx.bumps.pool_state;
+        
+        emit!(PoolInitialized {
+            pool: pool_state.key(),
+            collection: pool_state.collection,
+            admin: pool_state.admin,
+            reward_rate,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+
     /// Updates the time-based multiplier for a staked NFT
     pub fn update_time_multiplier(ctx: Context<UpdateTimeMultiplier>) -> Result<()> {
         let stake_info = &mut ctx.accounts.stake_info;
-        let pool = &ctx.accounts.pool_state;
+        let pool = &mut ctx.accounts.pool_state;
         let current_time = Clock::get()?.unix_timestamp;
         
         // Check if NFT is staked
         require!(stake_info.is_staked, StakingError::NotStaked);
-        require!(!pool.paused, StakingError::PoolPaused);
+        require_not_paused(pool, math::pause::Instruction::UpdateTimeMultiplier)?;
         
         // Calculate staking duration in days
         let days_staked = (current_time - stake_info.staked_at) / 86400;
         
-        // Calculate periods completed (e.g., 30-day periods)
-        let multiplier_periods = (days_staked / pool.time_multiplier_period_days as i64) as u64;
-        
-        // Calculate new multiplier (with maximum cap)
-        let new_multiplier = std::cmp::min(
-            multiplier_periods.saturating_mul(pool.time_multiplier_increment),
-            pool.max_time_multiplier
+        // Calculate new multiplier (periods completed, with maximum cap)
+        let tier_table = active_tier_table(pool, &ctx.accounts.tier_table)?;
+        let new_multiplier = math::time_multiplier(
+            &pool.reward_params(tier_table),
+            stake_info.staked_at,
+            current_time,
         );
         
         // Only update if multiplier has increased
@@ -36,7 +122,13 @@ pub mod nft_staking_enhanced {
             stake_info.last_multiplier_update = current_time;
             
             // Check for milestone achievement
-            check_and_process_milestone(ctx.accounts, current_time)?;
+            check_and_process_milestone(
+                stake_info,
+                pool,
+                tier_table,
+                ctx.accounts.owner.key(),
+                current_time,
+            )?;
             
             // Emit multiplier updated event
             emit!(MultiplierUpdated {
@@ -55,31 +147,31 @@ pub mod nft_staking_enhanced {
     /// Process auto-compound for a staked NFT
     pub fn process_auto_compound(ctx: Context<ProcessAutoCompound>) -> Result<()> {
         let stake_info = &mut ctx.accounts.stake_info;
-        let pool_state = &ctx.accounts.pool_state;
+        let pool_state = &mut ctx.accounts.pool_state;
         let current_time = Clock::get()?.unix_timestamp;
         
         // Check if NFT is staked
         require!(stake_info.is_staked, StakingError::NotStaked);
-        require!(!pool_state.paused, StakingError::PoolPaused);
+        require_not_paused(pool_state, math::pause::Instruction::ProcessAutoCompound)?;
         require!(stake_info.auto_compound, StakingError::AutoCompoundNotEnabled);
         
         // Check if it's time to compound based on frequency
-        let compound_interval = match stake_info.compound_frequency {
-            0 => 86400,     // Daily (24 hours)
-            1 => 604800,    // Weekly (7 days)
-            2 => 2592000,   // Monthly (30 days)
-            _ => return Err(StakingError::InvalidCompoundFrequency.into()),
-        };
+        let compound_interval = math::compound::compound_interval(stake_info.compound_frequency)
+            .ok_or(StakingError::InvalidCompoundFrequency)?;
         
         require!(
             current_time - stake_info.last_compound_time >= compound_interval,
             StakingError::CompoundTooEarly
         );
         
-        // Calculate earned rewards since last claim/compound
-        let earned_rewards = calculate_rewards(
+        // Settle rewards earned since the last checkpoint
+        let schedule = active_schedule(pool_state, &ctx.accounts.emission_schedule)?;
+        let tier_table = active_tier_table(pool_state, &ctx.accounts.tier_table)?;
+        let earned_rewards = settle_rewards(
             stake_info,
             pool_state,
+            schedule,
+            tier_table,
             current_time,
         )?;
         
@@ -87,6 +179,7 @@ pub mod nft_staking_enhanced {
         stake_info.accumulated_compound = stake_info.accumulated_compound
             .checked_add(earned_rewards)
             .ok_or(StakingError::ArithmeticError)?;
+        record_liability(pool_state, earned_rewards)?;
         
         // Update last compound time
         stake_info.last_compound_time = current_time;
@@ -98,10 +191,12 @@ pub mod nft_staking_enhanced {
             
         // Update streak multiplier (every 5 consecutive compounds = +1% bonus, max 10%)
         let old_multiplier = stake_info.compound_streak_multiplier;
-        stake_info.compound_streak_multiplier = std::cmp::min(
-            (stake_info.compound_streak / 5) as u64 * 100, // +1% per 5 streaks
-            1000 // 10% max bonus
-        );
+        stake_info.compound_streak_multiplier =
+            math::compound::compound_streak_multiplier(stake_info.compound_streak);
+        if stake_info.compound_streak_multiplier != old_multiplier {
+            untrack_accrual(pool_state, stake_info);
+            track_accrual(pool_state, stake_info, schedule, tier_table)?;
+        }
         
         // Emit events
         emit!(RewardsCompounded {
@@ -133,7 +228,7 @@ pub mod nft_staking_enhanced {
     ) -> Result<()> {
         // Validate frequency value
         require!(
-            frequency == 0 || frequency == 1 || frequency == 2 || frequency == 255,
+            math::compound::is_valid_frequency(frequency),
             StakingError::InvalidCompoundFrequency
         );
         
@@ -144,7 +239,9 @@ pub mod nft_staking_enhanced {
         stake_info.compound_frequency = frequency;
         
         // Reset compound streak if switching to manual
-        if frequency == 255 && old_frequency != 255 {
+        if frequency == math::compound::FREQUENCY_MANUAL
+            && old_frequency != math::compound::FREQUENCY_MANUAL
+        {
             stake_info.compound_streak = 0;
             stake_info.compound_streak_multiplier = 0;
         }
@@ -161,120 +258,148 @@ pub mod nft_staking_enhanced {
         Ok(())
     }
     
-    /// Emergency unstaking with dynamic penalty
-    pub fn emergency_unstake_nft(
-        ctx: Context<EmergencyUnstakeNft>,
-    ) -> Result<()> {
-        let stake_info = &mut ctx.accounts.stake_info;
-        let pool_state = &ctx.accounts.pool_state;
+    /// Unstake once the staking period has completed
+    ///
+    /// Pays every reward owed on the stake without penalty, returns the NFT
+    /// and closes the stake account. When the pool is in underfunded mode and
+    /// the vault is short, the exit pays its pro-rata share and the rest
+    /// becomes an IOU on the user's staking info, paid by `claim_reward_iou`.
+    /// Additional reward streams are paid out as well, so `remaining_accounts`
+    /// holds the stream accounts `settle_streams` describes, four per stream.
+    pub fn unstake_nft<'info>(ctx: Context<'_, '_, 'info, 'info, UnstakeNft<'info>>) -> Result<()> {
         let current_time = Clock::get()?.unix_timestamp;
+        require!(
+            current_time >= ctx.accounts.stake_info.release_date,
+            StakingError::StakingPeriodNotCompleted
+        );
+        require_not_paused(&ctx.accounts.pool_state, math::pause::Instruction::UnstakeNft)?;
         
-        // Check if NFT is staked
-        require!(stake_info.is_staked, StakingError::NotStaked);
-        require!(!pool_state.paused, StakingError::PoolPaused);
-        
-        // Calculate staking duration so far
-        let staking_duration = current_time - stake_info.staked_at;
-        let staking_duration_days = staking_duration / 86400;
-        let staking_period_days = stake_info.staking_period as i64;
-        
-        // Calculate progress percentage (0-100)
-        let progress_percentage = (staking_duration_days * 100) / staking_period_days;
-        
-        // Calculate penalty percentage based on progress
-        let penalty_percentage = if progress_percentage < 30 {
-            50 // 50% penalty if < 30% complete
-        } else if progress_percentage < 60 {
-            30 // 30% penalty if 30-60% complete
-        } else if progress_percentage < 90 {
-            15 // 15% penalty if 60-90% complete
-        } else {
-            5  // 5% penalty if >= 90% complete
-        };
-        
-        // Calculate earned rewards
-        let earned_rewards = calculate_rewards(
-            stake_info,
-            pool_state,
-            current_time,
-        )?;
-        
-        // Apply penalty to rewards
-        let penalty_amount = (earned_rewards * penalty_percentage as u64) / 100;
-        let final_rewards = earned_rewards.saturating_sub(penalty_amount);
-        
-        // Process unstaking (token transfer logic would be here)
-        // This requires detailed implementation of token transfers
-        // Similar to regular unstaking but with penalty application
+        let earned_rewards = ctx.accounts.settle(current_time)?;
+        let payout = ctx.accounts.exit(&ctx.bumps, ctx.remaining_accounts, earned_rewards, current_time)?;
         
-        // Update user staking info
-        let user_staking_info = &mut ctx.accounts.user_staking_info;
+        emit!(NftUnstaked {
+            user: ctx.accounts.owner.key(),
+            nft_mint: ctx.accounts.nft_mint.key(),
+            earned_rewards,
+            rewards_paid: payout.paid,
+            shortfall: payout.shortfall,
+            timestamp: current_time,
+        });
         
-        // Remove NFT from user's staked list
-        if let Some(index) = user_staking_info.staked_mints.iter().position(|&mint| mint == ctx.accounts.nft_mint.key()) {
-            user_staking_info.staked_mints.remove(index);
-            user_staking_info.staked_count = user_staking_info.staked_count.saturating_sub(1);
+        Ok(())
+    }
+    
+    /// Emergency unstaking with dynamic penalty
+    ///
+    /// Once the release date has passed this is a regular `unstake_nft`: no
+    /// penalty applies and only the unstaking pause flag is checked.
+    pub fn emergency_unstake_nft<'info>(
+        ctx: Context<'_, '_, 'info, 'info, UnstakeNft<'info>>,
+    ) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        if current_time >= ctx.accounts.stake_info.release_date {
+            return unstake_nft(ctx);
         }
+        require_not_paused(&ctx.accounts.pool_state, math::pause::Instruction::EmergencyUnstakeNft)?;
         
-        // Update collection bonus
-        let bonus_changed = update_collection_bonus(
-            user_staking_info,
-            pool_state,
-        )?;
+        // Settle earned rewards and apply the progress-based penalty
+        let earned_rewards = ctx.accounts.settle(current_time)?;
+        let quote = math::penalty::apply_emergency_penalty(
+            earned_rewards,
+            &ctx.accounts.stake_info.reward_snapshot(),
+            &ctx.accounts.pool_state.math_penalty_curve(),
+            current_time,
+        );
         
-        // Mark NFT as unstaked
-        stake_info.is_staked = false;
+        // Pay what is left after the penalty together with compounded rewards
+        // and any IOU; the stake is leaving, so a shortfall is owed to the user
+        let payout = ctx.accounts.exit(
+            &ctx.bumps,
+            ctx.remaining_accounts,
+            quote.final_rewards,
+            current_time,
+        )?;
         
         // Emit emergency unstaking event
         emit!(EmergencyUnstaked {
             user: ctx.accounts.owner.key(),
             nft_mint: ctx.accounts.nft_mint.key(),
-            earned_rewards,
-            penalty_amount,
-            final_rewards,
-            progress_percentage: progress_percentage as u8,
+            earned_rewards: quote.earned_rewards,
+            penalty_bps: quote.penalty_bps,
+            penalty_amount: quote.penalty_amount,
+            final_rewards: quote.final_rewards,
+            progress_percentage: quote.progress_percentage,
+            curve_segment: quote.curve_segment,
+            lock_completed: quote.lock_completed,
+            rewards_paid: payout.paid,
+            shortfall: payout.shortfall,
             timestamp: current_time,
         });
         
-        // Emit collection bonus event if changed
-        if bonus_changed {
-            emit!(CollectionBonusUpdated {
-                user: ctx.accounts.owner.key(),
-                staked_count: user_staking_info.staked_count,
-                new_bonus: user_staking_info.collection_bonus,
-                timestamp: current_time,
-            });
-        }
-        
         Ok(())
     }
     
     // Existing stake_nft function with collection bonus update
+    //
+    // Once the pool publishes a rarity root, `nft_tier` and `bonus_bps` must
+    // match the mint's entry in it and `proof` is that entry's Merkle proof.
+    //
+    // Regular NFTs move into escrow. pNFTs cannot be moved by a plain SPL
+    // transfer, so they stay in the owner's wallet, delegated to and locked
+    // by the escrow PDA through Token Metadata.
     pub fn stake_nft(
         ctx: Context<StakeNft>, 
         staking_period: u64, 
         nft_tier: u8, 
-        auto_compound: bool
+        auto_compound: bool,
+        bonus_bps: u16,
+        proof: Vec<[u8; 32]>,
     ) -> Result<()> {
-        // Existing staking logic...
         // We're extending the implementation to handle collection bonuses
         
         let stake_info = &mut ctx.accounts.stake_info;
-        let user_staking_info = &mut ctx.accounts.user_staking_info;
         let pool_state = &ctx.accounts.pool_state;
         let current_time = Clock::get()?.unix_timestamp;
         
         // Validate inputs
-        require!(!pool_state.paused, StakingError::PoolPaused);
-        require!(nft_tier <= 3, StakingError::InvalidNftTier); // 0=Common, 1=Rare, 2=Epic, 3=Legendary
-        require!(staking_period > 0, StakingError::InvalidStakingPeriod);
+        require_not_paused(pool_state, math::pause::Instruction::StakeNft)?;
+        let tier_table = active_tier_table(pool_state, &ctx.accounts.tier_table)?;
+        require!(
+            pool_state.reward_params(tier_table).tiers.contains(nft_tier),
+            StakingError::InvalidNftTier
+        );
+        // Only the pool's lock options can be staked, each with its fixed boost
+        let lock_boost_bps = math::locks::boost_for(&pool_state.math_lock_options(), staking_period)
+            .ok_or(StakingError::InvalidStakingPeriod)?;
+        
+        // Tier and bonus are self-reported only until a rarity root is published
+        let leaf = math::rarity::RarityLeaf {
+            mint: ctx.accounts.nft_mint.key().to_bytes(),
+            tier: nft_tier,
+            bonus_bps,
+        };
+        match active_rarity_root(pool_state, &ctx.accounts.rarity_root)? {
+            Some(rarity_root) => require!(
+                math::rarity::verify(keccak_hashv, &leaf, &proof, &rarity_root.root),
+                StakingError::InvalidRarityProof
+            ),
+            None => require!(bonus_bps == 0, StakingError::InvalidRarityProof),
+        }
         
         // Check max NFTs per user
         require!(
-            user_staking_info.staked_count < pool_state.max_nfts_per_user,
+            ctx.accounts.user_staking_info.staked_count < pool_state.max_nfts_per_user,
             StakingError::MaxNftsExceeded
         );
         
+        // Only NFTs the pool's collection has verified can be staked
+        let metadata = ctx.accounts.token_metadata.read(&ctx.accounts.nft_mint.key())?;
+        require!(
+            metadata.verified_collection == Some(pool_state.collection),
+            StakingError::NotInCollection
+        );
+        let programmable = metadata.programmable;
+        
         // Set up stake info
         stake_info.owner = ctx.accounts.owner.key();
         stake_info.mint = ctx.accounts.nft_mint.key();
@@ -292,20 +417,59 @@ pub mod nft_staking_enhanced {
         stake_info.last_multiplier_update = current_time;
         stake_info.milestones_achieved = 0;
         stake_info.next_milestone_days = 30; // First milestone at 30 days
-        stake_info.compound_frequency = if auto_compound { 0 } else { 255 }; // Default to daily if auto-compound enabled
+        // Default to daily if auto-compound enabled
+        stake_info.compound_frequency = if auto_compound {
+            math::compound::FREQUENCY_DAILY
+        } else {
+            math::compound::FREQUENCY_MANUAL
+        };
         stake_info.last_compound_time = current_time;
         stake_info.compound_streak = 0;
         stake_info.compound_streak_multiplier = 0;
+        stake_info.rarity_bonus_bps = bonus_bps;
+        stake_info.lock_boost_bps = lock_boost_bps;
+        stake_info.programmable = programmable;
+        
+        // Count what it accrues towards the pool's liabilities from now on
+        let schedule = active_schedule(pool_state, &ctx.accounts.emission_schedule)?;
+        track_accrual(&mut ctx.accounts.pool_state, stake_info, schedule, tier_table)?;
+        
+        if programmable {
+            // Delegate to the escrow PDA and lock the pNFT where it is
+            ctx.accounts.token_metadata.lock(&HeldPnft {
+                mint: ctx.accounts.nft_mint.to_account_info(),
+                token: ctx.accounts.user_nft_account.to_account_info(),
+                owner: ctx.accounts.owner.to_account_info(),
+                escrow_authority: ctx.accounts.escrow_authority.to_account_info(),
+                escrow_bump: ctx.bumps.escrow_authority,
+                system_program: ctx.accounts.system_program.to_account_info(),
+                token_program: ctx.accounts.token_program.to_account_info(),
+            })?;
+        } else {
+            // Move the NFT into escrow
+            let escrow_nft_account = ctx.accounts.escrow_nft_account
+                .as_ref()
+                .ok_or(StakingError::MissingEscrowAccount)?;
+            token::transfer(
+                CpiContext::new(
+                    ctx.accounts.token_program.to_account_info(),
+                    token::Transfer {
+                        from: ctx.accounts.user_nft_account.to_account_info(),
+                        to: escrow_nft_account.to_account_info(),
+                        authority: ctx.accounts.owner.to_account_info(),
+                    },
+                ),
+                1,
+            )?;
+        }
         
         // Add NFT to user's staked list
+        let user_staking_info = &mut ctx.accounts.user_staking_info;
         user_staking_info.staked_mints.push(ctx.accounts.nft_mint.key());
         user_staking_info.staked_count += 1;
         
         // Update collection bonus
-        let bonus_changed = update_collection_bonus(
-            user_staking_info,
-            pool_state,
-        )?;
+        let bonus_changed = update_collection_bonus(user_staking_info)?;
         
         // Emit staking event
         emit!(NftStaked {
@@ -314,6 +478,8 @@ pub mod nft_staking_enhanced {
             timestamp: current_time,
             tier: nft_tier,
             staking_period,
+            rarity_bonus_bps: bonus_bps,
+            programmable,
         });
         
         // Emit collection bonus event if changed
@@ -329,340 +495,4318 @@ pub mod nft_staking_enhanced {
         Ok(())
     }
     
-    // Helper functions would be implemented in the same module
-}
-
-/// Helper function to check and process milestones
-fn check_and_process_milestone(
-    accounts: &UpdateTimeMultiplier,
-    current_time: i64,
-) -> Result<()> {
-    let stake_info = &mut accounts.stake_info;
-    let pool_state = &accounts.pool_state;
-    
-    // Calculate days staked
-    let days_staked = (current_time - stake_info.staked_at) / 86400;
-    
-    // Define milestones (days and bitmap position)
-    let milestones = [
-        (30, 0),   // 30-day milestone, bit 0
-        (90, 1),   // 90-day milestone, bit 1
-        (180, 2),  // 180-day milestone, bit 2
-        (365, 3),  // 365-day milestone, bit 3
-    ];
+    /// Lengthen a stake's lock without unstaking
+    ///
+    /// Rewards up to now are settled at the old boost and credited to the
+    /// stake, then the period, release date and boost move to the extended
+    /// lock option. `staked_at` is kept, so the time multiplier and
+    /// milestones carry over. Only a running lock can be extended; once the
+    /// release date has passed the stake no longer commits to anything.
+    pub fn extend_lock(ctx: Context<ExtendLock>, additional_days: u64) -> Result<()> {
+        let stake_info = &mut ctx.accounts.stake_info;
+        let pool_state = &mut ctx.accounts.pool_state;
+        let current_time = Clock::get()?.unix_timestamp;
+        
+        require_not_paused(pool_state, math::pause::Instruction::ExtendLock)?;
+        require!(current_time < stake_info.release_date, StakingError::LockExpired);
+        let extended = math::locks::extend(
+            &pool_state.math_lock_options(),
+            stake_info.staking_period,
+            additional_days,
+        )
+        .ok_or(StakingError::InvalidStakingPeriod)?;
+        
+        // Settle at the old boost; the rewards are paid on the next claim
+        let schedule = active_schedule(pool_state, &ctx.accounts.emission_schedule)?;
+        let tier_table = active_tier_table(pool_state, &ctx.accounts.tier_table)?;
+        let settled_rewards = settle_rewards(
+            stake_info,
+            pool_state,
+            schedule,
+            tier_table,
+            current_time,
+        )?;
+        stake_info.accumulated_compound = stake_info.accumulated_compound
+            .checked_add(settled_rewards)
+            .ok_or(StakingError::ArithmeticError)?;
+        record_liability(pool_state, settled_rewards)?;
+        
+        let lock_seconds = (extended.days as i64)
+            .checked_mul(86400)
+            .ok_or(StakingError::ArithmeticError)?;
+        stake_info.staking_period = extended.days;
+        stake_info.release_date = stake_info.staked_at
+            .checked_add(lock_seconds)
+            .ok_or(StakingError::ArithmeticError)?;
+        stake_info.lock_boost_bps = extended.boost_bps;
+        untrack_accrual(pool_state, stake_info);
+        track_accrual(pool_state, stake_info, schedule, tier_table)?;
+        
+        emit!(LockExtended {
+            user: ctx.accounts.owner.key(),
+            nft_mint: ctx.accounts.nft_mint.key(),
+            staking_period: stake_info.staking_period,
+            release_date: stake_info.release_date,
+            lock_boost_bps: stake_info.lock_boost_bps,
+            settled_rewards,
+            timestamp: current_time,
+        });
+        
+        Ok(())
+    }
     
-    // Check if any milestone has been reached but not yet claimed
-    for (days, bit_pos) in milestones.iter() {
-        let bit_mask = 1u8 << bit_pos;
+    /// Hand a staked position to another wallet without unstaking
+    ///
+    /// Everything owed so far is paid (or vested) to the current owner first.
+    /// What a short vault cannot pay in underfunded mode stays owed to the
+    /// current owner as an IOU on their staking info, never on the stake.
+    /// Every additional reward stream is settled to the current owner too, so
+    /// `remaining_accounts` holds the stream accounts `settle_streams`
+    /// describes, four per stream, and the stake's reward state must
+    /// exist once the pool has streams. The pool's `transfer_cooldown` must
+    /// have passed since the stake was created or last transferred, and the
+    /// new owner must have run `init_user_staking_info`. pNFT stakes cannot be
+    /// transferred, as the pNFT stays locked in the staker's wallet.
+    pub fn transfer_stake<'info>(
+        ctx: Context<'_, '_, 'info, 'info, TransferStake<'info>>,
+    ) -> Result<()> {
+        let stake_info = &mut ctx.accounts.stake_info;
+        let pool_state = &mut ctx.accounts.pool_state;
+        let current_time = Clock::get()?.unix_timestamp;
         
-        // If days staked exceeds milestone days and milestone not yet achieved
-        if days_staked >= *days as i64 && (stake_info.milestones_achieved & bit_mask) == 0 {
-            // Mark milestone as achieved
-            stake_info.milestones_achieved |= bit_mask;
-            
-            // Calculate milestone bonus (percentage of base rewards)
-            let bonus_percent = match bit_pos {
-                0 => 5,    // 5% bonus for 30-day milestone
-                1 => 10,   // 10% bonus for 90-day milestone
-                2 => 15,   // 15% bonus for 180-day milestone
-                3 => 25,   // 25% bonus for 365-day milestone
-                _ => 0,
-            };
-            
-            // Calculate tier multiplier
-            let tier_multiplier = match stake_info.tier {
-                0 => pool_state.common_multiplier,    // Common
-                1 => pool_state.rare_multiplier,      // Rare
-                2 => pool_state.epic_multiplier,      // Epic
-                3 => pool_state.legendary_multiplier, // Legendary
-                _ => pool_state.common_multiplier,    // Default to Common
-            };
-            
-            // Calculate base daily reward
-            let base_daily_reward = pool_state.reward_rate
-                .checked_mul(tier_multiplier)
-                .ok_or(StakingError::ArithmeticError)?
-                .checked_div(100)
-                .ok_or(StakingError::ArithmeticError)?;
-                
-            // Calculate milestone reward (30 days worth of % bonus)
-            let milestone_reward = base_daily_reward
-                .checked_mul(bonus_percent)
-                .ok_or(StakingError::ArithmeticError)?
-                .checked_div(100)
-                .ok_or(StakingError::ArithmeticError)?
-                .checked_mul(30) // 30 days worth
-                .ok_or(StakingError::ArithmeticError)?;
-                
-            // Apply milestone reward to accumulated compound if auto-compound is enabled
-            if stake_info.auto_compound {
-                stake_info.accumulated_compound = stake_info.accumulated_compound
-                    .checked_add(milestone_reward)
-                    .ok_or(StakingError::ArithmeticError)?;
+        require!(!stake_info.programmable, StakingError::StakeNotTransferable);
+        require_not_paused(pool_state, math::pause::Instruction::TransferStake)?;
+        let held_since = std::cmp::max(stake_info.staked_at, stake_info.last_transfer_time);
+        require!(
+            current_time.saturating_sub(held_since) >= pool_state.transfer_cooldown,
+            StakingError::TransferCooldownActive
+        );
+        
+        // Pay the current owner everything owed up to now
+        let schedule = active_schedule(pool_state, &ctx.accounts.emission_schedule)?;
+        let tier_table = active_tier_table(pool_state, &ctx.accounts.tier_table)?;
+        let payout = settle_claim(
+            stake_info,
+            pool_state,
+            schedule,
+            tier_table,
+            ctx.accounts.reward_vault.amount,
+            current_time,
+        )?;
+        
+        // A shortfall is owed to the seller, so it must not travel with the stake
+        let user_staking_info = &mut ctx.accounts.user_staking_info;
+        user_staking_info.reward_iou = user_staking_info.reward_iou
+            .checked_add(payout.shortfall)
+            .ok_or(StakingError::ArithmeticError)?;
+        stake_info.reward_iou = 0;
+        
+        let vested = vest_claim(
+            pool_state,
+            &mut ctx.accounts.vesting_schedule,
+            payout.paid,
+            current_time,
+        )?;
+        let received = if payout.paid > 0 && !vested {
+            pay_rewards(
+                &ctx.accounts.token_program,
+                &ctx.accounts.reward_vault,
+                &mut ctx.accounts.user_token_account,
+                &ctx.accounts.reward_mint,
+                &ctx.accounts.reward_vault_authority,
+                pool_state.key(),
+                ctx.bumps.reward_vault_authority,
+                payout.paid,
+            )?
+        } else {
+            payout.paid
+        };
+        
+        // Settle the streams too, so the new owner only accrues from now on;
+        // the checkpoints must be recorded as the stake stays open
+        settle_unchecked_streams(
+            &ctx.accounts.reward_streams,
+            &ctx.accounts.stake_reward_state,
+            true,
+            stake_info,
+            &pool_state.reward_params(tier_table),
+            &ctx.accounts.nft_mint.key(),
+            ctx.remaining_accounts,
+            &ctx.accounts.reward_vault_authority,
+            pool_state.key(),
+            ctx.bumps.reward_vault_authority,
+            current_time,
+        )?;
+        
+        // Move the mint between both users' staked lists
+        let nft_mint = ctx.accounts.nft_mint.key();
+        let user_staking_info = &mut ctx.accounts.user_staking_info;
+        if let Some(index) = user_staking_info.staked_mints.iter().position(|&mint| mint == nft_mint) {
+            user_staking_info.staked_mints.remove(index);
+            user_staking_info.staked_count = user_staking_info.staked_count.saturating_sub(1);
+        }
+        
+        let new_user_staking_info = &mut ctx.accounts.new_user_staking_info;
+        require!(
+            new_user_staking_info.staked_count < pool_state.max_nfts_per_user,
+            StakingError::MaxNftsExceeded
+        );
+        new_user_staking_info.staked_mints.push(nft_mint);
+        new_user_staking_info.staked_count += 1;
+        
+        let old_bonus_changed = update_collection_bonus(user_staking_info)?;
+        let new_bonus_changed = update_collection_bonus(new_user_staking_info)?;
+        
+        stake_info.owner = ctx.accounts.new_owner.key();
+        stake_info.last_transfer_time = current_time;
+        
+        emit!(StakeTransferred {
+            nft_mint,
+            from: ctx.accounts.owner.key(),
+            to: stake_info.owner,
+            rewards_paid: received,
+            shortfall: payout.shortfall,
+            timestamp: current_time,
+        });
+        
+        for (changed, info) in [
+            (old_bonus_changed, &**user_staking_info),
+            (new_bonus_changed, &**new_user_staking_info),
+        ] {
+            if changed {
+                emit!(CollectionBonusUpdated {
+                    user: info.owner,
+                    staked_count: info.staked_count,
+                    new_bonus: info.collection_bonus,
+                    timestamp: current_time,
+                });
             }
-            
-            // Set next milestone
-            let next_milestones: Vec<u64> = milestones.iter()
-                .filter(|&&(milestone_days, pos)| milestone_days as i64 > days_staked && (stake_info.milestones_achieved & (1 << pos)) == 0)
-                .map(|&(days, _)| days)
-                .collect();
-                
-            stake_info.next_milestone_days = next_milestones.first().copied().unwrap_or(0);
-            
-            // Emit milestone event
-            emit!(MilestoneAchieved {
-                user: accounts.owner.key(),
-                nft_mint: accounts.nft_mint.key(),
-                milestone_type: *bit_pos as u8,
-                reward_amount: milestone_reward,
-                timestamp: current_time,
-            });
         }
+        
+        Ok(())
     }
     
-    Ok(())
-}
-
-/// Helper function to update collection bonus
-fn update_collection_bonus(
-    user_staking_info: &mut UserStakingInfo,
-    pool_state: &PoolState,
-) -> Result<bool> {
-    let staked_count = user_staking_info.staked_count;
-    
-    // Calculate new bonus based on number of staked NFTs
-    let new_bonus = if staked_count >= 21 {
-        2000 // 20% for 21+ NFTs
-    } else if staked_count >= 11 {
-        1500 // 15% for 11-20 NFTs
-    } else if staked_count >= 6 {
-        1000 // 10% for 6-10 NFTs
-    } else if staked_count >= 3 {
-        500  // 5% for 3-5 NFTs
-    } else {
-        0    // No bonus for fewer than 3 NFTs
-    };
-    
-    // Check if bonus changed
-    let bonus_changed = new_bonus != user_staking_info.collection_bonus;
-    
-    // Update bonus if changed
-    if bonus_changed {
-        user_staking_info.collection_bonus = new_bonus;
+    /// Create the signer's staking info for a pool
+    ///
+    /// Needed once per wallet and pool before it can stake or receive a
+    /// transferred stake.
+    pub fn init_user_staking_info(ctx: Context<InitUserStakingInfo>) -> Result<()> {
+        let user_staking_info = &mut ctx.accounts.user_staking_info;
+        user_staking_info.owner = ctx.accounts.user.key();
+        Ok(())
     }
     
-    Ok(bonus_changed)
-}
-
-/// Calculate rewards for a staked NFT
-fn calculate_rewards(
-    stake_info: &StakeInfo,
-    pool_state: &PoolState,
-    current_time: i64,
-) -> Result<u64> {
-    // Get time elapsed since last claim
-    let time_elapsed = current_time.checked_sub(stake_info.last_claim_time)
-        .ok_or(StakingError::ArithmeticError)?;
+    /// Let a second key claim, compound and update multipliers for the owner's stakes
+    ///
+    /// Claims still pay the owner's reward destination and only the owner can
+    /// unstake, extend or transfer. Pass the default pubkey to revoke.
+    pub fn set_delegate(ctx: Context<SetDelegate>, delegate: Pubkey) -> Result<()> {
+        let user_staking_info = &mut ctx.accounts.user_staking_info;
+        user_staking_info.delegate = delegate;
+        
+        emit!(DelegateUpdated {
+            user: user_staking_info.owner,
+            pool: ctx.accounts.pool_state.key(),
+            delegate,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
         
-    // If no time has passed, return 0
-    if time_elapsed <= 0 {
-        return Ok(0);
+        Ok(())
     }
     
-    // Calculate elapsed days (integer division)
-    let elapsed_days = time_elapsed / 86400;
-    
-    // Apply fractional day
-    let fractional_day = (time_elapsed % 86400) as f64 / 86400.0;
-    
-    // Get tier multiplier
-    let tier_multiplier = match stake_info.tier {
-        0 => pool_state.common_multiplier,    // Common
-        1 => pool_state.rare_multiplier,      // Rare
-        2 => pool_state.epic_multiplier,      // Epic
-        3 => pool_state.legendary_multiplier, // Legendary
-        _ => pool_state.common_multiplier,    // Default to Common
-    };
-    
-    // Calculate base daily reward
-    let base_daily_reward = pool_state.reward_rate
-        .checked_mul(tier_multiplier)
-        .ok_or(StakingError::ArithmeticError)?
-        .checked_div(100)
-        .ok_or(StakingError::ArithmeticError)?;
+    /// Route this user's primary rewards to another token account of the reward mint
+    ///
+    /// Claims, including those made by a delegate, then pay `destination`
+    /// instead of the owner's associated token account. Omit `destination`
+    /// to go back to the owner's associated token account.
+    pub fn set_reward_destination(ctx: Context<SetRewardDestination>) -> Result<()> {
+        let destination = ctx.accounts.destination
+            .as_ref()
+            .map(|destination| destination.key())
+            .unwrap_or_default();
         
-    // Apply time-based multiplier
-    let with_time_multiplier = base_daily_reward
-        .checked_mul(10000 + stake_info.current_time_multiplier)
-        .ok_or(StakingError::ArithmeticError)?
-        .checked_div(10000)
-        .ok_or(StakingError::ArithmeticError)?;
+        let user_staking_info = &mut ctx.accounts.user_staking_info;
+        user_staking_info.reward_destination = destination;
         
-    // Apply compound streak multiplier
-    let with_compound_multiplier = with_time_multiplier
-        .checked_mul(10000 + stake_info.compound_streak_multiplier)
-        .ok_or(StakingError::ArithmeticError)?
-        .checked_div(10000)
-        .ok_or(StakingError::ArithmeticError)?;
+        emit!(RewardDestinationChanged {
+            user: user_staking_info.owner,
+            pool: ctx.accounts.pool_state.key(),
+            destination: user_staking_info.reward_account(
+                &ctx.accounts.pool_state.reward_mint,
+                &ctx.accounts.pool_state.reward_token_program,
+            ),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
         
-    // Calculate reward for full days
-    let full_days_reward = with_compound_multiplier
-        .checked_mul(elapsed_days as u64)
-        .ok_or(StakingError::ArithmeticError)?;
+        Ok(())
+    }
+    
+    /// Admin: set the minimum seconds a stake must be held before `transfer_stake`
+    pub fn set_transfer_cooldown(ctx: Context<AdminAction>, cooldown: i64) -> Result<()> {
+        require!(cooldown >= 0, StakingError::InvalidAmount);
+        
+        let pool_state = &mut ctx.accounts.pool_state;
+        pool_state.transfer_cooldown = cooldown;
+        
+        emit!(TransferCooldownUpdated {
+            pool: pool_state.key(),
+            cooldown,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
         
-    // Calculate reward for fractional day
-    let fractional_reward = (with_compound_multiplier as f64 * fractional_day) as u64;
+        Ok(())
+    }
     
-    // Total reward
-    let total_reward = full_days_reward
-        .checked_add(fractional_reward)
-        .ok_or(StakingError::ArithmeticError)?;
+    /// Admin: set the reward-mint token account that receives vesting exit haircuts
+    pub fn set_treasury(ctx: Context<SetTreasury>) -> Result<()> {
+        let pool_state = &mut ctx.accounts.pool_state;
+        pool_state.treasury = ctx.accounts.treasury.key();
         
-    Ok(total_reward)
-}
-
-#[derive(Accounts)]
-pub struct UpdateTimeMultiplier<'info> {
-    #[account(mut)]
-    pub owner: Signer<'info>,
+        emit!(TreasuryUpdated {
+            pool: pool_state.key(),
+            treasury: pool_state.treasury,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+    
+    /// Claim pending, compounded and previously unpaid rewards for a staked NFT
+    ///
+    /// When the pool is in underfunded mode and the vault cannot cover all
+    /// liabilities, the claim pays its pro-rata share and records the rest as
+    /// an IOU on the stake instead of failing. When the pool vests rewards,
+    /// the payout goes into the owner's vesting schedule instead.
+    pub fn claim_rewards(ctx: Context<ClaimRewards>) -> Result<()> {
+        let stake_info = &mut ctx.accounts.stake_info;
+        let pool_state = &mut ctx.accounts.pool_state;
+        let current_time = Clock::get()?.unix_timestamp;
+        
+        require!(stake_info.is_staked, StakingError::NotStaked);
+        require_not_paused(pool_state, math::pause::Instruction::ClaimRewards)?;
+        
+        // Settle and split what is owed into what the vault pays now and an IOU
+        let schedule = active_schedule(pool_state, &ctx.accounts.emission_schedule)?;
+        let tier_table = active_tier_table(pool_state, &ctx.accounts.tier_table)?;
+        let payout = settle_claim(
+            stake_info,
+            pool_state,
+            schedule,
+            tier_table,
+            ctx.accounts.reward_vault.amount,
+            current_time,
+        )?;
+        require!(
+            payout.paid > 0 || payout.shortfall > 0,
+            StakingError::NoRewardsToClaim
+        );
+        
+        let vested = vest_claim(
+            pool_state,
+            &mut ctx.accounts.vesting_schedule,
+            payout.paid,
+            current_time,
+        )?;
+        let received = if payout.paid > 0 && !vested {
+            pay_rewards(
+                &ctx.accounts.token_program,
+                &ctx.accounts.reward_vault,
+                &mut ctx.accounts.user_token_account,
+                &ctx.accounts.reward_mint,
+                &ctx.accounts.reward_vault_authority,
+                ctx.accounts.pool_state.key(),
+                ctx.bumps.reward_vault_authority,
+                payout.paid,
+            )?
+        } else {
+            payout.paid
+        };
+        
+        emit!(RewardsClaimed {
+            user: ctx.accounts.owner.key(),
+            nft_mint: ctx.accounts.nft_mint.key(),
+            amount: received,
+            shortfall: payout.shortfall,
+            timestamp: current_time,
+        });
+        
+        Ok(())
+    }
+    
+    /// Claim the IOU that stakes left owed to their owner when they were unstaked
+    ///
+    /// Pays like `claim_rewards`: the pro-rata share while the pool is in
+    /// underfunded mode and the vault is short, with the rest staying owed,
+    /// and into the owner's vesting schedule when the pool vests rewards.
+    pub fn claim_reward_iou(ctx: Context<ClaimRewardIou>) -> Result<()> {
+        let user_staking_info = &mut ctx.accounts.user_staking_info;
+        let pool_state = &mut ctx.accounts.pool_state;
+        let current_time = Clock::get()?.unix_timestamp;
+        
+        require!(user_staking_info.reward_iou > 0, StakingError::NoRewardsToClaim);
+        require_not_paused(pool_state, math::pause::Instruction::ClaimRewardIou)?;
+        
+        let schedule = active_schedule(pool_state, &ctx.accounts.emission_schedule)?;
+        let outstanding = if pool_state.underfunded_mode {
+            outstanding_liabilities(pool_state, schedule, current_time)?
+        } else {
+            pool_state.total_liabilities
+        };
+        let payout = math::solvency::claim_payout(
+            user_staking_info.reward_iou,
+            ctx.accounts.reward_vault.amount,
+            outstanding,
+            pool_state.underfunded_mode,
+        )
+        .ok_or(StakingError::InsufficientRewardBalance)?;
+        user_staking_info.reward_iou = payout.shortfall;
+        release_liability(pool_state, payout.paid);
+        
+        let vested = vest_claim(
+            pool_state,
+            &mut ctx.accounts.vesting_schedule,
+            payout.paid,
+            current_time,
+        )?;
+        let received = if payout.paid > 0 && !vested {
+            pay_rewards(
+                &ctx.accounts.token_program,
+                &ctx.accounts.reward_vault,
+                &mut ctx.accounts.user_token_account,
+                &ctx.accounts.reward_mint,
+                &ctx.accounts.reward_vault_authority,
+                ctx.accounts.pool_state.key(),
+                ctx.bumps.reward_vault_authority,
+                payout.paid,
+            )?
+        } else {
+            payout.paid
+        };
+        
+        emit!(RewardIouClaimed {
+            user: ctx.accounts.owner.key(),
+            pool: ctx.accounts.pool_state.key(),
+            amount: received,
+            shortfall: payout.shortfall,
+            timestamp: current_time,
+        });
+        
+        Ok(())
+    }
+    
+    /// Read-only quote of pending rewards, returned via return data for `simulateTransaction`
+    pub fn get_pending_rewards(ctx: Context<ViewStake>) -> Result<PendingRewardsQuote> {
+        let stake_info = &ctx.accounts.stake_info;
+        let pool_state = &ctx.accounts.pool_state;
+        let current_time = Clock::get()?.unix_timestamp;
+        
+        let schedule = active_schedule(pool_state, &ctx.accounts.emission_schedule)?;
+        let tier_table = active_tier_table(pool_state, &ctx.accounts.tier_table)?;
+        let pending_rewards = calculate_rewards(
+            stake_info,
+            pool_state,
+            schedule,
+            tier_table,
+            current_time,
+        )?;
+        
+        // Daily rate at the base rate currently in effect
+        let epochs = schedule.map(EmissionSchedule::math_epochs).unwrap_or_default();
+        let params = pool_state.reward_params(tier_table);
+        let current_params = math::PoolParams {
+            reward_rate: emission(schedule, &epochs).rate_at(params.reward_rate, current_time),
+            ..params
+        };
+        let daily_reward = math::rewards::daily_reward(
+            &stake_info.reward_snapshot(),
+            &current_params,
+        )
+        .map_err(StakingError::from)?;
+        
+        Ok(PendingRewardsQuote {
+            pending_rewards,
+            accumulated_compound: stake_info.accumulated_compound,
+            daily_reward,
+            timestamp: current_time,
+        })
+    }
+    
+    /// Read-only quote of an emergency unstake, returned via return data for `simulateTransaction`
+    pub fn get_emergency_unstake_quote(ctx: Context<ViewStake>) -> Result<EmergencyUnstakeQuote> {
+        let pool_state = &ctx.accounts.pool_state;
+        let current_time = Clock::get()?.unix_timestamp;
+        
+        let schedule = active_schedule(pool_state, &ctx.accounts.emission_schedule)?;
+        let tier_table = active_tier_table(pool_state, &ctx.accounts.tier_table)?;
+        let epochs = schedule.map(EmissionSchedule::math_epochs).unwrap_or_default();
+        let quote = math::emergency_unstake_quote(
+            &ctx.accounts.stake_info.reward_snapshot(),
+            &pool_state.reward_params(tier_table),
+            emission(schedule, &epochs),
+            &pool_state.math_penalty_curve(),
+            current_time,
+        )
+        .map_err(StakingError::from)?;
+        
+        Ok(EmergencyUnstakeQuote {
+            earned_rewards: quote.earned_rewards,
+            penalty_bps: quote.penalty_bps,
+            penalty_amount: quote.penalty_amount,
+            final_rewards: quote.final_rewards,
+            progress_percentage: quote.progress_percentage,
+            curve_segment: quote.curve_segment,
+            lock_completed: quote.lock_completed,
+            timestamp: current_time,
+        })
+    }
+    
+    /// Read-only solvency report of the reward vault, returned via return data
+    pub fn get_solvency(ctx: Context<ViewSolvency>) -> Result<SolvencyReport> {
+        let pool_state = &ctx.accounts.pool_state;
+        let vault_balance = ctx.accounts.reward_vault.amount;
+        let schedule = active_schedule(pool_state, &ctx.accounts.emission_schedule)?;
+        let total_liabilities = outstanding_liabilities(
+            pool_state,
+            schedule,
+            Clock::get()?.unix_timestamp,
+        )?;
+        
+        Ok(SolvencyReport {
+            vault_balance,
+            total_liabilities,
+            settled_liabilities: pool_state.total_liabilities,
+            solvency_ratio_bps: math::solvency::solvency_ratio_bps(
+                vault_balance,
+                total_liabilities,
+            ),
+            underfunded_mode: pool_state.underfunded_mode,
+        })
+    }
+    
+    /// Treasurer: switch claims between failing and paying pro-rata when the vault is short
+    pub fn set_underfunded_mode(ctx: Context<TreasurerAction>, enabled: bool) -> Result<()> {
+        let pool_state = &mut ctx.accounts.pool_state;
+        pool_state.underfunded_mode = enabled;
+        
+        emit!(UnderfundedModeChanged {
+            admin: ctx.accounts.treasurer.key(),
+            enabled,
+            total_liabilities: pool_state.total_liabilities,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+    
+    /// Admin: create the reward vault for the pool's reward mint
+    pub fn initialize_reward_vault(ctx: Context<InitializeRewardVault>) -> Result<()> {
+        let pool_state = &mut ctx.accounts.pool_state;
+        
+        require!(
+            pool_state.reward_vault == Pubkey::default(),
+            StakingError::RewardVaultAlreadyInitialized
+        );
+        
+        pool_state.reward_mint = ctx.accounts.reward_mint.key();
+        pool_state.reward_vault = ctx.accounts.reward_vault.key();
+        pool_state.reward_token_program = ctx.accounts.token_program.key();
+        
+        emit!(RewardVaultInitialized {
+            reward_mint: pool_state.reward_mint,
+            reward_vault: pool_state.reward_vault,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+    
+    /// Treasurer: deposit reward tokens into the reward vault
+    pub fn fund_reward_pool(ctx: Context<FundRewardPool>, amount: u64) -> Result<()> {
+        require!(amount > 0, StakingError::InvalidAmount);
+        
+        let balance_before = ctx.accounts.reward_vault.amount;
+        token_interface::transfer_checked(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                token_interface::TransferChecked {
+                    from: ctx.accounts.treasurer_token_account.to_account_info(),
+                    mint: ctx.accounts.reward_mint.to_account_info(),
+                    to: ctx.accounts.reward_vault.to_account_info(),
+                    authority: ctx.accounts.treasurer.to_account_info(),
+                },
+            ),
+            amount,
+            ctx.accounts.reward_mint.decimals,
+        )?;
+        
+        // A Token-2022 transfer fee is withheld from what the vault receives
+        ctx.accounts.reward_vault.reload()?;
+        let vault_balance = ctx.accounts.reward_vault.amount;
+        
+        emit!(RewardPoolFunded {
+            funder: ctx.accounts.treasurer.key(),
+            amount: vault_balance.saturating_sub(balance_before),
+            vault_balance,
+            total_liabilities: ctx.accounts.pool_state.total_liabilities,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+    
+    /// Rate setter: start pricing rewards through a per-epoch emission schedule
+    ///
+    /// The schedule activates once the pool's `change_delay` has passed, and
+    /// no epoch may start before then. Time before activation keeps accruing
+    /// at the flat `reward_rate`; from activation on, only time inside an
+    /// epoch earns rewards.
+    pub fn initialize_emission_schedule(
+        ctx: Context<InitializeEmissionSchedule>,
+        epochs: Vec<EmissionEpoch>,
+    ) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        validate_emission_epochs(&epochs)?;
+        
+        let change_delay = ctx.accounts.pool_state.change_delay;
+        let new_epochs: Vec<math::EmissionEpoch> = epochs.iter().map(EmissionEpoch::to_math).collect();
+        require!(
+            math::emission::gives_notice(&[], &new_epochs, current_time, change_delay),
+            StakingError::ChangeDelayTooShort
+        );
+        
+        let schedule = &mut ctx.accounts.emission_schedule;
+        schedule.pool = ctx.accounts.pool_state.key();
+        schedule.activated_at = current_time
+            .checked_add(change_delay)
+            .ok_or(StakingError::ArithmeticError)?;
+        schedule.epochs = epochs;
+        
+        let pool_state = &mut ctx.accounts.pool_state;
+        pool_state.emission_schedule = schedule.key();
+        
+        emit!(EmissionScheduleUpdated {
+            pool: schedule.pool,
+            epoch_count: schedule.epochs.len() as u8,
+            activated_at: schedule.activated_at,
+            timestamp: current_time,
+        });
+        
+        Ok(())
+    }
+    
+    /// Rate setter: replace the epochs of the emission schedule
+    ///
+    /// Epochs that already started must be kept unchanged so accrued rewards
+    /// are never repriced. Epochs can only be added, edited or removed if they
+    /// start at least the pool's `change_delay` from now.
+    pub fn update_emission_schedule(
+        ctx: Context<UpdateEmissionSchedule>,
+        epochs: Vec<EmissionEpoch>,
+    ) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        validate_emission_epochs(&epochs)?;
+        
+        let schedule = &mut ctx.accounts.emission_schedule;
+        let new_epochs: Vec<math::EmissionEpoch> = epochs.iter().map(EmissionEpoch::to_math).collect();
+        require!(
+            math::emission::preserves_started_epochs(&schedule.math_epochs(), &new_epochs, current_time),
+            StakingError::EmissionEpochStarted
+        );
+        require!(
+            math::emission::gives_notice(
+                &schedule.math_epochs(),
+                &new_epochs,
+                current_time,
+                ctx.accounts.pool_state.change_delay,
+            ),
+            StakingError::ChangeDelayTooShort
+        );
+        schedule.epochs = epochs;
+        
+        emit!(EmissionScheduleUpdated {
+            pool: schedule.pool,
+            epoch_count: schedule.epochs.len() as u8,
+            activated_at: schedule.activated_at,
+            timestamp: current_time,
+        });
+        
+        Ok(())
+    }
+    
+    /// Admin: create the registry of additional reward streams for the pool
+    pub fn initialize_reward_streams(ctx: Context<InitializeRewardStreams>) -> Result<()> {
+        let reward_streams = &mut ctx.accounts.reward_streams;
+        reward_streams.pool = ctx.accounts.pool_state.key();
+        reward_streams.streams = Vec::new();
+        
+        Ok(())
+    }
+    
+    /// Admin: add a partner reward stream with its own mint, vault, rate and end time
+    ///
+    /// The mint may belong to SPL Token or Token-2022, and the vault is created
+    /// under the mint's token program. The vault is funded with plain token
+    /// transfers. `reward_rate` is the
+    /// stream's base daily rate, scaled by tier and multipliers like the
+    /// primary reward.
+    pub fn add_reward_stream(
+        ctx: Context<AddRewardStream>,
+        reward_rate: u64,
+        start_time: i64,
+        end_time: i64,
+    ) -> Result<()> {
+        let reward_streams = &mut ctx.accounts.reward_streams;
+        
+        require!(
+            reward_streams.streams.len() < math::streams::MAX_REWARD_STREAMS,
+            StakingError::TooManyRewardStreams
+        );
+        require!(start_time < end_time, StakingError::InvalidRewardStream);
+        require!(
+            ctx.accounts.reward_mint.key() != ctx.accounts.pool_state.reward_mint
+                && reward_streams.streams.iter().all(|s| s.mint != ctx.accounts.reward_mint.key()),
+            StakingError::InvalidRewardStream
+        );
+        
+        let stream = RewardStream {
+            mint: ctx.accounts.reward_mint.key(),
+            vault: ctx.accounts.stream_vault.key(),
+            reward_rate,
+            start_time,
+            end_time,
+        };
+        reward_streams.streams.push(stream);
+        
+        emit!(RewardStreamAdded {
+            pool: reward_streams.pool,
+            index: (reward_streams.streams.len() - 1) as u8,
+            reward_mint: stream.mint,
+            vault: stream.vault,
+            reward_rate,
+            start_time,
+            end_time,
+        });
+        
+        Ok(())
+    }
+    
+    /// Create the side-car account tracking a stake's accrual in each reward stream
+    pub fn init_stake_reward_state(ctx: Context<InitStakeRewardState>) -> Result<()> {
+        let stake_reward_state = &mut ctx.accounts.stake_reward_state;
+        stake_reward_state.stake = ctx.accounts.stake_info.key();
+        
+        // Streams accrue from the stake date, bounded by each stream's start
+        stake_reward_state.last_settled = [ctx.accounts.stake_info.staked_at; 4];
+        
+        Ok(())
+    }
+    
+    /// Create the account holding this user's vesting rewards, needed to claim while the pool vests
+    pub fn init_vesting_schedule(ctx: Context<InitVestingSchedule>) -> Result<()> {
+        let vesting_schedule = &mut ctx.accounts.vesting_schedule;
+        vesting_schedule.pool = ctx.accounts.pool_state.key();
+        vesting_schedule.owner = ctx.accounts.owner.key();
+        
+        Ok(())
+    }
+    
+    /// Pay out everything the user's vesting schedule has unlocked
+    ///
+    /// Works whether or not the pool still vests claims; turning vesting off
+    /// unlocks every schedule in full.
+    pub fn withdraw_vested(ctx: Context<WithdrawVested>) -> Result<()> {
+        let pool_state = &mut ctx.accounts.pool_state;
+        let current_time = Clock::get()?.unix_timestamp;
+        
+        require_not_paused(pool_state, math::pause::Instruction::WithdrawVested)?;
+        
+        let vesting_schedule = &mut ctx.accounts.vesting_schedule;
+        let mut state = vesting_schedule.to_math();
+        let amount = state.withdraw(&pool_state.vesting_terms(), current_time);
+        require!(amount > 0, StakingError::NoRewardsToClaim);
+        require!(
+            ctx.accounts.reward_vault.amount >= amount,
+            StakingError::InsufficientRewardBalance
+        );
+        vesting_schedule.apply(&state);
+        release_liability(pool_state, amount);
+        
+        let received = pay_rewards(
+            &ctx.accounts.token_program,
+            &ctx.accounts.reward_vault,
+            &mut ctx.accounts.user_token_account,
+            &ctx.accounts.reward_mint,
+            &ctx.accounts.reward_vault_authority,
+            pool_state.key(),
+            ctx.bumps.reward_vault_authority,
+            amount,
+        )?;
+        
+        emit!(VestedRewardsWithdrawn {
+            user: vesting_schedule.owner,
+            pool: pool_state.key(),
+            amount: received,
+            haircut: 0,
+            timestamp: current_time,
+        });
+        
+        Ok(())
+    }
+    
+    /// Owner: leave vesting early, taking unlocked rewards in full and locked ones minus the haircut
+    ///
+    /// The haircut moves from the reward vault to the pool's treasury account,
+    /// which must be passed whenever there is a haircut.
+    pub fn exit_vesting(ctx: Context<WithdrawVested>) -> Result<()> {
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.owner.key(),
+            StakingError::NotOwner
+        );
+        
+        let pool_state = &mut ctx.accounts.pool_state;
+        let current_time = Clock::get()?.unix_timestamp;
+        
+        require_not_paused(pool_state, math::pause::Instruction::ExitVesting)?;
+        
+        let vesting_schedule = &mut ctx.accounts.vesting_schedule;
+        let mut state = vesting_schedule.to_math();
+        let quote = state.exit(&pool_state.vesting_terms(), current_time);
+        require!(quote.payout > 0, StakingError::NoRewardsToClaim);
+        let owed = quote.payout
+            .checked_add(quote.haircut)
+            .ok_or(StakingError::ArithmeticError)?;
+        require!(
+            ctx.accounts.reward_vault.amount >= owed,
+            StakingError::InsufficientRewardBalance
+        );
+        vesting_schedule.apply(&state);
+        release_liability(pool_state, owed);
+        
+        let received = pay_rewards(
+            &ctx.accounts.token_program,
+            &ctx.accounts.reward_vault,
+            &mut ctx.accounts.user_token_account,
+            &ctx.accounts.reward_mint,
+            &ctx.accounts.reward_vault_authority,
+            pool_state.key(),
+            ctx.bumps.reward_vault_authority,
+            quote.payout,
+        )?;
+        
+        if quote.haircut > 0 {
+            let treasury = ctx.accounts.treasury
+                .as_mut()
+                .ok_or(StakingError::InvalidTreasury)?;
+            pay_rewards(
+                &ctx.accounts.token_program,
+                &ctx.accounts.reward_vault,
+                treasury,
+                &ctx.accounts.reward_mint,
+                &ctx.accounts.reward_vault_authority,
+                pool_state.key(),
+                ctx.bumps.reward_vault_authority,
+                quote.haircut,
+            )?;
+        }
+        
+        emit!(VestedRewardsWithdrawn {
+            user: vesting_schedule.owner,
+            pool: pool_state.key(),
+            amount: received,
+            haircut: quote.haircut,
+            timestamp: current_time,
+        });
+        
+        Ok(())
+    }
+    
+    /// Claim the primary reward and every additional reward stream in one transaction
+    ///
+    /// `remaining_accounts` holds one `(stream_vault, stream_mint,
+    /// user_token_account, token_program)` group per stream, in registry
+    /// order, so streams may pay SPL Token or Token-2022 mints.
+    pub fn claim_all_rewards<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ClaimAllRewards<'info>>,
+    ) -> Result<()> {
+        let stake_info = &mut ctx.accounts.stake_info;
+        let pool_state = &mut ctx.accounts.pool_state;
+        let current_time = Clock::get()?.unix_timestamp;
+        
+        require!(stake_info.is_staked, StakingError::NotStaked);
+        require_not_paused(pool_state, math::pause::Instruction::ClaimAllRewards)?;
+        
+        // Primary reward, with the same solvency handling as claim_rewards
+        let schedule = active_schedule(pool_state, &ctx.accounts.emission_schedule)?;
+        let tier_table = active_tier_table(pool_state, &ctx.accounts.tier_table)?;
+        let payout = settle_claim(
+            stake_info,
+            pool_state,
+            schedule,
+            tier_table,
+            ctx.accounts.reward_vault.amount,
+            current_time,
+        )?;
+        
+        let vested = vest_claim(
+            pool_state,
+            &mut ctx.accounts.vesting_schedule,
+            payout.paid,
+            current_time,
+        )?;
+        let received = if payout.paid > 0 && !vested {
+            pay_rewards(
+                &ctx.accounts.token_program,
+                &ctx.accounts.reward_vault,
+                &mut ctx.accounts.user_token_account,
+                &ctx.accounts.reward_mint,
+                &ctx.accounts.reward_vault_authority,
+                pool_state.key(),
+                ctx.bumps.reward_vault_authority,
+                payout.paid,
+            )?
+        } else {
+            payout.paid
+        };
+        
+        emit!(RewardsClaimed {
+            user: ctx.accounts.owner.key(),
+            nft_mint: ctx.accounts.nft_mint.key(),
+            amount: received,
+            shortfall: payout.shortfall,
+            timestamp: current_time,
+        });
+        
+        // Additional streams
+        let params = pool_state.reward_params(tier_table);
+        settle_streams(
+            &ctx.accounts.reward_streams.streams,
+            &mut ctx.accounts.stake_reward_state,
+            stake_info,
+            &params,
+            &ctx.accounts.nft_mint.key(),
+            ctx.remaining_accounts,
+            &ctx.accounts.reward_vault_authority,
+            pool_state.key(),
+            ctx.bumps.reward_vault_authority,
+            current_time,
+        )?;
+        
+        Ok(())
+    }
+    
+    /// Admin: propose a new admin, who must accept before control moves
+    ///
+    /// The new admin may be a PDA of an external multisig program, accepting
+    /// through a CPI signed by the multisig. Proposing the default key
+    /// cancels a pending transfer.
+    pub fn propose_admin(ctx: Context<AdminAction>, new_admin: Pubkey) -> Result<()> {
+        let pool_state = &mut ctx.accounts.pool_state;
+        pool_state.pending_admin = new_admin;
+        
+        emit!(AdminProposed {
+            pool: pool_state.key(),
+            admin: pool_state.admin,
+            pending_admin: new_admin,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+    
+    /// Pending admin: accept the transfer proposed by the current admin
+    pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+        let pool_state = &mut ctx.accounts.pool_state;
+        let old_admin = pool_state.admin;
+        
+        pool_state.admin = ctx.accounts.new_admin.key();
+        pool_state.pending_admin = Pubkey::default();
+        
+        emit!(AdminChanged {
+            pool: pool_state.key(),
+            old_admin,
+            new_admin: pool_state.admin,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+    
+    /// Admin: create the pool's roles account
+    ///
+    /// Every role starts with the admin; `set_roles` hands them to other keys.
+    pub fn initialize_roles(ctx: Context<InitializeRoles>) -> Result<()> {
+        let roles = &mut ctx.accounts.roles;
+        roles.pool = ctx.accounts.pool_state.key();
+        roles.pauser = ctx.accounts.admin.key();
+        roles.rate_setter = ctx.accounts.admin.key();
+        roles.treasurer = ctx.accounts.admin.key();
+        
+        emit!(RolesUpdated {
+            pool: roles.pool,
+            pauser: roles.pauser,
+            rate_setter: roles.rate_setter,
+            treasurer: roles.treasurer,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+    
+    /// Admin: assign the pauser, rate-setter and treasurer keys
+    pub fn set_roles(
+        ctx: Context<SetRoles>,
+        pauser: Pubkey,
+        rate_setter: Pubkey,
+        treasurer: Pubkey,
+    ) -> Result<()> {
+        let roles = &mut ctx.accounts.roles;
+        roles.pauser = pauser;
+        roles.rate_setter = rate_setter;
+        roles.treasurer = treasurer;
+        
+        emit!(RolesUpdated {
+            pool: roles.pool,
+            pauser,
+            rate_setter,
+            treasurer,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+    
+    /// Pauser: pause every operation in the pool
+    pub fn pause_pool(ctx: Context<PauserAction>) -> Result<()> {
+        set_pause(ctx, math::pause::PAUSE_ALL)
+    }
+    
+    /// Pauser: resume every operation in the pool
+    pub fn unpause_pool(ctx: Context<PauserAction>) -> Result<()> {
+        set_pause(ctx, 0)
+    }
+    
+    /// Pauser: pause a chosen set of operations (`math::pause::PAUSE_*` bits)
+    ///
+    /// Lets an incident freeze new stakes, claims or compounding while users
+    /// can still unstake or exit early.
+    pub fn set_pause_flags(ctx: Context<PauserAction>, pause_flags: u8) -> Result<()> {
+        require!(
+            math::pause::is_valid_flags(pause_flags),
+            StakingError::InvalidPauseFlags
+        );
+        set_pause(ctx, pause_flags)
+    }
+    
+    /// Admin: raise the minimum notice, in seconds, before a queued rate change can execute
+    ///
+    /// Raising the delay only gives stakers more notice, so it applies at
+    /// once. Lowering it is queued as a `ParamChange::ChangeDelay`, so the
+    /// current notice still covers the changes queued after it.
+    pub fn set_change_delay(ctx: Context<AdminAction>, delay: i64) -> Result<()> {
+        let pool_state = &mut ctx.accounts.pool_state;
+        require!(
+            delay >= MIN_CHANGE_DELAY && delay >= pool_state.change_delay,
+            StakingError::InvalidChangeDelay
+        );
+        
+        pool_state.change_delay = delay;
+        
+        emit!(ChangeDelayUpdated {
+            pool: pool_state.key(),
+            delay,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+    
+    /// Rate setter: queue a change to the reward rate, multipliers, vesting or change delay
+    ///
+    /// The change can execute once `effective_at` is reached, giving stakers
+    /// at least the pool's `change_delay` to claim at the current rates. One
+    /// change of each kind can be pending at a time.
+    pub fn queue_param_change(
+        ctx: Context<QueueParamChange>,
+        change: ParamChange,
+        effective_at: i64,
+    ) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let earliest = current_time
+            .checked_add(ctx.accounts.pool_state.change_delay)
+            .ok_or(StakingError::ArithmeticError)?;
+        require!(effective_at >= earliest, StakingError::ChangeDelayTooShort);
+        change.validate()?;
+        
+        let pending_change = &mut ctx.accounts.pending_change;
+        pending_change.pool = ctx.accounts.pool_state.key();
+        pending_change.change = change;
+        pending_change.queued_at = current_time;
+        pending_change.effective_at = effective_at;
+        
+        emit!(ParamChangeQueued {
+            pool: pending_change.pool,
+            change,
+            effective_at,
+            timestamp: current_time,
+        });
+        
+        Ok(())
+    }
+    
+    /// Rate setter: apply a queued change once its `effective_at` has passed
+    pub fn execute_param_change(ctx: Context<PendingParamChange>) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let pending_change = &ctx.accounts.pending_change;
+        require!(current_time >= pending_change.effective_at, StakingError::ChangeNotYetEffective);
+        
+        let pool_state = &mut ctx.accounts.pool_state;
+        let tier_table = active_tier_table_mut(pool_state, &mut ctx.accounts.tier_table)?;
+        
+        // Multipliers reprice unsettled time, so raise the accrual bound with them
+        let mut accrual = pool_state.accrual.to_math();
+        match pending_change.change {
+            ParamChange::TierMultipliers { common, rare, epic, legendary } => accrual.rescale(&[
+                (pool_state.common_multiplier, common),
+                (pool_state.rare_multiplier, rare),
+                (pool_state.epic_multiplier, epic),
+                (pool_state.legendary_multiplier, legendary),
+            ]),
+            ParamChange::TierMultiplier { tier, multiplier } => {
+                let old = tier_table
+                    .as_ref()
+                    .and_then(|tier_table| tier_table.tiers.get(tier as usize))
+                    .map_or(0, |entry| entry.multiplier);
+                accrual.rescale(&[(old, multiplier)])
+            }
+            ParamChange::TimeMultiplier { max_multiplier, .. } => accrual.rescale(&[(
+                math::BPS_DENOMINATOR + pool_state.max_time_multiplier,
+                math::BPS_DENOMINATOR + max_multiplier,
+            )]),
+            // The flat rate is applied when the bound is read
+            ParamChange::RewardRate { .. } | ParamChange::Vesting { .. } | ParamChange::ChangeDelay { .. } => {
+                Ok(())
+            }
+        }
+        .map_err(StakingError::from)?;
+        pool_state.accrual.apply(&accrual);
+        
+        match pending_change.change {
+            ParamChange::RewardRate { reward_rate } => {
+                pool_state.reward_rate = reward_rate;
+            }
+            ParamChange::TierMultipliers { common, rare, epic, legendary } => {
+                pool_state.common_multiplier = common;
+                pool_state.rare_multiplier = rare;
+                pool_state.epic_multiplier = epic;
+                pool_state.legendary_multiplier = legendary;
+                
+                // Keep the table's built-in tiers in step
+                if let Some(tier_table) = tier_table {
+                    for (tier, multiplier) in tier_table.tiers.iter_mut().zip([common, rare, epic, legendary]) {
+                        tier.multiplier = multiplier;
+                    }
+                }
+            }
+            ParamChange::TierMultiplier { tier, multiplier } => {
+                let entry = tier_table
+                    .and_then(|tier_table| tier_table.tiers.get_mut(tier as usize))
+                    .ok_or(StakingError::InvalidNftTier)?;
+                entry.multiplier = multiplier;
+                
+                // The first four tiers mirror the pool's built-in multipliers
+                match tier {
+                    0 => pool_state.common_multiplier = multiplier,
+                    1 => pool_state.rare_multiplier = multiplier,
+                    2 => pool_state.epic_multiplier = multiplier,
+                    3 => pool_state.legendary_multiplier = multiplier,
+                    _ => {}
+                }
+            }
+            ParamChange::TimeMultiplier { increment, period_days, max_multiplier } => {
+                pool_state.time_multiplier_increment = increment;
+                pool_state.time_multiplier_period_days = period_days;
+                pool_state.max_time_multiplier = max_multiplier;
+            }
+            ParamChange::Vesting { config } => {
+                // Exit haircuts are paid to the treasury, so one must be set
+                require!(
+                    !config.enabled
+                        || config.early_exit_haircut_bps == 0
+                        || pool_state.treasury != Pubkey::default(),
+                    StakingError::InvalidTreasury
+                );
+                pool_state.vesting = config;
+            }
+            ParamChange::ChangeDelay { delay } => {
+                pool_state.change_delay = delay;
+            }
+        }
+        
+        emit!(ParamChangeExecuted {
+            pool: pool_state.key(),
+            change: pending_change.change,
+            effective_at: pending_change.effective_at,
+            timestamp: current_time,
+        });
+        
+        Ok(())
+    }
+    
+    /// Rate setter: drop a queued change before it executes
+    pub fn cancel_pending_change(ctx: Context<PendingParamChange>) -> Result<()> {
+        let pending_change = &ctx.accounts.pending_change;
+        
+        emit!(ParamChangeCancelled {
+            pool: pending_change.pool,
+            change: pending_change.change,
+            effective_at: pending_change.effective_at,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+    
+    /// Treasurer: withdraw reward tokens the vault holds beyond outstanding liabilities
+    ///
+    /// Outstanding liabilities include an upper bound on rewards stakes have
+    /// accrued since they last settled, not only settled rewards.
+    pub fn withdraw_rewards(ctx: Context<WithdrawRewards>, amount: u64) -> Result<()> {
+        require!(amount > 0, StakingError::InvalidAmount);
+        
+        // Never withdraw tokens already owed to stakers, including what they
+        // accrued but have not settled yet
+        let pool_state = &ctx.accounts.pool_state;
+        let schedule = active_schedule(pool_state, &ctx.accounts.emission_schedule)?;
+        let outstanding = outstanding_liabilities(
+            pool_state,
+            schedule,
+            Clock::get()?.unix_timestamp,
+        )?;
+        let vault_balance = ctx.accounts.reward_vault.amount;
+        let withdrawable = vault_balance.saturating_sub(outstanding);
+        require!(amount <= withdrawable, StakingError::InsufficientRewardBalance);
+        
+        let received = pay_rewards(
+            &ctx.accounts.token_program,
+            &ctx.accounts.reward_vault,
+            &mut ctx.accounts.treasurer_token_account,
+            &ctx.accounts.reward_mint,
+            &ctx.accounts.reward_vault_authority,
+            ctx.accounts.pool_state.key(),
+            ctx.bumps.reward_vault_authority,
+            amount,
+        )?;
+        
+        emit!(RewardsWithdrawn {
+            pool: ctx.accounts.pool_state.key(),
+            treasurer: ctx.accounts.treasurer.key(),
+            amount: received,
+            vault_balance: vault_balance - amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+    
+    /// Exit path that always works: return the NFT and forfeit every unpaid reward
+    ///
+    /// Ignores pause flags so a paused pool can never trap an NFT. Pending
+    /// accrual, compounded rewards and any IOU are forfeited and released
+    /// from the pool's liabilities, and the stake account is closed.
+    pub fn withdraw_nft_forfeit_rewards(ctx: Context<WithdrawNftForfeitRewards>) -> Result<()> {
+        let stake_info = &mut ctx.accounts.stake_info;
+        let pool_state = &mut ctx.accounts.pool_state;
+        let current_time = Clock::get()?.unix_timestamp;
+        
+        // Settled rewards were recorded as liabilities, unsettled accrual is
+        // only counted through the pool's accrual bound
+        let forfeited_liability = stake_info.accumulated_compound
+            .checked_add(stake_info.reward_iou)
+            .ok_or(StakingError::ArithmeticError)?;
+        release_liability(pool_state, forfeited_liability);
+        untrack_accrual(pool_state, stake_info);
+        stake_info.accumulated_compound = 0;
+        stake_info.reward_iou = 0;
+        stake_info.is_staked = false;
+        
+        release_nft(
+            stake_info.programmable,
+            &ctx.accounts.token_metadata,
+            &ctx.accounts.escrow_nft_account,
+            &HeldPnft {
+                mint: ctx.accounts.nft_mint.to_account_info(),
+                token: ctx.accounts.user_nft_account.to_account_info(),
+                owner: ctx.accounts.owner.to_account_info(),
+                escrow_authority: ctx.accounts.escrow_authority.to_account_info(),
+                escrow_bump: ctx.bumps.escrow_authority,
+                system_program: ctx.accounts.system_program.to_account_info(),
+                token_program: ctx.accounts.token_program.to_account_info(),
+            },
+        )?;
+        
+        // Remove NFT from user's staked list
+        let user_staking_info = &mut ctx.accounts.user_staking_info;
+        if let Some(index) = user_staking_info.staked_mints.iter().position(|&mint| mint == ctx.accounts.nft_mint.key()) {
+            user_staking_info.staked_mints.remove(index);
+            user_staking_info.staked_count = user_staking_info.staked_count.saturating_sub(1);
+        }
+        let bonus_changed = update_collection_bonus(user_staking_info)?;
+        
+        emit!(NftWithdrawnForfeitingRewards {
+            user: ctx.accounts.owner.key(),
+            nft_mint: ctx.accounts.nft_mint.key(),
+            forfeited_liability,
+            timestamp: current_time,
+        });
+        
+        if bonus_changed {
+            emit!(CollectionBonusUpdated {
+                user: ctx.accounts.owner.key(),
+                staked_count: user_staking_info.staked_count,
+                new_bonus: user_staking_info.collection_bonus,
+                timestamp: current_time,
+            });
+        }
+        
+        Ok(())
+    }
+    
+    /// Admin: replace the emergency unstake penalty curve
+    ///
+    /// Points map lock progress to a penalty in basis points; the penalty is
+    /// interpolated linearly between them.
+    pub fn set_penalty_curve(ctx: Context<AdminAction>, points: Vec<PenaltyPoint>) -> Result<()> {
+        let curve: Vec<math::PenaltyPoint> = points.iter().map(PenaltyPoint::to_math).collect();
+        require!(
+            math::penalty::is_valid_curve(&curve),
+            StakingError::InvalidPenaltyCurve
+        );
+        
+        let pool_state = &mut ctx.accounts.pool_state;
+        pool_state.penalty_curve = points;
+        
+        emit!(PenaltyCurveUpdated {
+            pool: pool_state.key(),
+            point_count: pool_state.penalty_curve.len() as u8,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+    
+    /// Admin: replace the lock periods offered at stake time
+    ///
+    /// Boosts are fixed when a stake is created, so existing stakes keep the
+    /// boost of the option they chose.
+    pub fn set_lock_options(ctx: Context<AdminAction>, options: Vec<LockOption>) -> Result<()> {
+        let math_options: Vec<math::locks::LockOption> = options.iter().map(LockOption::to_math).collect();
+        require!(
+            math::locks::is_valid_options(&math_options),
+            StakingError::InvalidLockOptions
+        );
+        
+        let pool_state = &mut ctx.accounts.pool_state;
+        pool_state.lock_options = options;
+        
+        emit!(LockOptionsUpdated {
+            pool: pool_state.key(),
+            option_count: pool_state.lock_options.len() as u8,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+    
+    /// Admin: create the pool's tier table
+    ///
+    /// The table starts with the four built-in tiers at the pool's current
+    /// multipliers and from then on prices every stake in the pool.
+    pub fn initialize_tier_table(ctx: Context<InitializeTierTable>) -> Result<()> {
+        let pool_state = &mut ctx.accounts.pool_state;
+        let tier_table = &mut ctx.accounts.tier_table;
+        let current_time = Clock::get()?.unix_timestamp;
+        
+        tier_table.pool = pool_state.key();
+        let built_in = [
+            ("Common", pool_state.common_multiplier),
+            ("Rare", pool_state.rare_multiplier),
+            ("Epic", pool_state.epic_multiplier),
+            ("Legendary", pool_state.legendary_multiplier),
+        ];
+        for (index, (name, multiplier)) in built_in.into_iter().enumerate() {
+            tier_table.tiers.push(Tier { name: name.to_string(), multiplier });
+            
+            emit!(TierAdded {
+                pool: tier_table.pool,
+                tier: index as u8,
+                name: name.to_string(),
+                multiplier,
+                timestamp: current_time,
+            });
+        }
+        
+        pool_state.tier_table = tier_table.key();
+        
+        Ok(())
+    }
+    
+    /// Admin: append a new tier, its id is its position in the table
+    ///
+    /// Tiers cannot be removed, so ids stored in existing stakes stay valid.
+    /// Multipliers of existing tiers change through `queue_param_change`.
+    pub fn add_tier(ctx: Context<AddTier>, name: String, multiplier: u64) -> Result<()> {
+        require!(
+            !name.is_empty() && name.len() <= math::tiers::MAX_TIER_NAME_LEN,
+            StakingError::InvalidTierName
+        );
+        require!(multiplier > 0, StakingError::InvalidAmount);
+        
+        let tier_table = &mut ctx.accounts.tier_table;
+        require!(
+            tier_table.tiers.len() < math::tiers::MAX_TIERS,
+            StakingError::TooManyTiers
+        );
+        
+        let tier = tier_table.tiers.len() as u8;
+        tier_table.tiers.push(Tier { name: name.clone(), multiplier });
+        
+        emit!(TierAdded {
+            pool: tier_table.pool,
+            tier,
+            name,
+            multiplier,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+    
+    /// Admin: publish the Merkle root of per-mint `(mint, tier, bonus_bps)` entries
+    ///
+    /// From then on `stake_nft` only accepts tiers and bonuses proven
+    /// against the root. Existing stakes keep what they staked with.
+    pub fn initialize_rarity_root(ctx: Context<InitializeRarityRoot>, root: [u8; 32]) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        
+        let rarity_root = &mut ctx.accounts.rarity_root;
+        rarity_root.pool = ctx.accounts.pool_state.key();
+        rarity_root.root = root;
+        rarity_root.updated_at = current_time;
+        
+        ctx.accounts.pool_state.rarity_root = rarity_root.key();
+        
+        emit!(RarityRootUpdated {
+            pool: rarity_root.pool,
+            root,
+            timestamp: current_time,
+        });
+        
+        Ok(())
+    }
+    
+    /// Admin: replace the rarity root, e.g. after adding newly minted pieces
+    pub fn update_rarity_root(ctx: Context<UpdateRarityRoot>, root: [u8; 32]) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        
+        let rarity_root = &mut ctx.accounts.rarity_root;
+        rarity_root.root = root;
+        rarity_root.updated_at = current_time;
+        
+        emit!(RarityRootUpdated {
+            pool: rarity_root.pool,
+            root,
+            timestamp: current_time,
+        });
+        
+        Ok(())
+    }
+}
+
+/// Helper function to check and process milestones
+fn check_and_process_milestone(
+    stake_info: &mut Account<StakeInfo>,
+    pool_state: &mut PoolState,
+    tier_table: Option<&TierTable>,
+    owner: Pubkey,
+    current_time: i64,
+) -> Result<()> {
+    let params = pool_state.reward_params(tier_table);
+    
+    // Calculate days staked
+    let days_staked = (current_time - stake_info.staked_at) / 86400;
+    
+    // Check if any milestone has been reached but not yet claimed
+    for milestone in math::milestones::MILESTONES.iter() {
+        if !milestone.is_pending(stake_info.milestones_achieved, days_staked) {
+            continue;
+        }
+        
+        // Mark milestone as achieved
+        stake_info.milestones_achieved |= milestone.mask();
+        
+        // Calculate milestone reward (30 days worth of % bonus)
+        let milestone_reward = math::milestones::milestone_reward(&params, stake_info.tier, milestone)
+            .map_err(StakingError::from)?;
+            
+        // Apply milestone reward to accumulated compound if auto-compound is enabled
+        if stake_info.auto_compound {
+            stake_info.accumulated_compound = stake_info.accumulated_compound
+                .checked_add(milestone_reward)
+                .ok_or(StakingError::ArithmeticError)?;
+            record_liability(pool_state, milestone_reward)?;
+        }
+        
+        // Set next milestone
+        stake_info.next_milestone_days = math::milestones::next_milestone_days(
+            stake_info.milestones_achieved,
+            days_staked,
+        );
+        
+        // Emit milestone event
+        emit!(MilestoneAchieved {
+            user: owner,
+            nft_mint: stake_info.mint,
+            milestone_type: milestone.bit,
+            reward_amount: milestone_reward,
+            timestamp: current_time,
+        });
+    }
+    
+    Ok(())
+}
+
+/// Helper function to update collection bonus
+fn update_collection_bonus(
+    user_staking_info: &mut UserStakingInfo,
+) -> Result<bool> {
+    // Calculate new bonus based on number of staked NFTs
+    let new_bonus = math::collection_bonus(user_staking_info.staked_count);
+    
+    // Check if bonus changed
+    let bonus_changed = new_bonus != user_staking_info.collection_bonus;
+    
+    // Update bonus if changed
+    if bonus_changed {
+        user_staking_info.collection_bonus = new_bonus;
+    }
+    
+    Ok(bonus_changed)
+}
+
+/// Calculate rewards for a staked NFT
+fn calculate_rewards(
+    stake_info: &StakeInfo,
+    pool_state: &PoolState,
+    schedule: Option<&EmissionSchedule>,
+    tier_table: Option<&TierTable>,
+    current_time: i64,
+) -> Result<u64> {
+    let epochs = schedule.map(EmissionSchedule::math_epochs).unwrap_or_default();
+    let rewards = math::calculate_rewards(
+        &stake_info.reward_snapshot(),
+        &pool_state.reward_params(tier_table),
+        emission(schedule, &epochs),
+        current_time,
+    )
+    .map_err(StakingError::from)?;
+    
+    Ok(rewards)
+}
+
+/// Settle rewards accrued since the last checkpoint and advance `last_claim_time`
+///
+/// Claim, compound and unstake must all go through here so that no period is
+/// counted twice. The stake's pending accrual restarts from the new
+/// checkpoint in the pool's `accrual`.
+fn settle_rewards(
+    stake_info: &mut StakeInfo,
+    pool_state: &mut PoolState,
+    schedule: Option<&EmissionSchedule>,
+    tier_table: Option<&TierTable>,
+    current_time: i64,
+) -> Result<u64> {
+    let epochs = schedule.map(EmissionSchedule::math_epochs).unwrap_or_default();
+    let mut snapshot = stake_info.reward_snapshot();
+    let rewards = math::settle_rewards(
+        &mut snapshot,
+        &pool_state.reward_params(tier_table),
+        emission(schedule, &epochs),
+        current_time,
+    )
+    .map_err(StakingError::from)?;
+    
+    stake_info.last_claim_time = snapshot.last_claim_time;
+    untrack_accrual(pool_state, stake_info);
+    track_accrual(pool_state, stake_info, schedule, tier_table)?;
+    
+    Ok(rewards)
+}
+
+/// Fail with `PoolPaused` when the pool's pause flags block `instruction`
+fn require_not_paused(pool_state: &PoolState, instruction: math::pause::Instruction) -> Result<()> {
+    require!(
+        instruction.is_allowed(pool_state.pause_flags),
+        StakingError::PoolPaused
+    );
+    Ok(())
+}
+
+/// Store new pause flags and announce them
+fn set_pause(ctx: Context<PauserAction>, pause_flags: u8) -> Result<()> {
+    let pool_state = &mut ctx.accounts.pool_state;
+    pool_state.pause_flags = pause_flags;
+    
+    emit!(PoolPauseChanged {
+        pool: pool_state.key(),
+        pause_flags,
+        authority: ctx.accounts.pauser.key(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    
+    Ok(())
+}
+
+/// Settle a stake's primary rewards and split everything owed into payout and IOU
+///
+/// When the pool is in underfunded mode and the vault cannot cover all
+/// liabilities, the payout is the stake's pro-rata share; otherwise a short
+/// vault fails the claim.
+fn settle_claim(
+    stake_info: &mut StakeInfo,
+    pool_state: &mut PoolState,
+    schedule: Option<&EmissionSchedule>,
+    tier_table: Option<&TierTable>,
+    vault_balance: u64,
+    current_time: i64,
+) -> Result<math::solvency::Payout> {
+    let earned_rewards = settle_rewards(stake_info, pool_state, schedule, tier_table, current_time)?;
+    claim_settled(stake_info, pool_state, schedule, earned_rewards, vault_balance, current_time)
+}
+
+/// Split newly settled rewards plus everything already owed on a stake into
+/// what the vault pays now and an IOU
+fn claim_settled(
+    stake_info: &mut StakeInfo,
+    pool_state: &mut PoolState,
+    schedule: Option<&EmissionSchedule>,
+    earned_rewards: u64,
+    vault_balance: u64,
+    current_time: i64,
+) -> Result<math::solvency::Payout> {
+    // Settled rewards become a liability until paid
+    record_liability(pool_state, earned_rewards)?;
+    
+    // Everything owed on this stake
+    let owed = earned_rewards
+        .checked_add(stake_info.accumulated_compound)
+        .ok_or(StakingError::ArithmeticError)?
+        .checked_add(stake_info.reward_iou)
+        .ok_or(StakingError::ArithmeticError)?;
+    
+    let outstanding = if pool_state.underfunded_mode {
+        outstanding_liabilities(pool_state, schedule, current_time)?
+    } else {
+        pool_state.total_liabilities
+    };
+    let payout = math::solvency::claim_payout(owed, vault_balance, outstanding, pool_state.underfunded_mode)
+        .ok_or(StakingError::InsufficientRewardBalance)?;
+    
+    // Whatever could not be paid stays owed as an IOU
+    stake_info.accumulated_compound = 0;
+    stake_info.reward_iou = payout.shortfall;
+    release_liability(pool_state, payout.paid);
+    
+    Ok(payout)
+}
+
+/// Pay the stake's owner what it accrued in every additional reward stream up to `current_time`
+///
+/// `stream_accounts` holds one `(stream_vault, stream_mint, user_token_account,
+/// token_program)` group per stream, in registry order. The token program
+/// owns the stream's mint, SPL Token or Token-2022, and the user token account
+/// is the owner's associated token account for that mint and program.
+fn settle_streams<'info>(
+    streams: &[RewardStream],
+    stake_reward_state: &mut StakeRewardState,
+    stake_info: &StakeInfo,
+    params: &math::PoolParams,
+    nft_mint: &Pubkey,
+    stream_accounts: &'info [AccountInfo<'info>],
+    reward_vault_authority: &UncheckedAccount<'info>,
+    pool: Pubkey,
+    bump: u8,
+    current_time: i64,
+) -> Result<()> {
+    require!(
+        stream_accounts.len() == streams.len() * 4,
+        StakingError::InvalidRewardStream
+    );
+    
+    let stake_snapshot = stake_info.reward_snapshot();
+    
+    for (index, (stream, accounts)) in streams.iter().zip(stream_accounts.chunks_exact(4)).enumerate() {
+        let stream_vault = InterfaceAccount::<token_interface::TokenAccount>::try_from(&accounts[0])?;
+        let stream_mint = InterfaceAccount::<token_interface::Mint>::try_from(&accounts[1])?;
+        let mut user_token_account =
+            InterfaceAccount::<token_interface::TokenAccount>::try_from(&accounts[2])?;
+        let token_program = Interface::<TokenInterface>::try_from(&accounts[3])?;
+        require!(stream_vault.key() == stream.vault, StakingError::InvalidVault);
+        require!(stream_mint.key() == stream.mint, StakingError::InvalidMint);
+        require_keys_eq!(*accounts[1].owner, token_program.key(), StakingError::InvalidMint);
+        require!(
+            user_token_account.key()
+                == get_associated_token_address_with_program_id(
+                    &stake_info.owner,
+                    &stream.mint,
+                    &token_program.key(),
+                ),
+            StakingError::InvalidRewardDestination
+        );
+        
+        // Never accrue for time before the current stake began
+        let last_settled = std::cmp::max(
+            stake_reward_state.last_settled[index],
+            stake_info.staked_at,
+        );
+        let amount = math::streams::stream_rewards(
+            &stake_snapshot,
+            params,
+            &stream.to_math(),
+            last_settled,
+            current_time,
+        )
+        .map_err(StakingError::from)?;
+        stake_reward_state.last_settled[index] = current_time;
+        
+        if amount == 0 {
+            continue;
+        }
+        require!(stream_vault.amount >= amount, StakingError::InsufficientRewardBalance);
+        
+        let received = pay_rewards(
+            &token_program,
+            &stream_vault,
+            &mut user_token_account,
+            &stream_mint,
+            reward_vault_authority,
+            pool,
+            bump,
+            amount,
+        )?;
+        
+        emit!(StreamRewardsClaimed {
+            user: stake_info.owner,
+            nft_mint: *nft_mint,
+            reward_mint: stream.mint,
+            amount: received,
+            timestamp: current_time,
+        });
+    }
+    
+    Ok(())
+}
+
+/// `settle_streams` for instructions that also run on pools without streams
+///
+/// `reward_streams` and `stake_reward_state` are the pool's registry and the
+/// stake's checkpoints, either of which may not exist yet. Without streams no
+/// stream accounts may be passed. A stake that never created its reward state
+/// settles from when it was staked, which `require_state` refuses for stakes
+/// that stay open, as nothing would record the checkpoints.
+fn settle_unchecked_streams<'info>(
+    reward_streams: &UncheckedAccount<'info>,
+    stake_reward_state: &UncheckedAccount<'info>,
+    require_state: bool,
+    stake_info: &StakeInfo,
+    params: &math::PoolParams,
+    nft_mint: &Pubkey,
+    stream_accounts: &'info [AccountInfo<'info>],
+    reward_vault_authority: &UncheckedAccount<'info>,
+    pool: Pubkey,
+    bump: u8,
+    current_time: i64,
+) -> Result<()> {
+    let streams = if reward_streams.data_is_empty() {
+        Vec::new()
+    } else {
+        RewardStreams::try_deserialize(&mut &reward_streams.try_borrow_data()?[..])?.streams
+    };
+    if streams.is_empty() {
+        require!(stream_accounts.is_empty(), StakingError::InvalidRewardStream);
+        return Ok(());
+    }
+    
+    let has_state = !stake_reward_state.data_is_empty();
+    require!(has_state || !require_state, StakingError::InvalidRewardStream);
+    let mut state = if has_state {
+        StakeRewardState::try_deserialize(&mut &stake_reward_state.try_borrow_data()?[..])?
+    } else {
+        // Only settled from, never written
+        StakeRewardState {
+            stake: Pubkey::default(),
+            last_settled: [0; 4],
+        }
+    };
+    
+    settle_streams(
+        &streams,
+        &mut state,
+        stake_info,
+        params,
+        nft_mint,
+        stream_accounts,
+        reward_vault_authority,
+        pool,
+        bump,
+        current_time,
+    )?;
+    
+    if has_state {
+        state.try_serialize(&mut &mut stake_reward_state.try_borrow_mut_data()?[..])?;
+    }
+    Ok(())
+}
+
+/// Pay reward tokens out of the reward vault and return what `to` received
+///
+/// Uses `transfer_checked` so the reward mint may belong to SPL Token or
+/// Token-2022. A Token-2022 transfer fee is withheld from the recipient,
+/// so the amount received can be less than `amount`.
+fn pay_rewards<'info>(
+    token_program: &Interface<'info, TokenInterface>,
+    reward_vault: &InterfaceAccount<'info, token_interface::TokenAccount>,
+    to: &mut InterfaceAccount<'info, token_interface::TokenAccount>,
+    reward_mint: &InterfaceAccount<'info, token_interface::Mint>,
+    reward_vault_authority: &UncheckedAccount<'info>,
+    pool: Pubkey,
+    bump: u8,
+    amount: u64,
+) -> Result<u64> {
+    let balance_before = to.amount;
+    let signer_seeds: &[&[&[u8]]] = &[&[b"reward_vault_authority", pool.as_ref(), &[bump]]];
+    
+    token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            token_interface::TransferChecked {
+                from: reward_vault.to_account_info(),
+                mint: reward_mint.to_account_info(),
+                to: to.to_account_info(),
+                authority: reward_vault_authority.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        amount,
+        reward_mint.decimals,
+    )?;
+    
+    to.reload()?;
+    Ok(to.amount.saturating_sub(balance_before))
+}
+
+/// Give a staked NFT back: out of escrow, or unlocked in place for a pNFT
+fn release_nft<'info>(
+    programmable: bool,
+    token_metadata: &TokenMetadataAccounts<'info>,
+    escrow_nft_account: &Option<Account<'info, TokenAccount>>,
+    nft: &HeldPnft<'info>,
+) -> Result<()> {
+    if programmable {
+        return token_metadata.unlock(nft);
+    }
+    
+    let escrow_nft_account = escrow_nft_account
+        .as_ref()
+        .ok_or(StakingError::MissingEscrowAccount)?;
+    let signer_seeds: &[&[&[u8]]] = &[&[b"escrow", nft.mint.key.as_ref(), &[nft.escrow_bump]]];
+    
+    token::transfer(
+        CpiContext::new_with_signer(
+            nft.token_program.clone(),
+            token::Transfer {
+                from: escrow_nft_account.to_account_info(),
+                to: nft.token.clone(),
+                authority: nft.escrow_authority.clone(),
+            },
+            signer_seeds,
+        ),
+        1,
+    )
+}
+
+// Token Metadata instruction tags and argument variants used for pNFT staking
+const TM_DELEGATE: u8 = 44;
+const TM_REVOKE: u8 = 45;
+const TM_LOCK: u8 = 46;
+const TM_UNLOCK: u8 = 47;
+const TM_STAKING_V1: u8 = 5;       // DelegateArgs / RevokeArgs::StakingV1
+const TM_METADATA_V1_KEY: u8 = 4;  // Key::MetadataV1
+const TM_PROGRAMMABLE_STANDARDS: [u8; 2] = [4, 5]; // ProgrammableNonFungible(Edition)
+
+/// The pNFT being locked or unlocked and the accounts around it
+struct HeldPnft<'info> {
+    mint: AccountInfo<'info>,
+    token: AccountInfo<'info>,           // The owner's token account holding the pNFT
+    owner: AccountInfo<'info>,
+    escrow_authority: AccountInfo<'info>, // Staking delegate, signs lock and unlock
+    escrow_bump: u8,
+    system_program: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+}
+
+/// The parts of a Token Metadata account the program reads
+struct NftMetadata {
+    update_authority: Pubkey,
+    programmable: bool,
+    verified_collection: Option<Pubkey>, // Collection mint, once the collection has verified it
+}
+
+/// Parse `metadata` as `mint`'s Token Metadata account
+///
+/// `token_standard` and `collection` follow the variable-length name,
+/// symbol, uri and creators. Metadata written before token standards existed
+/// counts as a regular NFT without a collection.
+fn read_metadata(metadata: &AccountInfo, mint: &Pubkey) -> Result<NftMetadata> {
+    require_keys_eq!(*metadata.owner, token_metadata_program::ID, StakingError::InvalidMetadata);
+    let data = metadata.try_borrow_data()?;
+    require!(
+        data.first() == Some(&TM_METADATA_V1_KEY) && data.get(33..65) == Some(mint.as_ref()),
+        StakingError::InvalidMetadata
+    );
+    
+    let u32_at = |offset: usize| -> Result<usize> {
+        let bytes = data.get(offset..offset + 4).ok_or(StakingError::InvalidMetadata)?;
+        Ok(u32::from_le_bytes(bytes.try_into().unwrap()) as usize)
+    };
+    let byte_at = |offset: usize| data.get(offset).copied();
+    
+    // Key, update authority and mint, then name, symbol and uri
+    let mut offset = 65;
+    for _ in 0..3 {
+        offset += 4 + u32_at(offset)?;
+    }
+    offset += 2; // seller_fee_basis_points
+    if byte_at(offset) == Some(1) {
+        offset += 4 + u32_at(offset + 1)? * 34; // 34-byte creators
+    }
+    offset += 1 + 2; // creators tag, primary_sale_happened, is_mutable
+    if byte_at(offset) == Some(1) {
+        offset += 1; // edition_nonce
+    }
+    offset += 1;
+    
+    let programmable = byte_at(offset) == Some(1)
+        && matches!(byte_at(offset + 1), Some(standard) if TM_PROGRAMMABLE_STANDARDS.contains(&standard));
+    if byte_at(offset) == Some(1) {
+        offset += 1; // token_standard
+    }
+    offset += 1;
+    
+    // collection: Option<{ verified, key }>
+    let verified_collection = match data.get(offset + 2..offset + 34) {
+        Some(key) if byte_at(offset) == Some(1) && byte_at(offset + 1) == Some(1) => {
+            Some(Pubkey::try_from(key).unwrap())
+        }
+        _ => None,
+    };
+    
+    Ok(NftMetadata {
+        update_authority: Pubkey::try_from(&data[1..33]).unwrap(),
+        programmable,
+        verified_collection,
+    })
+}
+
+impl<'info> UnstakeNft<'info> {
+    /// Settle the stake's rewards up to `current_time`
+    fn settle(&mut self, current_time: i64) -> Result<u64> {
+        let schedule = active_schedule(&self.pool_state, &self.emission_schedule)?;
+        let tier_table = active_tier_table(&self.pool_state, &self.tier_table)?;
+        settle_rewards(
+            &mut self.stake_info,
+            &mut self.pool_state,
+            schedule,
+            tier_table,
+            current_time,
+        )
+    }
+    
+    /// Pay `rewards` with everything else owed on the stake, return the NFT
+    /// and take the stake off the user's and the pool's books
+    ///
+    /// Every additional reward stream is paid out too, from the groups of
+    /// four accounts per stream in `stream_accounts`.
+    /// Returns what the reward account received, or what vested, and the
+    /// shortfall a short vault left owed to the user. The stake account itself
+    /// is closed to the owner by the `close` constraint.
+    fn exit(
+        &mut self,
+        bumps: &UnstakeNftBumps,
+        stream_accounts: &'info [AccountInfo<'info>],
+        rewards: u64,
+        current_time: i64,
+    ) -> Result<math::solvency::Payout> {
+        let schedule = active_schedule(&self.pool_state, &self.emission_schedule)?;
+        let payout = claim_settled(
+            &mut self.stake_info,
+            &mut self.pool_state,
+            schedule,
+            rewards,
+            self.reward_vault.amount,
+            current_time,
+        )?;
+        
+        // The stake is closing, so a shortfall moves to the user as an IOU
+        self.user_staking_info.reward_iou = self.user_staking_info.reward_iou
+            .checked_add(payout.shortfall)
+            .ok_or(StakingError::ArithmeticError)?;
+        self.stake_info.reward_iou = 0;
+        
+        let vested = vest_claim(
+            &mut self.pool_state,
+            &mut self.vesting_schedule,
+            payout.paid,
+            current_time,
+        )?;
+        let rewards_paid = if payout.paid > 0 && !vested {
+            pay_rewards(
+                &self.reward_token_program,
+                &self.reward_vault,
+                &mut self.user_token_account,
+                &self.reward_mint,
+                &self.reward_vault_authority,
+                self.pool_state.key(),
+                bumps.reward_vault_authority,
+                payout.paid,
+            )?
+        } else {
+            payout.paid
+        };
+        
+        // Stream rewards would be lost with the stake, so settle them now
+        let tier_table = active_tier_table(&self.pool_state, &self.tier_table)?;
+        settle_unchecked_streams(
+            &self.reward_streams,
+            &self.stake_reward_state,
+            false,
+            &self.stake_info,
+            &self.pool_state.reward_params(tier_table),
+            &self.nft_mint.key(),
+            stream_accounts,
+            &self.reward_vault_authority,
+            self.pool_state.key(),
+            bumps.reward_vault_authority,
+            current_time,
+        )?;
+        
+        // Return the NFT from escrow, or unlock it in place for a pNFT
+        release_nft(
+            self.stake_info.programmable,
+            &self.token_metadata,
+            &self.escrow_nft_account,
+            &HeldPnft {
+                mint: self.nft_mint.to_account_info(),
+                token: self.user_nft_account.to_account_info(),
+                owner: self.owner.to_account_info(),
+                escrow_authority: self.escrow_authority.to_account_info(),
+                escrow_bump: bumps.escrow_authority,
+                system_program: self.system_program.to_account_info(),
+                token_program: self.token_program.to_account_info(),
+            },
+        )?;
+        
+        // Remove NFT from user's staked list and update the collection bonus
+        let user_staking_info = &mut self.user_staking_info;
+        let nft_mint = self.nft_mint.key();
+        if let Some(index) = user_staking_info.staked_mints.iter().position(|&mint| mint == nft_mint) {
+            user_staking_info.staked_mints.remove(index);
+            user_staking_info.staked_count = user_staking_info.staked_count.saturating_sub(1);
+        }
+        if update_collection_bonus(user_staking_info)? {
+            emit!(CollectionBonusUpdated {
+                user: self.owner.key(),
+                staked_count: user_staking_info.staked_count,
+                new_bonus: user_staking_info.collection_bonus,
+                timestamp: current_time,
+            });
+        }
+        
+        self.stake_info.is_staked = false;
+        untrack_accrual(&mut self.pool_state, &mut self.stake_info);
+        
+        Ok(math::solvency::Payout { paid: rewards_paid, shortfall: payout.shortfall })
+    }
+}
+
+impl<'info> TokenMetadataAccounts<'info> {
+    /// Parse `metadata` as `mint`'s Token Metadata account
+    fn read(&self, mint: &Pubkey) -> Result<NftMetadata> {
+        read_metadata(&self.metadata, mint)
+    }
+    
+    /// Delegate the pNFT to the escrow PDA for staking and lock it in the owner's wallet
+    fn lock(&self, nft: &HeldPnft<'info>) -> Result<()> {
+        let mut delegate_data = vec![TM_DELEGATE, TM_STAKING_V1];
+        delegate_data.extend_from_slice(&1u64.to_le_bytes());
+        delegate_data.push(0); // No authorization data
+        self.invoke(delegate_data, self.delegate_accounts(nft)?, &[])?;
+        
+        let signer_seeds: &[&[&[u8]]] = &[&[b"escrow", nft.mint.key.as_ref(), &[nft.escrow_bump]]];
+        self.invoke(vec![TM_LOCK, 0, 0], self.lock_accounts(nft)?, signer_seeds)
+    }
+    
+    /// Unlock the pNFT and revoke the escrow PDA's staking delegation
+    fn unlock(&self, nft: &HeldPnft<'info>) -> Result<()> {
+        let signer_seeds: &[&[&[u8]]] = &[&[b"escrow", nft.mint.key.as_ref(), &[nft.escrow_bump]]];
+        self.invoke(vec![TM_UNLOCK, 0, 0], self.lock_accounts(nft)?, signer_seeds)?;
+        
+        self.invoke(vec![TM_REVOKE, TM_STAKING_V1], self.delegate_accounts(nft)?, &[])
+    }
+    
+    /// Accounts of Token Metadata's Delegate and Revoke, as `(account, writable, signer)`
+    fn delegate_accounts(&self, nft: &HeldPnft<'info>) -> Result<Vec<(AccountInfo<'info>, bool, bool)>> {
+        Ok(vec![
+            (self.program()?, false, false), // delegate_record, unused for token delegates
+            (nft.escrow_authority.clone(), false, false),
+            (self.metadata.to_account_info(), true, false),
+            (required(&self.edition)?, false, false),
+            (required(&self.token_record)?, true, false),
+            (nft.mint.clone(), false, false),
+            (nft.token.clone(), true, false),
+            (nft.owner.clone(), false, true), // authority
+            (nft.owner.clone(), true, true),  // payer
+            (nft.system_program.clone(), false, false),
+            (required(&self.sysvar_instructions)?, false, false),
+            (nft.token_program.clone(), false, false),
+            (self.optional(&self.authorization_rules_program)?, false, false),
+            (self.optional(&self.authorization_rules)?, false, false),
+        ])
+    }
+    
+    /// Accounts of Token Metadata's Lock and Unlock, as `(account, writable, signer)`
+    fn lock_accounts(&self, nft: &HeldPnft<'info>) -> Result<Vec<(AccountInfo<'info>, bool, bool)>> {
+        Ok(vec![
+            (nft.escrow_authority.clone(), false, true), // authority, the staking delegate
+            (nft.owner.clone(), false, false),           // token_owner
+            (nft.token.clone(), true, false),
+            (nft.mint.clone(), false, false),
+            (self.metadata.to_account_info(), true, false),
+            (required(&self.edition)?, false, false),
+            (required(&self.token_record)?, true, false),
+            (nft.owner.clone(), true, true), // payer
+            (nft.system_program.clone(), false, false),
+            (required(&self.sysvar_instructions)?, false, false),
+            (nft.token_program.clone(), false, false),
+            (self.optional(&self.authorization_rules_program)?, false, false),
+            (self.optional(&self.authorization_rules)?, false, false),
+        ])
+    }
+    
+    fn program(&self) -> Result<AccountInfo<'info>> {
+        required(&self.token_metadata_program)
+    }
+    
+    /// Token Metadata takes its own program id in place of an omitted optional account
+    fn optional(&self, account: &Option<UncheckedAccount<'info>>) -> Result<AccountInfo<'info>> {
+        match account {
+            Some(account) => Ok(account.to_account_info()),
+            None => self.program(),
+        }
+    }
+    
+    fn invoke(
+        &self,
+        data: Vec<u8>,
+        accounts: Vec<(AccountInfo<'info>, bool, bool)>,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        let metas = accounts
+            .iter()
+            .map(|(account, writable, signer)| if *writable {
+                AccountMeta::new(account.key(), *signer)
+            } else {
+                AccountMeta::new_readonly(account.key(), *signer)
+            })
+            .collect();
+        let mut infos: Vec<AccountInfo<'info>> = accounts.into_iter().map(|(account, ..)| account).collect();
+        infos.push(self.program()?);
+        
+        let instruction = anchor_lang::solana_program::instruction::Instruction {
+            program_id: token_metadata_program::ID,
+            accounts: metas,
+            data,
+        };
+        anchor_lang::solana_program::program::invoke_signed(&instruction, &infos, signer_seeds)?;
+        Ok(())
+    }
+}
+
+/// An account only pNFTs need, required once the stake is programmable
+fn required<'info>(account: &Option<UncheckedAccount<'info>>) -> Result<AccountInfo<'info>> {
+    account
+        .as_ref()
+        .map(|account| account.to_account_info())
+        .ok_or_else(|| StakingError::MissingPnftAccounts.into())
+}
+
+/// The pool's emission schedule, if it has one
+///
+/// Once a schedule is active the matching account must be passed, otherwise
+/// a caller could fall back to the flat rate by omitting it.
+fn active_schedule<'a>(
+    pool_state: &PoolState,
+    emission_schedule: &'a Option<Account<EmissionSchedule>>,
+) -> Result<Option<&'a EmissionSchedule>> {
+    if pool_state.emission_schedule == Pubkey::default() {
+        return Ok(None);
+    }
+    
+    match emission_schedule {
+        Some(schedule) if schedule.key() == pool_state.emission_schedule => Ok(Some(&**schedule)),
+        _ => err!(StakingError::InvalidEmissionSchedule),
+    }
+}
+
+/// The pool's tier table, if it has one
+///
+/// Once a table is active the matching account must be passed, otherwise a
+/// caller could fall back to the pool's four built-in multipliers by omitting it.
+fn active_tier_table<'a>(
+    pool_state: &PoolState,
+    tier_table: &'a Option<Account<TierTable>>,
+) -> Result<Option<&'a TierTable>> {
+    if pool_state.tier_table == Pubkey::default() {
+        return Ok(None);
+    }
+    
+    match tier_table {
+        Some(table) if table.key() == pool_state.tier_table => Ok(Some(&**table)),
+        _ => err!(StakingError::InvalidTierTable),
+    }
+}
+
+/// Mutable access to the pool's tier table, see `active_tier_table`
+fn active_tier_table_mut<'a>(
+    pool_state: &PoolState,
+    tier_table: &'a mut Option<Account<TierTable>>,
+) -> Result<Option<&'a mut TierTable>> {
+    if pool_state.tier_table == Pubkey::default() {
+        return Ok(None);
+    }
+    
+    match tier_table {
+        Some(table) if table.key() == pool_state.tier_table => Ok(Some(&mut **table)),
+        _ => err!(StakingError::InvalidTierTable),
+    }
+}
+
+/// The pool's rarity root, if it has published one, see `active_schedule`
+fn active_rarity_root<'a>(
+    pool_state: &PoolState,
+    rarity_root: &'a Option<Account<RarityRoot>>,
+) -> Result<Option<&'a RarityRoot>> {
+    if pool_state.rarity_root == Pubkey::default() {
+        return Ok(None);
+    }
+    
+    match rarity_root {
+        Some(root) if root.key() == pool_state.rarity_root => Ok(Some(&**root)),
+        _ => err!(StakingError::InvalidRarityRoot),
+    }
+}
+
+/// Keccak-256 over the concatenated parts, through the runtime syscall
+fn keccak_hashv(parts: &[&[u8]]) -> [u8; 32] {
+    anchor_lang::solana_program::keccak::hashv(parts).to_bytes()
+}
+
+/// Base-rate source for the shared math crate
+fn emission<'a>(
+    schedule: Option<&EmissionSchedule>,
+    epochs: &'a [math::EmissionEpoch],
+) -> math::Emission<'a> {
+    match schedule {
+        Some(schedule) => math::Emission::Scheduled {
+            epochs,
+            activated_at: schedule.activated_at,
+        },
+        None => math::Emission::Flat,
+    }
+}
+
+/// Check emission epochs are present, ordered, non-overlapping and fit the account
+fn validate_emission_epochs(epochs: &[EmissionEpoch]) -> Result<()> {
+    require!(
+        epochs.len() <= math::emission::MAX_EMISSION_EPOCHS,
+        StakingError::InvalidEmissionSchedule
+    );
+    
+    let math_epochs: Vec<math::EmissionEpoch> = epochs.iter().map(EmissionEpoch::to_math).collect();
+    require!(
+        math::emission::is_valid_schedule(&math_epochs),
+        StakingError::InvalidEmissionSchedule
+    );
+    
+    Ok(())
+}
+
+/// Record rewards credited to a stake but not yet paid out of the vault
+fn record_liability(pool_state: &mut PoolState, amount: u64) -> Result<()> {
+    pool_state.total_liabilities = pool_state.total_liabilities
+        .checked_add(amount)
+        .ok_or(StakingError::ArithmeticError)?;
+    Ok(())
+}
+
+/// Release liabilities that were paid out or forfeited
+fn release_liability(pool_state: &mut PoolState, amount: u64) {
+    pool_state.total_liabilities = pool_state.total_liabilities.saturating_sub(amount);
+}
+
+/// Where the pool's accrual clock stands at `current_time`
+fn accrual_point(schedule: Option<&EmissionSchedule>, current_time: i64) -> math::solvency::AccrualPoint {
+    let epochs = schedule.map(EmissionSchedule::math_epochs).unwrap_or_default();
+    math::solvency::AccrualPoint::at(emission(schedule, &epochs), current_time)
+}
+
+/// Count what a stake accrues from its last settlement on towards the pool's liabilities
+fn track_accrual(
+    pool_state: &mut PoolState,
+    stake_info: &mut StakeInfo,
+    schedule: Option<&EmissionSchedule>,
+    tier_table: Option<&TierTable>,
+) -> Result<()> {
+    let weight = math::solvency::accrual_weight(
+        &stake_info.reward_snapshot(),
+        &pool_state.reward_params(tier_table),
+    )
+    .map_err(StakingError::from)?;
+    
+    let mut accrual = pool_state.accrual.to_math();
+    let entry = accrual
+        .track(weight, accrual_point(schedule, stake_info.last_claim_time))
+        .map_err(StakingError::from)?;
+    pool_state.accrual.apply(&accrual);
+    stake_info.accrual = StakeAccrual::from_math(&entry);
+    Ok(())
+}
+
+/// Stop counting a stake's accrual, before it settles again or leaves the pool
+fn untrack_accrual(pool_state: &mut PoolState, stake_info: &mut StakeInfo) {
+    let mut accrual = pool_state.accrual.to_math();
+    accrual.untrack(&stake_info.accrual.to_math());
+    pool_state.accrual.apply(&accrual);
+    stake_info.accrual = StakeAccrual::default();
+}
+
+/// Settled rewards not yet paid plus a bound on what every stake accrued since it last settled
+fn outstanding_liabilities(
+    pool_state: &PoolState,
+    schedule: Option<&EmissionSchedule>,
+    current_time: i64,
+) -> Result<u64> {
+    let pending = pool_state.accrual
+        .to_math()
+        .pending(pool_state.reward_rate, accrual_point(schedule, current_time))
+        .map_err(StakingError::from)?;
+    pool_state.total_liabilities
+        .checked_add(pending)
+        .ok_or(StakingError::ArithmeticError.into())
+}
+
+/// Lock a claim's payout in the owner's vesting schedule when the pool vests rewards
+///
+/// Returns whether `amount` was vested. Vested rewards stay in the reward
+/// vault and remain a liability until withdrawn.
+fn vest_claim(
+    pool_state: &mut PoolState,
+    vesting_schedule: &mut Option<Account<VestingSchedule>>,
+    amount: u64,
+    current_time: i64,
+) -> Result<bool> {
+    if !pool_state.vesting.enabled || amount == 0 {
+        return Ok(false);
+    }
+    let vesting_schedule = vesting_schedule
+        .as_mut()
+        .ok_or(StakingError::VestingScheduleRequired)?;
+    
+    let mut state = vesting_schedule.to_math();
+    state
+        .deposit(&pool_state.vesting_terms(), amount, current_time)
+        .map_err(StakingError::from)?;
+    vesting_schedule.apply(&state);
+    record_liability(pool_state, amount)?;
+    
+    emit!(RewardsVested {
+        user: vesting_schedule.owner,
+        pool: vesting_schedule.pool,
+        amount,
+        locked: state.locked(&pool_state.vesting_terms(), current_time),
+        timestamp: current_time,
+    });
+    
+    Ok(true)
+}
+
+impl StakeInfo {
+    /// Reward-relevant fields in the form the shared math crate expects
+    pub fn reward_snapshot(&self) -> math::StakeSnapshot {
+        math::StakeSnapshot {
+            staked_at: self.staked_at,
+            release_date: self.release_date,
+            last_claim_time: self.last_claim_time,
+            staking_period: self.staking_period,
+            tier: self.tier,
+            current_time_multiplier: self.current_time_multiplier,
+            compound_streak_multiplier: self.compound_streak_multiplier,
+            rarity_bonus_bps: self.rarity_bonus_bps as u64,
+            lock_boost_bps: self.lock_boost_bps as u64,
+        }
+    }
+}
+
+impl PoolState {
+    /// Reward parameters in the form the shared math crate expects
+    ///
+    /// Tiers are priced by `tier_table` once the pool has one, otherwise by
+    /// the four multipliers stored on the pool.
+    pub fn reward_params(&self, tier_table: Option<&TierTable>) -> math::PoolParams {
+        let tiers = match tier_table {
+            Some(table) => table.math_tiers(),
+            None => math::TierMultipliers::new(&[
+                self.common_multiplier,
+                self.rare_multiplier,
+                self.epic_multiplier,
+                self.legendary_multiplier,
+            ])
+            .unwrap_or_default(),
+        };
+        
+        math::PoolParams {
+            reward_rate: self.reward_rate,
+            tiers,
+            time_multiplier_increment: self.time_multiplier_increment,
+            time_multiplier_period_days: self.time_multiplier_period_days,
+            max_time_multiplier: self.max_time_multiplier,
+        }
+    }
+    
+    /// The penalty curve in the shared math crate's representation
+    pub fn math_penalty_curve(&self) -> Vec<math::PenaltyPoint> {
+        self.penalty_curve.iter().map(PenaltyPoint::to_math).collect()
+    }
+    
+    /// Vesting terms in effect, all-zero so everything is unlocked while vesting is off
+    pub fn vesting_terms(&self) -> math::vesting::VestingConfig {
+        if self.vesting.enabled {
+            self.vesting.to_math()
+        } else {
+            math::vesting::VestingConfig::default()
+        }
+    }
+    
+    /// The lock options in the shared math crate's representation
+    pub fn math_lock_options(&self) -> Vec<math::locks::LockOption> {
+        self.lock_options.iter().map(LockOption::to_math).collect()
+    }
+}
+
+impl EmissionEpoch {
+    pub fn to_math(&self) -> math::EmissionEpoch {
+        math::EmissionEpoch {
+            start_time: self.start_time,
+            end_time: self.end_time,
+            reward_rate: self.reward_rate,
+        }
+    }
+}
+
+impl ParamChange {
+    /// Seed byte distinguishing the pending-change account of each kind
+    pub fn kind(&self) -> u8 {
+        match self {
+            ParamChange::RewardRate { .. } => 0,
+            ParamChange::TierMultipliers { .. } => 1,
+            ParamChange::TimeMultiplier { .. } => 2,
+            ParamChange::TierMultiplier { .. } => 3,
+            ParamChange::Vesting { .. } => 4,
+            ParamChange::ChangeDelay { .. } => 5,
+        }
+    }
+    
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            ParamChange::TimeMultiplier { period_days, .. } => {
+                require!(*period_days > 0, StakingError::InvalidAmount);
+            }
+            ParamChange::TierMultiplier { tier, .. } => {
+                require!((*tier as usize) < math::tiers::MAX_TIERS, StakingError::InvalidNftTier);
+            }
+            ParamChange::Vesting { config } => {
+                require!(
+                    !config.enabled || math::vesting::is_valid_config(&config.to_math()),
+                    StakingError::InvalidVestingConfig
+                );
+            }
+            ParamChange::ChangeDelay { delay } => {
+                require!(*delay >= MIN_CHANGE_DELAY, StakingError::InvalidChangeDelay);
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+impl VestingConfig {
+    pub fn to_math(&self) -> math::vesting::VestingConfig {
+        math::vesting::VestingConfig {
+            cliff_seconds: self.cliff_seconds,
+            duration_seconds: self.duration_seconds,
+            early_exit_haircut_bps: self.early_exit_haircut_bps,
+        }
+    }
+}
+
+impl Accrual {
+    pub fn to_math(&self) -> math::solvency::Accrual {
+        math::solvency::Accrual {
+            weight: self.weight,
+            flat_offset: self.flat_offset,
+            scheduled_offset: self.scheduled_offset,
+            scale_bps: self.scale_bps,
+        }
+    }
+    
+    /// Store the bound after it changed in the math crate
+    pub fn apply(&mut self, accrual: &math::solvency::Accrual) {
+        self.weight = accrual.weight;
+        self.flat_offset = accrual.flat_offset;
+        self.scheduled_offset = accrual.scheduled_offset;
+        self.scale_bps = accrual.scale_bps;
+    }
+}
+
+impl StakeAccrual {
+    pub fn to_math(&self) -> math::solvency::StakeAccrual {
+        math::solvency::StakeAccrual {
+            weight: self.weight,
+            point: math::solvency::AccrualPoint {
+                flat_seconds: self.flat_seconds,
+                scheduled: self.scheduled,
+            },
+        }
+    }
+    
+    pub fn from_math(entry: &math::solvency::StakeAccrual) -> Self {
+        Self {
+            weight: entry.weight,
+            flat_seconds: entry.point.flat_seconds,
+            scheduled: entry.point.scheduled,
+        }
+    }
+}
+
+impl VestingSchedule {
+    /// The schedule in the form the shared math crate expects
+    pub fn to_math(&self) -> math::vesting::VestingState {
+        math::vesting::VestingState {
+            amount: self.amount,
+            start_time: self.start_time,
+            withdrawn: self.withdrawn,
+            carried: self.carried,
+        }
+    }
+    
+    pub fn apply(&mut self, state: &math::vesting::VestingState) {
+        self.amount = state.amount;
+        self.start_time = state.start_time;
+        self.withdrawn = state.withdrawn;
+        self.carried = state.carried;
+    }
+}
+
+impl PenaltyPoint {
+    pub fn to_math(&self) -> math::PenaltyPoint {
+        math::PenaltyPoint {
+            progress_percent: self.progress_percent,
+            penalty_bps: self.penalty_bps,
+        }
+    }
+    
+    pub fn from_math(point: &math::PenaltyPoint) -> Self {
+        Self {
+            progress_percent: point.progress_percent,
+            penalty_bps: point.penalty_bps,
+        }
+    }
+}
+
+impl LockOption {
+    pub fn to_math(&self) -> math::locks::LockOption {
+        math::locks::LockOption {
+            days: self.days,
+            boost_bps: self.boost_bps,
+        }
+    }
+    
+    pub fn from_math(option: &math::locks::LockOption) -> Self {
+        Self {
+            days: option.days,
+            boost_bps: option.boost_bps,
+        }
+    }
+}
+
+impl RewardStream {
+    /// The stream as a single emission epoch for the shared math crate
+    pub fn to_math(&self) -> math::EmissionEpoch {
+        math::EmissionEpoch {
+            start_time: self.start_time,
+            end_time: self.end_time,
+            reward_rate: self.reward_rate,
+        }
+    }
+}
+
+impl EmissionSchedule {
+    /// Epochs in the form the shared math crate expects
+    pub fn math_epochs(&self) -> Vec<math::EmissionEpoch> {
+        self.epochs.iter().map(EmissionEpoch::to_math).collect()
+    }
+}
+
+impl TierTable {
+    /// Multipliers in the form the shared math crate expects
+    pub fn math_tiers(&self) -> math::TierMultipliers {
+        let multipliers: Vec<u64> = self.tiers.iter().map(|tier| tier.multiplier).collect();
+        math::TierMultipliers::new(&multipliers).unwrap_or_default()
+    }
+}
+
+impl From<math::MathError> for StakingError {
+    fn from(_: math::MathError) -> Self {
+        StakingError::ArithmeticError
+    }
+}
+
+#[derive(Accounts)]
+pub struct InitializePool<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    
+    pub collection_mint: Account<'info, Mint>,
+    
+    /// CHECK: The collection NFT's metadata, parsed and matched to the mint by `read_metadata`
+    pub collection_metadata: UncheckedAccount<'info>,
+    
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + PoolState::INIT_SPACE,
+        seeds = [b"pool_state", collection_mint.key().as_ref()],
+        bump,
+    )]
+    pub pool_state: Account<'info, PoolState>,
+    
+    /// The program's upgrade data, when its upgrade authority creates the pool
+    #[account(
+        seeds = [crate::ID.as_ref()],
+        bump,
+        seeds::program = anchor_lang::solana_program::bpf_loader_upgradeable::ID,
+    )]
+    pub program_data: Option<Account<'info, ProgramData>>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateTimeMultiplier<'info> {
+    /// The owner or their delegate
+    pub authority: Signer<'info>,
+    
+    /// CHECK: The stake's owner, matched against stake_info and user_staking_info
+    pub owner: UncheckedAccount<'info>,
+    
+    pub nft_mint: Account<'info, Mint>,
+    
+    #[account(
+        mut,
+        seeds = [b"stake", pool_state.key().as_ref(), nft_mint.key().as_ref()],
+        bump,
+        constraint = stake_info.owner == owner.key() @ StakingError::NotOwner,
+        constraint = stake_info.is_staked == true @ StakingError::NotStaked,
+    )]
+    pub stake_info: Account<'info, StakeInfo>,
+    
+    #[account(mut)]
+    pub pool_state: Account<'info, PoolState>,
+    
+    #[account(constraint = tier_table.pool == pool_state.key() @ StakingError::InvalidTierTable)]
+    pub tier_table: Option<Account<'info, TierTable>>,
+    
+    #[account(
+        seeds = [b"user_staking", pool_state.key().as_ref(), owner.key().as_ref()],
+        bump,
+        constraint = user_staking_info.is_authorized(authority.key()) @ StakingError::NotOwnerOrDelegate,
+    )]
+    pub user_staking_info: Account<'info, UserStakingInfo>,
+}
+
+#[derive(Accounts)]
+pub struct ProcessAutoCompound<'info> {
+    /// The owner or their delegate
+    pub authority: Signer<'info>,
+    
+    /// CHECK: The stake's owner, matched against stake_info and user_staking_info
+    pub owner: UncheckedAccount<'info>,
+    
+    pub nft_mint: Account<'info, Mint>,
+    
+    #[account(
+        mut,
+        seeds = [b"stake", pool_state.key().as_ref(), nft_mint.key().as_ref()],
+        bump,
+        constraint = stake_info.owner == owner.key() @ StakingError::NotOwner,
+        constraint = stake_info.is_staked == true @ StakingError::NotStaked,
+        constraint = stake_info.auto_compound == true @ StakingError::AutoCompoundNotEnabled,
+    )]
+    pub stake_info: Account<'info, StakeInfo>,
+    
+    #[account(mut)]
+    pub pool_state: Account<'info, PoolState>,
+    
+    #[account(constraint = emission_schedule.pool == pool_state.key() @ StakingError::InvalidEmissionSchedule)]
+    pub emission_schedule: Option<Account<'info, EmissionSchedule>>,
+    
+    #[account(constraint = tier_table.pool == pool_state.key() @ StakingError::InvalidTierTable)]
+    pub tier_table: Option<Account<'info, TierTable>>,
+    
+    #[account(
+        seeds = [b"user_staking", pool_state.key().as_ref(), owner.key().as_ref()],
+        bump,
+        constraint = user_staking_info.is_authorized(authority.key()) @ StakingError::NotOwnerOrDelegate,
+    )]
+    pub user_staking_info: Account<'info, UserStakingInfo>,
+}
+
+#[derive(Accounts)]
+pub struct ExtendLock<'info> {
+    pub owner: Signer<'info>,
+    
+    pub nft_mint: Account<'info, Mint>,
+    
+    #[account(
+        mut,
+        seeds = [b"stake", pool_state.key().as_ref(), nft_mint.key().as_ref()],
+        bump,
+        constraint = stake_info.owner == owner.key() @ StakingError::NotOwner,
+        constraint = stake_info.is_staked == true @ StakingError::NotStaked,
+    )]
+    pub stake_info: Account<'info, StakeInfo>,
+    
+    #[account(mut)]
+    pub pool_state: Account<'info, PoolState>,
+    
+    #[account(constraint = emission_schedule.pool == pool_state.key() @ StakingError::InvalidEmissionSchedule)]
+    pub emission_schedule: Option<Account<'info, EmissionSchedule>>,
+    
+    #[account(constraint = tier_table.pool == pool_state.key() @ StakingError::InvalidTierTable)]
+    pub tier_table: Option<Account<'info, TierTable>>,
+}
+
+#[derive(Accounts)]
+pub struct TransferStake<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    /// CHECK: Any wallet can receive a stake once it ran init_user_staking_info
+    #[account(constraint = new_owner.key() != owner.key() @ StakingError::InvalidStakeTransfer)]
+    pub new_owner: UncheckedAccount<'info>,
+    
+    pub nft_mint: Account<'info, Mint>,
+    
+    #[account(
+        mut,
+        seeds = [b"stake", pool_state.key().as_ref(), nft_mint.key().as_ref()],
+        bump,
+        constraint = stake_info.owner == owner.key() @ StakingError::NotOwner,
+        constraint = stake_info.is_staked == true @ StakingError::NotStaked,
+    )]
+    pub stake_info: Account<'info, StakeInfo>,
+    
+    #[account(mut)]
+    pub pool_state: Account<'info, PoolState>,
+    
+    #[account(constraint = emission_schedule.pool == pool_state.key() @ StakingError::InvalidEmissionSchedule)]
+    pub emission_schedule: Option<Account<'info, EmissionSchedule>>,
+    
+    #[account(constraint = tier_table.pool == pool_state.key() @ StakingError::InvalidTierTable)]
+    pub tier_table: Option<Account<'info, TierTable>>,
+    
+    #[account(
+        mut,
+        seeds = [b"user_staking", pool_state.key().as_ref(), owner.key().as_ref()],
+        bump,
+        constraint = user_staking_info.owner == owner.key() @ StakingError::NotOwner,
+    )]
+    pub user_staking_info: Account<'info, UserStakingInfo>,
+    
+    #[account(
+        mut,
+        seeds = [b"user_staking", pool_state.key().as_ref(), new_owner.key().as_ref()],
+        bump,
+        constraint = new_user_staking_info.owner == new_owner.key() @ StakingError::NotOwner,
+    )]
+    pub new_user_staking_info: Account<'info, UserStakingInfo>,
+    
+    #[account(
+        mut,
+        seeds = [b"vesting", pool_state.key().as_ref(), owner.key().as_ref()],
+        bump,
+    )]
+    pub vesting_schedule: Option<Account<'info, VestingSchedule>>,
+    
+    #[account(
+        mut,
+        constraint = reward_vault.key() == pool_state.reward_vault @ StakingError::InvalidVault,
+    )]
+    pub reward_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    /// CHECK: PDA signing for the reward vault
+    #[account(
+        seeds = [b"reward_vault_authority", pool_state.key().as_ref()],
+        bump,
+    )]
+    pub reward_vault_authority: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        constraint = user_token_account.mint == pool_state.reward_mint @ StakingError::InvalidMint,
+        constraint = user_token_account.key() == user_staking_info.reward_account(&pool_state.reward_mint, &pool_state.reward_token_program) @ StakingError::InvalidRewardDestination,
+    )]
+    pub user_token_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    #[account(
+        constraint = reward_mint.key() == pool_state.reward_mint @ StakingError::InvalidMint,
+    )]
+    pub reward_mint: InterfaceAccount<'info, token_interface::Mint>,
+    
+    /// CHECK: The pool's reward stream registry, empty until the pool adds streams
+    #[account(
+        seeds = [b"reward_streams", pool_state.key().as_ref()],
+        bump,
+    )]
+    pub reward_streams: UncheckedAccount<'info>,
+    
+    /// CHECK: The stake's stream checkpoints, required once the pool has streams
+    #[account(
+        mut,
+        seeds = [b"stake_rewards", stake_info.key().as_ref()],
+        bump,
+    )]
+    pub stake_reward_state: UncheckedAccount<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct InitUserStakingInfo<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    
+    pub pool_state: Account<'info, PoolState>,
+    
+    #[account(
+        init,
+        payer = user,
+        space = 8 + UserStakingInfo::INIT_SPACE,
+        seeds = [b"user_staking", pool_state.key().as_ref(), user.key().as_ref()],
+        bump,
+    )]
+    pub user_staking_info: Account<'info, UserStakingInfo>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetDelegate<'info> {
+    pub owner: Signer<'info>,
+    
+    pub pool_state: Account<'info, PoolState>,
+    
+    #[account(
+        mut,
+        seeds = [b"user_staking", pool_state.key().as_ref(), owner.key().as_ref()],
+        bump,
+        constraint = user_staking_info.owner == owner.key() @ StakingError::NotOwner,
+    )]
+    pub user_staking_info: Account<'info, UserStakingInfo>,
+}
+
+#[derive(Accounts)]
+pub struct SetTreasury<'info> {
+    pub admin: Signer<'info>,
+    
+    #[account(
+        mut,
+        has_one = admin @ StakingError::NotAdmin,
+    )]
+    pub pool_state: Account<'info, PoolState>,
+    
+    #[account(constraint = treasury.mint == pool_state.reward_mint @ StakingError::InvalidMint)]
+    pub treasury: InterfaceAccount<'info, token_interface::TokenAccount>,
+}
+
+#[derive(Accounts)]
+pub struct SetRewardDestination<'info> {
+    pub owner: Signer<'info>,
+    
+    pub pool_state: Account<'info, PoolState>,
+    
+    #[account(
+        mut,
+        seeds = [b"user_staking", pool_state.key().as_ref(), owner.key().as_ref()],
+        bump,
+        constraint = user_staking_info.owner == owner.key() @ StakingError::NotOwner,
+    )]
+    pub user_staking_info: Account<'info, UserStakingInfo>,
+    
+    #[account(constraint = destination.mint == pool_state.reward_mint @ StakingError::InvalidMint)]
+    pub destination: Option<InterfaceAccount<'info, token_interface::TokenAccount>>,
+}
+
+#[derive(Accounts)]
+pub struct SetCompoundFrequency<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    pub nft_mint: Account<'info, Mint>,
+    
+    #[account(
+        mut,
+        seeds = [b"stake", pool_state.key().as_ref(), nft_mint.key().as_ref()],
+        bump,
+        constraint = stake_info.owner == owner.key() @ StakingError::NotOwner,
+        constraint = stake_info.is_staked == true @ StakingError::NotStaked,
+    )]
+    pub stake_info: Account<'info, StakeInfo>,
+    
+    pub pool_state: Account<'info, PoolState>,
+}
+
+/// Accounts for `unstake_nft` and `emergency_unstake_nft`
+#[derive(Accounts)]
+pub struct UnstakeNft<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    pub nft_mint: Account<'info, Mint>,
+    
+    #[account(
+        mut,
+        close = owner,
+        seeds = [b"stake", pool_state.key().as_ref(), nft_mint.key().as_ref()],
+        bump,
+        constraint = stake_info.owner == owner.key() @ StakingError::NotOwner,
+        constraint = stake_info.is_staked == true @ StakingError::NotStaked,
+    )]
+    pub stake_info: Account<'info, StakeInfo>,
+    
+    #[account(
+        mut,
+        seeds = [b"user_staking", pool_state.key().as_ref(), owner.key().as_ref()],
+        bump,
+    )]
+    pub user_staking_info: Account<'info, UserStakingInfo>,
+    
+    #[account(mut)]
+    pub pool_state: Account<'info, PoolState>,
+    
+    #[account(constraint = emission_schedule.pool == pool_state.key() @ StakingError::InvalidEmissionSchedule)]
+    pub emission_schedule: Option<Account<'info, EmissionSchedule>>,
+    
+    #[account(constraint = tier_table.pool == pool_state.key() @ StakingError::InvalidTierTable)]
+    pub tier_table: Option<Account<'info, TierTable>>,
+    
+    #[account(
+        mut,
+        seeds = [b"vesting", pool_state.key().as_ref(), owner.key().as_ref()],
+        bump,
+    )]
+    pub vesting_schedule: Option<Account<'info, VestingSchedule>>,
+    
+    #[account(
+        mut,
+        constraint = reward_vault.key() == pool_state.reward_vault @ StakingError::InvalidVault,
+    )]
+    pub reward_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    /// CHECK: PDA signing for the reward vault
+    #[account(
+        seeds = [b"reward_vault_authority", pool_state.key().as_ref()],
+        bump,
+    )]
+    pub reward_vault_authority: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        constraint = user_token_account.mint == pool_state.reward_mint @ StakingError::InvalidMint,
+        constraint = user_token_account.key() == user_staking_info.reward_account(&pool_state.reward_mint, &pool_state.reward_token_program) @ StakingError::InvalidRewardDestination,
+    )]
+    pub user_token_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    #[account(
+        constraint = reward_mint.key() == pool_state.reward_mint @ StakingError::InvalidMint,
+    )]
+    pub reward_mint: InterfaceAccount<'info, token_interface::Mint>,
+    
+    /// CHECK: The pool's reward stream registry, empty until the pool adds streams
+    #[account(
+        seeds = [b"reward_streams", pool_state.key().as_ref()],
+        bump,
+    )]
+    pub reward_streams: UncheckedAccount<'info>,
+    
+    /// CHECK: The stake's stream checkpoints, empty if it never created them
+    #[account(
+        mut,
+        seeds = [b"stake_rewards", stake_info.key().as_ref()],
+        bump,
+    )]
+    pub stake_reward_state: UncheckedAccount<'info>,
+    
+    /// Regular NFTs only
+    #[account(
+        mut,
+        associated_token::mint = nft_mint,
+        associated_token::authority = escrow_authority,
+    )]
+    pub escrow_nft_account: Option<Account<'info, TokenAccount>>,
+    
+    /// CHECK: PDA holding staked NFTs
+    #[account(
+        seeds = [b"escrow", nft_mint.key().as_ref()],
+        bump,
+    )]
+    pub escrow_authority: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        constraint = user_nft_account.mint == nft_mint.key() @ StakingError::InvalidMint,
+        constraint = user_nft_account.owner == owner.key() @ StakingError::NotOwner,
+    )]
+    pub user_nft_account: Account<'info, TokenAccount>,
+    
+    pub token_metadata: TokenMetadataAccounts<'info>,
+    
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub reward_token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct StakeNft<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    pub nft_mint: Account<'info, Mint>,
+    
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + StakeInfo::INIT_SPACE,
+        seeds = [b"stake", pool_state.key().as_ref(), nft_mint.key().as_ref()],
+        bump,
+    )]
+    pub stake_info: Account<'info, StakeInfo>,
+    
+    /// Regular NFTs only
+    #[account(
+        mut,
+        associated_token::mint = nft_mint,
+        associated_token::authority = escrow_authority,
+    )]
+    pub escrow_nft_account: Option<Account<'info, TokenAccount>>,
+    
+    /// CHECK: PDA holding staked NFTs
+    #[account(
+        seeds = [b"escrow", nft_mint.key().as_ref()],
+        bump,
+    )]
+    pub escrow_authority: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        constraint = user_nft_account.mint == nft_mint.key() @ StakingError::InvalidMint,
+        constraint = user_nft_account.owner == owner.key() @ StakingError::NotOwner,
+        constraint = user_nft_account.amount == 1 @ StakingError::InvalidMint,
+    )]
+    pub user_nft_account: Account<'info, TokenAccount>,
+    
+    pub token_metadata: TokenMetadataAccounts<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"user_staking", pool_state.key().as_ref(), owner.key().as_ref()],
+        bump,
+        constraint = user_staking_info.owner == owner.key() @ StakingError::NotOwner,
+    )]
+    pub user_staking_info: Account<'info, UserStakingInfo>,
+    
+    #[account(mut)]
+    pub pool_state: Account<'info, PoolState>,
+    
+    #[account(constraint = emission_schedule.pool == pool_state.key() @ StakingError::InvalidEmissionSchedule)]
+    pub emission_schedule: Option<Account<'info, EmissionSchedule>>,
+    
+    #[account(constraint = tier_table.pool == pool_state.key() @ StakingError::InvalidTierTable)]
+    pub tier_table: Option<Account<'info, TierTable>>,
+    
+    #[account(constraint = rarity_root.pool == pool_state.key() @ StakingError::InvalidRarityRoot)]
+    pub rarity_root: Option<Account<'info, RarityRoot>>,
+    
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawNftForfeitRewards<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    pub nft_mint: Account<'info, Mint>,
+    
+    #[account(
+        mut,
+        close = owner,
+        seeds = [b"stake", pool_state.key().as_ref(), nft_mint.key().as_ref()],
+        bump,
+        constraint = stake_info.owner == owner.key() @ StakingError::NotOwner,
+        constraint = stake_info.is_staked == true @ StakingError::NotStaked,
+    )]
+    pub stake_info: Account<'info, StakeInfo>,
+    
+    /// Regular NFTs only
+    #[account(
+        mut,
+        associated_token::mint = nft_mint,
+        associated_token::authority = escrow_authority,
+    )]
+    pub escrow_nft_account: Option<Account<'info, TokenAccount>>,
+    
+    /// CHECK: PDA holding staked NFTs
+    #[account(
+        seeds = [b"escrow", nft_mint.key().as_ref()],
+        bump,
+    )]
+    pub escrow_authority: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        constraint = user_nft_account.mint == nft_mint.key() @ StakingError::InvalidMint,
+        constraint = user_nft_account.owner == owner.key() @ StakingError::NotOwner,
+    )]
+    pub user_nft_account: Account<'info, TokenAccount>,
+    
+    pub token_metadata: TokenMetadataAccounts<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"user_staking", pool_state.key().as_ref(), owner.key().as_ref()],
+        bump,
+    )]
+    pub user_staking_info: Account<'info, UserStakingInfo>,
+    
+    #[account(mut)]
+    pub pool_state: Account<'info, PoolState>,
+    
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+}
+
+/// Token Metadata accounts for staking a pNFT in place
+///
+/// Regular NFTs only need `metadata`, read for its token standard and collection.
+#[derive(Accounts)]
+pub struct TokenMetadataAccounts<'info> {
+    /// CHECK: The NFT's metadata, matched to the mint by `read_metadata`
+    #[account(mut)]
+    pub metadata: UncheckedAccount<'info>,
+    
+    /// CHECK: Master edition, checked by Token Metadata
+    pub edition: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: Token record of the owner's token account, checked by Token Metadata
+    #[account(mut)]
+    pub token_record: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: The pNFT's rule set, checked by Token Metadata
+    pub authorization_rules: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: Program evaluating the rule set
+    pub authorization_rules_program: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: Token Metadata program
+    #[account(address = token_metadata_program::ID)]
+    pub token_metadata_program: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: Instructions sysvar, read by Token Metadata
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub sysvar_instructions: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimRewards<'info> {
+    /// The owner or their delegate
+    pub authority: Signer<'info>,
+    
+    /// CHECK: The stake's owner, matched against stake_info and user_staking_info
+    pub owner: UncheckedAccount<'info>,
     
     pub nft_mint: Account<'info, Mint>,
     
     #[account(
         mut,
-        seeds = [b"stake", nft_mint.key().as_ref()],
+        seeds = [b"stake", pool_state.key().as_ref(), nft_mint.key().as_ref()],
+        bump,
+        constraint = stake_info.owner == owner.key() @ StakingError::NotOwner,
+        constraint = stake_info.is_staked == true @ StakingError::NotStaked,
+    )]
+    pub stake_info: Account<'info, StakeInfo>,
+    
+    #[account(mut)]
+    pub pool_state: Account<'info, PoolState>,
+    
+    #[account(constraint = emission_schedule.pool == pool_state.key() @ StakingError::InvalidEmissionSchedule)]
+    pub emission_schedule: Option<Account<'info, EmissionSchedule>>,
+    
+    #[account(constraint = tier_table.pool == pool_state.key() @ StakingError::InvalidTierTable)]
+    pub tier_table: Option<Account<'info, TierTable>>,
+    
+    #[account(
+        seeds = [b"user_staking", pool_state.key().as_ref(), owner.key().as_ref()],
+        bump,
+        constraint = user_staking_info.is_authorized(authority.key()) @ StakingError::NotOwnerOrDelegate,
+    )]
+    pub user_staking_info: Account<'info, UserStakingInfo>,
+    
+    #[account(
+        mut,
+        seeds = [b"vesting", pool_state.key().as_ref(), owner.key().as_ref()],
+        bump,
+    )]
+    pub vesting_schedule: Option<Account<'info, VestingSchedule>>,
+    
+    #[account(
+        mut,
+        constraint = reward_vault.key() == pool_state.reward_vault @ StakingError::InvalidVault,
+    )]
+    pub reward_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    /// CHECK: PDA signing for the reward vault
+    #[account(
+        seeds = [b"reward_vault_authority", pool_state.key().as_ref()],
+        bump,
+    )]
+    pub reward_vault_authority: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        constraint = user_token_account.mint == pool_state.reward_mint @ StakingError::InvalidMint,
+        constraint = user_token_account.key() == user_staking_info.reward_account(&pool_state.reward_mint, &pool_state.reward_token_program) @ StakingError::InvalidRewardDestination,
+    )]
+    pub user_token_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    #[account(
+        constraint = reward_mint.key() == pool_state.reward_mint @ StakingError::InvalidMint,
+    )]
+    pub reward_mint: InterfaceAccount<'info, token_interface::Mint>,
+    
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimRewardIou<'info> {
+    /// The owner or their delegate
+    pub authority: Signer<'info>,
+    
+    /// CHECK: The IOU's owner, matched against user_staking_info
+    pub owner: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    pub pool_state: Account<'info, PoolState>,
+    
+    #[account(constraint = emission_schedule.pool == pool_state.key() @ StakingError::InvalidEmissionSchedule)]
+    pub emission_schedule: Option<Account<'info, EmissionSchedule>>,
+    
+    #[account(
+        mut,
+        seeds = [b"user_staking", pool_state.key().as_ref(), owner.key().as_ref()],
+        bump,
+        constraint = user_staking_info.is_authorized(authority.key()) @ StakingError::NotOwnerOrDelegate,
+    )]
+    pub user_staking_info: Account<'info, UserStakingInfo>,
+    
+    #[account(
+        mut,
+        seeds = [b"vesting", pool_state.key().as_ref(), owner.key().as_ref()],
+        bump,
+    )]
+    pub vesting_schedule: Option<Account<'info, VestingSchedule>>,
+    
+    #[account(
+        mut,
+        constraint = reward_vault.key() == pool_state.reward_vault @ StakingError::InvalidVault,
+    )]
+    pub reward_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    /// CHECK: PDA signing for the reward vault
+    #[account(
+        seeds = [b"reward_vault_authority", pool_state.key().as_ref()],
+        bump,
+    )]
+    pub reward_vault_authority: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        constraint = user_token_account.mint == pool_state.reward_mint @ StakingError::InvalidMint,
+        constraint = user_token_account.key() == user_staking_info.reward_account(&pool_state.reward_mint, &pool_state.reward_token_program) @ StakingError::InvalidRewardDestination,
+    )]
+    pub user_token_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    #[account(
+        constraint = reward_mint.key() == pool_state.reward_mint @ StakingError::InvalidMint,
+    )]
+    pub reward_mint: InterfaceAccount<'info, token_interface::Mint>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct ViewSolvency<'info> {
+    pub pool_state: Account<'info, PoolState>,
+    
+    #[account(constraint = emission_schedule.pool == pool_state.key() @ StakingError::InvalidEmissionSchedule)]
+    pub emission_schedule: Option<Account<'info, EmissionSchedule>>,
+    
+    #[account(
+        constraint = reward_vault.key() == pool_state.reward_vault @ StakingError::InvalidVault,
+    )]
+    pub reward_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+}
+
+#[derive(Accounts)]
+pub struct AdminAction<'info> {
+    pub admin: Signer<'info>,
+    
+    #[account(
+        mut,
+        has_one = admin @ StakingError::NotAdmin,
+    )]
+    pub pool_state: Account<'info, PoolState>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+    pub new_admin: Signer<'info>,
+    
+    #[account(
+        mut,
+        constraint = pool_state.pending_admin != Pubkey::default() @ StakingError::NotPendingAdmin,
+        constraint = pool_state.pending_admin == new_admin.key() @ StakingError::NotPendingAdmin,
+    )]
+    pub pool_state: Account<'info, PoolState>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeRoles<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    
+    #[account(has_one = admin @ StakingError::NotAdmin)]
+    pub pool_state: Account<'info, PoolState>,
+    
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + Roles::INIT_SPACE,
+        seeds = [b"roles", pool_state.key().as_ref()],
+        bump,
+    )]
+    pub roles: Account<'info, Roles>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetRoles<'info> {
+    pub admin: Signer<'info>,
+    
+    #[account(has_one = admin @ StakingError::NotAdmin)]
+    pub pool_state: Account<'info, PoolState>,
+    
+    #[account(
+        mut,
+        seeds = [b"roles", pool_state.key().as_ref()],
+        bump,
+    )]
+    pub roles: Account<'info, Roles>,
+}
+
+#[derive(Accounts)]
+pub struct PauserAction<'info> {
+    pub pauser: Signer<'info>,
+    
+    #[account(mut)]
+    pub pool_state: Account<'info, PoolState>,
+    
+    #[account(
+        seeds = [b"roles", pool_state.key().as_ref()],
+        bump,
+        constraint = roles.pauser == pauser.key() @ StakingError::NotPauser,
+    )]
+    pub roles: Account<'info, Roles>,
+}
+
+#[derive(Accounts)]
+#[instruction(change: ParamChange)]
+pub struct QueueParamChange<'info> {
+    #[account(mut)]
+    pub rate_setter: Signer<'info>,
+    
+    pub pool_state: Account<'info, PoolState>,
+    
+    #[account(
+        seeds = [b"roles", pool_state.key().as_ref()],
+        bump,
+        constraint = roles.rate_setter == rate_setter.key() @ StakingError::NotRateSetter,
+    )]
+    pub roles: Account<'info, Roles>,
+    
+    #[account(
+        init,
+        payer = rate_setter,
+        space = 8 + PendingChange::INIT_SPACE,
+        seeds = [b"pending_change", pool_state.key().as_ref(), &[change.kind()]],
+        bump,
+    )]
+    pub pending_change: Account<'info, PendingChange>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct PendingParamChange<'info> {
+    #[account(mut)]
+    pub rate_setter: Signer<'info>,
+    
+    #[account(mut)]
+    pub pool_state: Account<'info, PoolState>,
+    
+    #[account(
+        seeds = [b"roles", pool_state.key().as_ref()],
+        bump,
+        constraint = roles.rate_setter == rate_setter.key() @ StakingError::NotRateSetter,
+    )]
+    pub roles: Account<'info, Roles>,
+    
+    #[account(
+        mut,
+        close = rate_setter,
+        seeds = [b"pending_change", pool_state.key().as_ref(), &[pending_change.change.kind()]],
+        bump,
+    )]
+    pub pending_change: Account<'info, PendingChange>,
+    
+    #[account(
+        mut,
+        constraint = tier_table.pool == pool_state.key() @ StakingError::InvalidTierTable,
+    )]
+    pub tier_table: Option<Account<'info, TierTable>>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeTierTable<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    
+    #[account(
+        mut,
+        has_one = admin @ StakingError::NotAdmin,
+        constraint = pool_state.tier_table == Pubkey::default() @ StakingError::InvalidTierTable,
+    )]
+    pub pool_state: Account<'info, PoolState>,
+    
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + TierTable::INIT_SPACE,
+        seeds = [b"tier_table", pool_state.key().as_ref()],
+        bump,
+    )]
+    pub tier_table: Account<'info, TierTable>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeRarityRoot<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    
+    #[account(
+        mut,
+        has_one = admin @ StakingError::NotAdmin,
+    )]
+    pub pool_state: Account<'info, PoolState>,
+    
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + RarityRoot::INIT_SPACE,
+        seeds = [b"proof", pool_state.key().as_ref()],
+        bump,
+    )]
+    pub rarity_root: Account<'info, RarityRoot>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateRarityRoot<'info> {
+    pub admin: Signer<'info>,
+    
+    #[account(has_one = admin @ StakingError::NotAdmin)]
+    pub pool_state: Account<'info, PoolState>,
+    
+    #[account(
+        mut,
+        seeds = [b"proof", pool_state.key().as_ref()],
+        bump,
+        constraint = rarity_root.key() == pool_state.rarity_root @ StakingError::InvalidRarityRoot,
+    )]
+    pub rarity_root: Account<'info, RarityRoot>,
+}
+
+#[derive(Accounts)]
+pub struct AddTier<'info> {
+    pub admin: Signer<'info>,
+    
+    #[account(has_one = admin @ StakingError::NotAdmin)]
+    pub pool_state: Account<'info, PoolState>,
+    
+    #[account(
+        mut,
+        seeds = [b"tier_table", pool_state.key().as_ref()],
+        bump,
+    )]
+    pub tier_table: Account<'info, TierTable>,
+}
+
+#[derive(Accounts)]
+pub struct TreasurerAction<'info> {
+    pub treasurer: Signer<'info>,
+    
+    #[account(mut)]
+    pub pool_state: Account<'info, PoolState>,
+    
+    #[account(
+        seeds = [b"roles", pool_state.key().as_ref()],
+        bump,
+        constraint = roles.treasurer == treasurer.key() @ StakingError::NotTreasurer,
+    )]
+    pub roles: Account<'info, Roles>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawRewards<'info> {
+    pub treasurer: Signer<'info>,
+    
+    pub pool_state: Account<'info, PoolState>,
+    
+    #[account(constraint = emission_schedule.pool == pool_state.key() @ StakingError::InvalidEmissionSchedule)]
+    pub emission_schedule: Option<Account<'info, EmissionSchedule>>,
+    
+    #[account(
+        seeds = [b"roles", pool_state.key().as_ref()],
+        bump,
+        constraint = roles.treasurer == treasurer.key() @ StakingError::NotTreasurer,
+    )]
+    pub roles: Account<'info, Roles>,
+    
+    #[account(
+        mut,
+        constraint = reward_vault.key() == pool_state.reward_vault @ StakingError::InvalidVault,
+    )]
+    pub reward_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    /// CHECK: PDA signing for the reward vault
+    #[account(
+        seeds = [b"reward_vault_authority", pool_state.key().as_ref()],
+        bump,
+    )]
+    pub reward_vault_authority: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        constraint = treasurer_token_account.mint == pool_state.reward_mint @ StakingError::InvalidMint,
+    )]
+    pub treasurer_token_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    #[account(
+        constraint = reward_mint.key() == pool_state.reward_mint @ StakingError::InvalidMint,
+    )]
+    pub reward_mint: InterfaceAccount<'info, token_interface::Mint>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeRewardVault<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    
+    #[account(
+        mut,
+        has_one = admin @ StakingError::NotAdmin,
+    )]
+    pub pool_state: Account<'info, PoolState>,
+    
+    pub reward_mint: InterfaceAccount<'info, token_interface::Mint>,
+    
+    #[account(
+        init,
+        payer = admin,
+        seeds = [b"reward_vault", pool_state.key().as_ref(), reward_mint.key().as_ref()],
+        bump,
+        token::mint = reward_mint,
+        token::authority = reward_vault_authority,
+        token::token_program = token_program,
+    )]
+    pub reward_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    /// CHECK: PDA signing for the reward vault
+    #[account(
+        seeds = [b"reward_vault_authority", pool_state.key().as_ref()],
+        bump,
+    )]
+    pub reward_vault_authority: UncheckedAccount<'info>,
+    
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct FundRewardPool<'info> {
+    pub treasurer: Signer<'info>,
+    
+    pub pool_state: Account<'info, PoolState>,
+    
+    #[account(
+        seeds = [b"roles", pool_state.key().as_ref()],
+        bump,
+        constraint = roles.treasurer == treasurer.key() @ StakingError::NotTreasurer,
+    )]
+    pub roles: Account<'info, Roles>,
+    
+    #[account(
+        mut,
+        constraint = reward_vault.key() == pool_state.reward_vault @ StakingError::InvalidVault,
+    )]
+    pub reward_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    #[account(
+        mut,
+        constraint = treasurer_token_account.mint == pool_state.reward_mint @ StakingError::InvalidMint,
+        constraint = treasurer_token_account.owner == treasurer.key() @ StakingError::NotOwner,
+    )]
+    pub treasurer_token_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    #[account(
+        constraint = reward_mint.key() == pool_state.reward_mint @ StakingError::InvalidMint,
+    )]
+    pub reward_mint: InterfaceAccount<'info, token_interface::Mint>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeEmissionSchedule<'info> {
+    #[account(mut)]
+    pub rate_setter: Signer<'info>,
+    
+    #[account(mut)]
+    pub pool_state: Account<'info, PoolState>,
+    
+    #[account(
+        seeds = [b"roles", pool_state.key().as_ref()],
+        bump,
+        constraint = roles.rate_setter == rate_setter.key() @ StakingError::NotRateSetter,
+    )]
+    pub roles: Account<'info, Roles>,
+    
+    #[account(
+        init,
+        payer = rate_setter,
+        space = 8 + EmissionSchedule::INIT_SPACE,
+        seeds = [b"emission_schedule", pool_state.key().as_ref()],
+        bump,
+    )]
+    pub emission_schedule: Account<'info, EmissionSchedule>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateEmissionSchedule<'info> {
+    pub rate_setter: Signer<'info>,
+    
+    pub pool_state: Account<'info, PoolState>,
+    
+    #[account(
+        seeds = [b"roles", pool_state.key().as_ref()],
+        bump,
+        constraint = roles.rate_setter == rate_setter.key() @ StakingError::NotRateSetter,
+    )]
+    pub roles: Account<'info, Roles>,
+    
+    #[account(
+        mut,
+        seeds = [b"emission_schedule", pool_state.key().as_ref()],
         bump,
-        constraint = stake_info.owner == owner.key() @ StakingError::NotOwner,
-        constraint = stake_info.is_staked == true @ StakingError::NotStaked,
+        constraint = emission_schedule.key() == pool_state.emission_schedule @ StakingError::InvalidEmissionSchedule,
     )]
-    pub stake_info: Account<'info, StakeInfo>,
+    pub emission_schedule: Account<'info, EmissionSchedule>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeRewardStreams<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
     
+    #[account(has_one = admin @ StakingError::NotAdmin)]
     pub pool_state: Account<'info, PoolState>,
+    
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + RewardStreams::INIT_SPACE,
+        seeds = [b"reward_streams", pool_state.key().as_ref()],
+        bump,
+    )]
+    pub reward_streams: Account<'info, RewardStreams>,
+    
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct ProcessAutoCompound<'info> {
+pub struct AddRewardStream<'info> {
     #[account(mut)]
-    pub owner: Signer<'info>,
+    pub admin: Signer<'info>,
     
-    pub nft_mint: Account<'info, Mint>,
+    #[account(has_one = admin @ StakingError::NotAdmin)]
+    pub pool_state: Account<'info, PoolState>,
     
     #[account(
         mut,
-        seeds = [b"stake", nft_mint.key().as_ref()],
+        seeds = [b"reward_streams", pool_state.key().as_ref()],
         bump,
-        constraint = stake_info.owner == owner.key() @ StakingError::NotOwner,
-        constraint = stake_info.is_staked == true @ StakingError::NotStaked,
-        constraint = stake_info.auto_compound == true @ StakingError::AutoCompoundNotEnabled,
     )]
-    pub stake_info: Account<'info, StakeInfo>,
+    pub reward_streams: Account<'info, RewardStreams>,
     
-    pub pool_state: Account<'info, PoolState>,
+    pub reward_mint: InterfaceAccount<'info, token_interface::Mint>,
+    
+    #[account(
+        init,
+        payer = admin,
+        seeds = [b"stream_vault", pool_state.key().as_ref(), reward_mint.key().as_ref()],
+        bump,
+        token::mint = reward_mint,
+        token::authority = reward_vault_authority,
+        token::token_program = token_program,
+    )]
+    pub stream_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    /// CHECK: PDA signing for the reward vaults
+    #[account(
+        seeds = [b"reward_vault_authority", pool_state.key().as_ref()],
+        bump,
+    )]
+    pub reward_vault_authority: UncheckedAccount<'info>,
+    
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub rent: Sysvar<'info, Rent>,
 }
 
 #[derive(Accounts)]
-pub struct SetCompoundFrequency<'info> {
+pub struct InitStakeRewardState<'info> {
     #[account(mut)]
     pub owner: Signer<'info>,
     
     pub nft_mint: Account<'info, Mint>,
     
+    pub pool_state: Account<'info, PoolState>,
+    
     #[account(
-        mut,
-        seeds = [b"stake", nft_mint.key().as_ref()],
+        seeds = [b"stake", pool_state.key().as_ref(), nft_mint.key().as_ref()],
         bump,
         constraint = stake_info.owner == owner.key() @ StakingError::NotOwner,
-        constraint = stake_info.is_staked == true @ StakingError::NotStaked,
     )]
     pub stake_info: Account<'info, StakeInfo>,
+    
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + StakeRewardState::INIT_SPACE,
+        seeds = [b"stake_rewards", stake_info.key().as_ref()],
+        bump,
+    )]
+    pub stake_reward_state: Account<'info, StakeRewardState>,
+    
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct EmergencyUnstakeNft<'info> {
-    #[account(mut)]
-    pub owner: Signer<'info>,
+pub struct ClaimAllRewards<'info> {
+    /// The owner or their delegate
+    pub authority: Signer<'info>,
+    
+    /// CHECK: The stake's owner, matched against stake_info and user_staking_info
+    pub owner: UncheckedAccount<'info>,
     
     pub nft_mint: Account<'info, Mint>,
     
     #[account(
         mut,
-        seeds = [b"stake", nft_mint.key().as_ref()],
+        seeds = [b"stake", pool_state.key().as_ref(), nft_mint.key().as_ref()],
         bump,
         constraint = stake_info.owner == owner.key() @ StakingError::NotOwner,
         constraint = stake_info.is_staked == true @ StakingError::NotStaked,
     )]
     pub stake_info: Account<'info, StakeInfo>,
     
+    #[account(mut)]
+    pub pool_state: Account<'info, PoolState>,
+    
+    #[account(constraint = emission_schedule.pool == pool_state.key() @ StakingError::InvalidEmissionSchedule)]
+    pub emission_schedule: Option<Account<'info, EmissionSchedule>>,
+    
+    #[account(constraint = tier_table.pool == pool_state.key() @ StakingError::InvalidTierTable)]
+    pub tier_table: Option<Account<'info, TierTable>>,
+    
+    #[account(
+        seeds = [b"user_staking", pool_state.key().as_ref(), owner.key().as_ref()],
+        bump,
+        constraint = user_staking_info.is_authorized(authority.key()) @ StakingError::NotOwnerOrDelegate,
+    )]
+    pub user_staking_info: Account<'info, UserStakingInfo>,
+    
+    #[account(
+        seeds = [b"reward_streams", pool_state.key().as_ref()],
+        bump,
+    )]
+    pub reward_streams: Account<'info, RewardStreams>,
+    
+    #[account(
+        mut,
+        seeds = [b"stake_rewards", stake_info.key().as_ref()],
+        bump,
+    )]
+    pub stake_reward_state: Account<'info, StakeRewardState>,
+    
+    #[account(
+        mut,
+        seeds = [b"vesting", pool_state.key().as_ref(), owner.key().as_ref()],
+        bump,
+    )]
+    pub vesting_schedule: Option<Account<'info, VestingSchedule>>,
+    
+    #[account(
+        mut,
+        constraint = reward_vault.key() == pool_state.reward_vault @ StakingError::InvalidVault,
+    )]
+    pub reward_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    /// CHECK: PDA signing for the reward vaults
+    #[account(
+        seeds = [b"reward_vault_authority", pool_state.key().as_ref()],
+        bump,
+    )]
+    pub reward_vault_authority: UncheckedAccount<'info>,
+    
     #[account(
         mut,
-        seeds = [b"user_staking", owner.key().as_ref()],
+        constraint = user_token_account.mint == pool_state.reward_mint @ StakingError::InvalidMint,
+        constraint = user_token_account.key() == user_staking_info.reward_account(&pool_state.reward_mint, &pool_state.reward_token_program) @ StakingError::InvalidRewardDestination,
+    )]
+    pub user_token_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    #[account(
+        constraint = reward_mint.key() == pool_state.reward_mint @ StakingError::InvalidMint,
+    )]
+    pub reward_mint: InterfaceAccount<'info, token_interface::Mint>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct InitVestingSchedule<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    pub pool_state: Account<'info, PoolState>,
+    
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + VestingSchedule::INIT_SPACE,
+        seeds = [b"vesting", pool_state.key().as_ref(), owner.key().as_ref()],
         bump,
     )]
+    pub vesting_schedule: Account<'info, VestingSchedule>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawVested<'info> {
+    /// The owner or their delegate
+    pub authority: Signer<'info>,
+    
+    /// CHECK: The schedule's owner, matched against user_staking_info
+    pub owner: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    pub pool_state: Account<'info, PoolState>,
+    
+    #[account(
+        seeds = [b"user_staking", pool_state.key().as_ref(), owner.key().as_ref()],
+        bump,
+        constraint = user_staking_info.is_authorized(authority.key()) @ StakingError::NotOwnerOrDelegate,
+    )]
     pub user_staking_info: Account<'info, UserStakingInfo>,
     
+    #[account(
+        mut,
+        seeds = [b"vesting", pool_state.key().as_ref(), owner.key().as_ref()],
+        bump,
+    )]
+    pub vesting_schedule: Account<'info, VestingSchedule>,
+    
+    #[account(
+        mut,
+        constraint = reward_vault.key() == pool_state.reward_vault @ StakingError::InvalidVault,
+    )]
+    pub reward_vault: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    /// CHECK: PDA signing for the reward vault
+    #[account(
+        seeds = [b"reward_vault_authority", pool_state.key().as_ref()],
+        bump,
+    )]
+    pub reward_vault_authority: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        constraint = user_token_account.mint == pool_state.reward_mint @ StakingError::InvalidMint,
+        constraint = user_token_account.key() == user_staking_info.reward_account(&pool_state.reward_mint, &pool_state.reward_token_program) @ StakingError::InvalidRewardDestination,
+    )]
+    pub user_token_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+    
+    #[account(
+        constraint = reward_mint.key() == pool_state.reward_mint @ StakingError::InvalidMint,
+    )]
+    pub reward_mint: InterfaceAccount<'info, token_interface::Mint>,
+    
+    /// Receives the haircut of `exit_vesting`
+    #[account(
+        mut,
+        constraint = treasury.key() == pool_state.treasury @ StakingError::InvalidTreasury,
+    )]
+    pub treasury: Option<InterfaceAccount<'info, token_interface::TokenAccount>>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct ViewStake<'info> {
+    pub nft_mint: Account<'info, Mint>,
+    
+    #[account(
+        seeds = [b"stake", pool_state.key().as_ref(), nft_mint.key().as_ref()],
+        bump,
+        constraint = stake_info.is_staked == true @ StakingError::NotStaked,
+    )]
+    pub stake_info: Account<'info, StakeInfo>,
+    
     pub pool_state: Account<'info, PoolState>,
     
-    // Additional accounts needed for token transfers
-    // would be added here (similar to unstake_nft)
+    #[account(constraint = emission_schedule.pool == pool_state.key() @ StakingError::InvalidEmissionSchedule)]
+    pub emission_schedule: Option<Account<'info, EmissionSchedule>>,
+    
+    #[account(constraint = tier_table.pool == pool_state.key() @ StakingError::InvalidTierTable)]
+    pub tier_table: Option<Account<'info, TierTable>>,
+}
+
+/// Pending rewards quote returned by `get_pending_rewards`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct PendingRewardsQuote {
+    pub pending_rewards: u64,      // Accrued since last claim
+    pub accumulated_compound: u64, // Already compounded, paid out on claim
+    pub daily_reward: u64,         // Current daily rate for this NFT
+    pub timestamp: i64,
+}
+
+/// Emergency unstake quote returned by `get_emergency_unstake_quote`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct EmergencyUnstakeQuote {
+    pub earned_rewards: u64,
+    pub penalty_bps: u64,
+    pub penalty_amount: u64,
+    pub final_rewards: u64,
+    pub progress_percentage: u8,
+    pub curve_segment: u8,
+    pub lock_completed: bool,
+    pub timestamp: i64,
+}
+
+/// Reward vault solvency report returned by `get_solvency`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct SolvencyReport {
+    pub vault_balance: u64,
+    pub total_liabilities: u64,   // Settled liabilities plus the bound on unsettled accrual
+    pub settled_liabilities: u64, // Settled rewards not yet paid out
+    pub solvency_ratio_bps: u64, // 10000 = liabilities fully covered
+    pub underfunded_mode: bool,
+}
+
+/// An additional reward token paid alongside the primary reward
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, InitSpace)]
+pub struct RewardStream {
+    pub mint: Pubkey,
+    pub vault: Pubkey,
+    pub reward_rate: u64, // Base daily rate, scaled by tier and multipliers
+    pub start_time: i64,
+    pub end_time: i64,
+}
+
+/// Registry of a pool's additional reward streams
+#[account]
+#[derive(InitSpace)]
+pub struct RewardStreams {
+    pub pool: Pubkey,
+    #[max_len(4)]
+    pub streams: Vec<RewardStream>,
+}
+
+/// Per-stake accrual checkpoints for each reward stream, indexed like `RewardStreams.streams`
+#[account]
+#[derive(InitSpace)]
+pub struct StakeRewardState {
+    pub stake: Pubkey,
+    pub last_settled: [i64; 4],
+}
+
+/// Extended stake info account with new fields
+#[account]
+#[derive(InitSpace)]
+pub struct StakeInfo {
+    pub owner: Pubkey,
+    pub mint: Pubkey,
+    pub staked_at: i64,
+    pub release_date: i64,
+    pub is_staked: bool,
+    pub tier: u8,
+    pub last_claim_time: i64,             // Settlement checkpoint for claim, compound and unstake
+    pub staking_period: u64,
+    pub auto_compound: bool,
+    pub accumulated_compound: u64,
+    
+    // New fields for enhanced staking
+    pub current_time_multiplier: u64,     // Time-based multiplier in basis points (100 = 1%)
+    pub last_multiplier_update: i64,      // Last time the multiplier was updated
+    pub milestones_achieved: u8,          // Bitmap of achieved milestones
+    pub next_milestone_days: u64,         // Days until next milestone
+    pub compound_frequency: u8,           // 0=daily, 1=weekly, 2=monthly, 255=manual
+    pub last_compound_time: i64,          // Last time rewards were auto-compounded
+    pub compound_streak: u16,             // Consecutive successful compounds
+    pub compound_streak_multiplier: u64,  // Bonus from compound streak in basis points
+    pub reward_iou: u64,                  // Shortfall owed from pro-rata claims
+    pub rarity_bonus_bps: u16,            // Per-mint bonus proven against the pool's rarity root
+    pub lock_boost_bps: u16,              // Boost of the lock option chosen at stake time
+    pub last_transfer_time: i64,          // Last transfer_stake, 0 if never transferred
+    pub programmable: bool,               // pNFT locked in the owner's wallet instead of escrowed
+    pub accrual: StakeAccrual,            // This stake's entry in the pool's accrual bound
+}
+
+/// Extended pool state account with new fields
+#[account]
+#[derive(InitSpace)]
+pub struct PoolState {
+    pub admin: Pubkey,
+    pub reward_rate: u64,
+    pub pause_flags: u8,                   // Paused operations, math::pause::PAUSE_* bits
+    pub total_staked: u64,
+    pub common_multiplier: u64,
+    pub rare_multiplier: u64,
+    pub epic_multiplier: u64,
+    pub legendary_multiplier: u64,
+    pub max_nfts_per_user: u8,
+    
+    // New fields for enhanced staking
+    pub time_multiplier_increment: u64,    // Increase per period in basis points (500 = 5%)
+    pub time_multiplier_period_days: u64,  // Period length in days (e.g., 30)
+    pub max_time_multiplier: u64,          // Maximum time multiplier in basis points (5000 = 50%)
+    
+    // Reward vault solvency
+    pub reward_mint: Pubkey,
+    pub reward_vault: Pubkey,
+    pub total_liabilities: u64,            // Credited but unpaid rewards (settled, compounded, milestone, IOUs)
+    pub underfunded_mode: bool,            // Pay claims pro-rata instead of failing when the vault is short
+    pub emission_schedule: Pubkey,         // Active EmissionSchedule, default when rewards use the flat reward_rate
+    
+    // Pool identity
+    pub collection: Pubkey,                // Collection mint this pool stakes, part of the pool PDA seeds
+    pub bump: u8,
+    
+    // Admin rotation
+    pub pending_admin: Pubkey,             // Proposed admin awaiting accept_admin, default when none
+    
+    // Timelock
+    pub change_delay: i64,                 // Minimum seconds between queueing and executing a rate change
+    
+    // Emergency unstake penalty, replaces the fixed bands and emergency_fee_percent
+    #[max_len(10)]
+    pub penalty_curve: Vec<PenaltyPoint>,
+    
+    // Tiers beyond the four built-in multipliers
+    pub tier_table: Pubkey,                // Active TierTable, default when tiers use the multipliers above
+    pub rarity_root: Pubkey,               // Active RarityRoot, default when stakers self-report their tier
+    
+    // Lock periods offered at stake time, replaces long_staking_bonus
+    #[max_len(8)]
+    pub lock_options: Vec<LockOption>,
+    
+    pub transfer_cooldown: i64,            // Minimum seconds held before a stake can be transferred
+    
+    pub vesting: VestingConfig,            // Whether claims vest and on what terms
+    
+    pub reward_token_program: Pubkey,      // SPL Token or Token-2022, owner of the reward mint
+    
+    pub accrual: Accrual,                  // Bound on rewards staked NFTs accrued but have not settled
+    
+    pub treasury: Pubkey,                  // Reward-mint token account receiving vesting exit haircuts
+}
+
+/// Pool-wide bound on unsettled accrual, see `math::solvency::Accrual`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, InitSpace)]
+pub struct Accrual {
+    pub weight: u128,
+    pub flat_offset: u128,
+    pub scheduled_offset: u128,
+    pub scale_bps: u64,
+}
+
+/// A stake's entry in the pool's `Accrual`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, InitSpace)]
+pub struct StakeAccrual {
+    pub weight: u64,
+    pub flat_seconds: i64,
+    pub scheduled: u128,
+}
+
+/// How claimed rewards vest, see `math::vesting`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, InitSpace)]
+pub struct VestingConfig {
+    pub enabled: bool,
+    pub cliff_seconds: i64,
+    pub duration_seconds: i64,
+    pub early_exit_haircut_bps: u16, // Share of locked rewards forfeited by exit_vesting
+}
+
+/// A user's claimed rewards still vesting in the reward vault
+#[account]
+#[derive(InitSpace)]
+pub struct VestingSchedule {
+    pub pool: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64,     // Principal of the running schedule
+    pub start_time: i64, // Amount-weighted start of the running schedule
+    pub withdrawn: u64,  // Withdrawn or carried out of the running schedule
+    pub carried: u64,    // Unlocked before the last deposit and not yet withdrawn
+}
+
+/// A lock period offered at stake time and its reward boost
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, InitSpace)]
+pub struct LockOption {
+    pub days: u64,
+    pub boost_bps: u16, // Boost on the stake's daily reward in basis points
+}
+
+/// Merkle root of `(mint, tier, bonus_bps)` entries, see `math::rarity`
+#[account]
+#[derive(InitSpace)]
+pub struct RarityRoot {
+    pub pool: Pubkey,
+    pub root: [u8; 32],
+    pub updated_at: i64,
+}
+
+/// A named tier and its reward multiplier
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, InitSpace)]
+pub struct Tier {
+    #[max_len(16)]
+    pub name: String,
+    pub multiplier: u64, // Percent, 100 = 1x
+}
+
+/// Reward multipliers by tier id, indexed by `StakeInfo::tier`
+#[account]
+#[derive(InitSpace)]
+pub struct TierTable {
+    pub pool: Pubkey,
+    #[max_len(16)]
+    pub tiers: Vec<Tier>,
+}
+
+/// One point of the emergency unstake penalty curve
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, InitSpace)]
+pub struct PenaltyPoint {
+    pub progress_percent: u16, // Lock progress in percent of staking_period
+    pub penalty_bps: u16,      // Penalty on earned rewards in basis points
 }
 
-/// Extended stake info account with new fields
+/// Keys allowed to perform each class of privileged action on a pool
 #[account]
-pub struct StakeInfo {
-    pub owner: Pubkey,
-    pub mint: Pubkey,
-    pub staked_at: i64,
-    pub release_date: i64,
-    pub is_staked: bool,
-    pub tier: u8,
-    pub last_claim_time: i64,
-    pub staking_period: u64,
-    pub auto_compound: bool,
-    pub accumulated_compound: u64,
-    
-    // New fields for enhanced staking
-    pub current_time_multiplier: u64,     // Time-based multiplier in basis points (100 = 1%)
-    pub last_multiplier_update: i64,      // Last time the multiplier was updated
-    pub milestones_achieved: u8,          // Bitmap of achieved milestones
-    pub next_milestone_days: u64,         // Days until next milestone
-    pub compound_frequency: u8,           // 0=daily, 1=weekly, 2=monthly, 255=manual
-    pub compound_streak: u16,             // Consecutive successful compounds
-    pub compound_streak_multiplier: u64,  // Bonus from compound streak in basis points
+#[derive(InitSpace)]
+pub struct Roles {
+    pub pool: Pubkey,
+    pub pauser: Pubkey,      // Can pause and unpause the pool
+    pub rate_setter: Pubkey, // Can change reward rates, multipliers and emission schedules
+    pub treasurer: Pubkey,   // Can fund and withdraw from the reward vault
 }
 
-/// Extended pool state account with new fields
+/// A change to the pool's reward economics or its timelock that must wait out the timelock
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, InitSpace)]
+pub enum ParamChange {
+    RewardRate {
+        reward_rate: u64,
+    },
+    TierMultipliers {
+        common: u64, // Percent, 100 = 1x
+        rare: u64,
+        epic: u64,
+        legendary: u64,
+    },
+    TimeMultiplier {
+        increment: u64,      // Basis points per period
+        period_days: u64,
+        max_multiplier: u64, // Cap in basis points
+    },
+    TierMultiplier {
+        tier: u8,        // Index into the pool's TierTable
+        multiplier: u64, // Percent, 100 = 1x
+    },
+    Vesting {
+        config: VestingConfig,
+    },
+    ChangeDelay {
+        delay: i64, // Seconds, at least MIN_CHANGE_DELAY
+    },
+}
+
+/// A queued `ParamChange`, one per kind and pool
 #[account]
-pub struct PoolState {
-    pub admin: Pubkey,
+#[derive(InitSpace)]
+pub struct PendingChange {
+    pub pool: Pubkey,
+    pub change: ParamChange,
+    pub queued_at: i64,
+    pub effective_at: i64,
+}
+
+/// One epoch of an emission campaign, `[start_time, end_time)` at `reward_rate`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, InitSpace)]
+pub struct EmissionEpoch {
+    pub start_time: i64,
+    pub end_time: i64,
     pub reward_rate: u64,
-    pub emergency_fee_percent: u8,
-    pub paused: bool,
-    pub total_staked: u64,
-    pub common_multiplier: u64,
-    pub rare_multiplier: u64,
-    pub epic_multiplier: u64,
-    pub legendary_multiplier: u64,
-    pub long_staking_bonus: u64,
-    pub max_nfts_per_user: u8,
-    
-    // New fields for enhanced staking
-    pub time_multiplier_increment: u64,    // Increase per period in basis points (500 = 5%)
-    pub time_multiplier_period_days: u64,  // Period length in days (e.g., 30)
-    pub max_time_multiplier: u64,          // Maximum time multiplier in basis points (5000 = 50%)
+}
+
+/// Per-epoch reward rates for a pool, e.g. a pre-funded campaign with a declining rate
+#[account]
+#[derive(InitSpace)]
+pub struct EmissionSchedule {
+    pub pool: Pubkey,
+    pub activated_at: i64,         // Time before this accrues at the flat reward_rate
+    #[max_len(24)]
+    pub epochs: Vec<EmissionEpoch>, // Ascending, non-overlapping
 }
 
 /// Extended user staking info account with collection bonus
 #[account]
+#[derive(InitSpace)]
 pub struct UserStakingInfo {
     pub owner: Pubkey,
     pub staked_count: u8,
-    pub staked_mints: Vec<Pubkey>,
+    #[max_len(10)]
+    pub staked_mints: Vec<Pubkey>, // Bounded by the pool's max_nfts_per_user
     
     // New field for collection bonus
     pub collection_bonus: u64, // In basis points (500 = 5%)
+    
+    // Hot wallet allowed to claim, compound and update multipliers, default when none
+    pub delegate: Pubkey,
+    
+    // Token account claims pay into, default for the owner's associated token account
+    pub reward_destination: Pubkey,
+    
+    // Shortfall owed from stakes that left the pool while its vault was short
+    pub reward_iou: u64,
+}
+
+impl UserStakingInfo {
+    /// The token account this user's primary rewards are paid into
+    pub fn reward_account(&self, reward_mint: &Pubkey, token_program: &Pubkey) -> Pubkey {
+        if self.reward_destination == Pubkey::default() {
+            get_associated_token_address_with_program_id(&self.owner, reward_mint, token_program)
+        } else {
+            self.reward_destination
+        }
+    }
+    
+    /// Whether `key` may claim, compound and update multipliers for this user
+    pub fn is_authorized(&self, key: Pubkey) -> bool {
+        key == self.owner || (self.delegate != Pubkey::default() && key == self.delegate)
+    }
 }
 
 /// Custom events for enhanced staking features
+#[event]
+pub struct PoolInitialized {
+    pub pool: Pubkey,
+    pub collection: Pubkey,
+    pub admin: Pubkey,
+    pub reward_rate: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct NftStaked {
+    pub user: Pubkey,
+    pub nft_mint: Pubkey,
+    pub timestamp: i64,
+    pub tier: u8,
+    pub staking_period: u64,
+    pub rarity_bonus_bps: u16,
+    pub programmable: bool,
+}
+
 #[event]
 pub struct MultiplierUpdated {
     pub user: Pubkey,
@@ -682,6 +4826,15 @@ pub struct MilestoneAchieved {
     pub timestamp: i64,
 }
 
+#[event]
+pub struct RewardsCompounded {
+    pub user: Pubkey,
+    pub amount: u64,
+    pub new_total: u64,
+    pub nft_mint: Pubkey,
+    pub timestamp: i64,
+}
+
 #[event]
 pub struct CompoundStreakIncreased {
     pub user: Pubkey,
@@ -708,14 +4861,279 @@ pub struct CollectionBonusUpdated {
     pub timestamp: i64,
 }
 
+#[event]
+pub struct RewardsClaimed {
+    pub user: Pubkey,
+    pub nft_mint: Pubkey,
+    pub amount: u64,    // Received after any transfer fee, or vested
+    pub shortfall: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RewardIouClaimed {
+    pub user: Pubkey,
+    pub pool: Pubkey,
+    pub amount: u64,    // Received after any transfer fee, or vested
+    pub shortfall: u64, // Still owed to the user
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RewardsVested {
+    pub user: Pubkey,
+    pub pool: Pubkey,
+    pub amount: u64,
+    pub locked: u64, // Total still locked after this deposit
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VestedRewardsWithdrawn {
+    pub user: Pubkey,
+    pub pool: Pubkey,
+    pub amount: u64,  // Received after any transfer fee
+    pub haircut: u64, // Forfeited by exit_vesting, 0 for withdraw_vested
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AdminProposed {
+    pub pool: Pubkey,
+    pub admin: Pubkey,
+    pub pending_admin: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AdminChanged {
+    pub pool: Pubkey,
+    pub old_admin: Pubkey,
+    pub new_admin: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RolesUpdated {
+    pub pool: Pubkey,
+    pub pauser: Pubkey,
+    pub rate_setter: Pubkey,
+    pub treasurer: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PoolPauseChanged {
+    pub pool: Pubkey,
+    pub pause_flags: u8,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ChangeDelayUpdated {
+    pub pool: Pubkey,
+    pub delay: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ParamChangeQueued {
+    pub pool: Pubkey,
+    pub change: ParamChange,
+    pub effective_at: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ParamChangeExecuted {
+    pub pool: Pubkey,
+    pub change: ParamChange,
+    pub effective_at: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ParamChangeCancelled {
+    pub pool: Pubkey,
+    pub change: ParamChange,
+    pub effective_at: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RewardsWithdrawn {
+    pub pool: Pubkey,
+    pub treasurer: Pubkey,
+    pub amount: u64,        // Received after any transfer fee
+    pub vault_balance: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct UnderfundedModeChanged {
+    pub admin: Pubkey,
+    pub enabled: bool,
+    pub total_liabilities: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RewardVaultInitialized {
+    pub reward_mint: Pubkey,
+    pub reward_vault: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RewardPoolFunded {
+    pub funder: Pubkey,
+    pub amount: u64,        // Received by the vault after any transfer fee
+    pub vault_balance: u64,
+    pub total_liabilities: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EmissionScheduleUpdated {
+    pub pool: Pubkey,
+    pub epoch_count: u8,
+    pub activated_at: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RewardStreamAdded {
+    pub pool: Pubkey,
+    pub index: u8,
+    pub reward_mint: Pubkey,
+    pub vault: Pubkey,
+    pub reward_rate: u64,
+    pub start_time: i64,
+    pub end_time: i64,
+}
+
+#[event]
+pub struct StreamRewardsClaimed {
+    pub user: Pubkey,
+    pub nft_mint: Pubkey,
+    pub reward_mint: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct NftWithdrawnForfeitingRewards {
+    pub user: Pubkey,
+    pub nft_mint: Pubkey,
+    pub forfeited_liability: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PenaltyCurveUpdated {
+    pub pool: Pubkey,
+    pub point_count: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RewardDestinationChanged {
+    pub user: Pubkey,
+    pub pool: Pubkey,
+    pub destination: Pubkey, // Token account claims now pay into
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DelegateUpdated {
+    pub user: Pubkey,
+    pub pool: Pubkey,
+    pub delegate: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct StakeTransferred {
+    pub nft_mint: Pubkey,
+    pub from: Pubkey,
+    pub to: Pubkey,
+    pub rewards_paid: u64, // Received after any transfer fee, or vested
+    pub shortfall: u64,    // Left owed to the previous owner, see claim_reward_iou
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TransferCooldownUpdated {
+    pub pool: Pubkey,
+    pub cooldown: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TreasuryUpdated {
+    pub pool: Pubkey,
+    pub treasury: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LockExtended {
+    pub user: Pubkey,
+    pub nft_mint: Pubkey,
+    pub staking_period: u64,
+    pub release_date: i64,
+    pub lock_boost_bps: u16,
+    pub settled_rewards: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LockOptionsUpdated {
+    pub pool: Pubkey,
+    pub option_count: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RarityRootUpdated {
+    pub pool: Pubkey,
+    pub root: [u8; 32],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TierAdded {
+    pub pool: Pubkey,
+    pub tier: u8,
+    pub name: String,
+    pub multiplier: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct NftUnstaked {
+    pub user: Pubkey,
+    pub nft_mint: Pubkey,
+    pub earned_rewards: u64,
+    pub rewards_paid: u64,       // Received by the reward account, or vested
+    pub shortfall: u64,          // Left owed to the user, see claim_reward_iou
+    pub timestamp: i64,
+}
+
 #[event]
 pub struct EmergencyUnstaked {
     pub user: Pubkey,
     pub nft_mint: Pubkey,
     pub earned_rewards: u64,
+    pub penalty_bps: u64,
     pub penalty_amount: u64,
     pub final_rewards: u64,
     pub progress_percentage: u8,
+    pub curve_segment: u8,       // Index of the penalty curve point starting the applied segment
+    pub lock_completed: bool,    // Always false: completed stakes leave through unstake_nft
+    pub rewards_paid: u64,       // Received by the reward account, or vested
+    pub shortfall: u64,          // Left owed to the user, see claim_reward_iou
     pub timestamp: i64,
 }
 
@@ -760,4 +5178,121 @@ pub enum StakingError {
     
     #[msg("Invalid milestone")]
     InvalidMilestone,
+    
+    #[msg("Invalid mint")]
+    InvalidMint,
+    
+    #[msg("Invalid vault")]
+    InvalidVault,
+    
+    #[msg("Insufficient reward balance")]
+    InsufficientRewardBalance,
+    
+    #[msg("No rewards to claim")]
+    NoRewardsToClaim,
+    
+    #[msg("Invalid amount")]
+    InvalidAmount,
+    
+    #[msg("Reward vault already initialized")]
+    RewardVaultAlreadyInitialized,
+    
+    #[msg("Invalid emission schedule")]
+    InvalidEmissionSchedule,
+    
+    #[msg("Emission epoch already started and cannot be changed")]
+    EmissionEpochStarted,
+    
+    #[msg("Maximum reward streams reached")]
+    TooManyRewardStreams,
+    
+    #[msg("Invalid reward stream")]
+    InvalidRewardStream,
+    
+    #[msg("Signer is not the pending admin")]
+    NotPendingAdmin,
+    
+    #[msg("Signer does not hold the pauser role")]
+    NotPauser,
+    
+    #[msg("Signer does not hold the rate-setter role")]
+    NotRateSetter,
+    
+    #[msg("Signer does not hold the treasurer role")]
+    NotTreasurer,
+    
+    #[msg("Change must give at least the pool's minimum notice")]
+    ChangeDelayTooShort,
+    
+    #[msg("Queued change is not yet effective")]
+    ChangeNotYetEffective,
+    
+    #[msg("Unknown pause flag")]
+    InvalidPauseFlags,
+    
+    #[msg("Invalid penalty curve")]
+    InvalidPenaltyCurve,
+    
+    #[msg("Tier table does not belong to this pool")]
+    InvalidTierTable,
+    
+    #[msg("Maximum tiers reached")]
+    TooManyTiers,
+    
+    #[msg("Tier name must be 1 to 16 bytes")]
+    InvalidTierName,
+    
+    #[msg("Rarity root does not belong to this pool")]
+    InvalidRarityRoot,
+    
+    #[msg("Tier and bonus do not match the pool's rarity root")]
+    InvalidRarityProof,
+    
+    #[msg("Invalid lock options")]
+    InvalidLockOptions,
+    
+    #[msg("A stake cannot be transferred to its current owner")]
+    InvalidStakeTransfer,
+    
+    #[msg("Stake was staked or transferred too recently")]
+    TransferCooldownActive,
+    
+    #[msg("Signer is neither the owner nor their delegate")]
+    NotOwnerOrDelegate,
+    
+    #[msg("Rewards can only be paid to the user's reward destination")]
+    InvalidRewardDestination,
+    
+    #[msg("Pool vests rewards: pass the user's vesting schedule")]
+    VestingScheduleRequired,
+    
+    #[msg("Invalid vesting config")]
+    InvalidVestingConfig,
+    
+    #[msg("Metadata account does not belong to this mint")]
+    InvalidMetadata,
+    
+    #[msg("Regular NFTs need the escrow token account")]
+    MissingEscrowAccount,
+    
+    #[msg("pNFTs need their edition, token record and the Token Metadata accounts")]
+    MissingPnftAccounts,
+    
+    #[msg("pNFT stakes cannot be transferred")]
+    StakeNotTransferable,
+    
+    #[msg("Only the collection's update authority or the program's upgrade authority can create its pool")]
+    NotPoolCreator,
+    
+    #[msg("NFT is not a verified member of the pool's collection")]
+    NotInCollection,
+    
+    #[msg("Change delay is below the minimum, or lowers the delay without queueing")]
+    InvalidChangeDelay,
+    
+    #[msg("Lock has already expired and cannot be extended")]
+    LockExpired,
+    
+    #[msg("Vesting exit haircuts need the pool's treasury account")]
+    InvalidTreasury,
 }
\ No newline at end of file